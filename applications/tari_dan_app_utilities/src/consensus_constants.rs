@@ -33,6 +33,7 @@ pub struct ConsensusConstants {
     pub max_base_layer_blocks_behind: u64,
     pub num_preshards: NumPreshards,
     pub pacemaker_max_base_time: std::time::Duration,
+    pub max_commands_per_block: usize,
 }
 
 impl ConsensusConstants {
@@ -44,6 +45,7 @@ impl ConsensusConstants {
             max_base_layer_blocks_behind: 5,
             num_preshards: NumPreshards::P64,
             pacemaker_max_base_time: Duration::from_secs(10),
+            max_commands_per_block: 1000,
         }
     }
 }
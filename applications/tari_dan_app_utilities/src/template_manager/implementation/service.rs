@@ -153,6 +153,7 @@ impl<TAddr: NodeAddressable + 'static> TemplateManagerService<TAddr> {
                         .collect(),
                     output: f.output.to_string(),
                     is_mut: f.is_mut,
+                    is_constructor: f.is_constructor,
                 })
                 .collect(),
             version: loaded.template_def().tari_version().to_string(),
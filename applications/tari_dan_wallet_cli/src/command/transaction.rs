@@ -608,6 +608,9 @@ fn display_vec<W: fmt::Write>(writer: &mut W, ty: &Type, result: &InstructionRes
                 },
             }
         },
+        Type::Option(_) => {
+            write!(writer, "{}", serde_json::to_string(&result.indexed).unwrap())?;
+        },
         Type::Tuple(subtypes) => {
             let str = format_tuple(subtypes, result);
             write!(writer, "{}", str)?;
@@ -687,6 +690,9 @@ pub fn print_execution_results(results: &[InstructionResult]) {
                     },
                 }
             },
+            Type::Option(ty) => {
+                println!("Option<{}>: {}", ty, serde_json::to_string(&result.indexed).unwrap());
+            },
             Type::Tuple(subtypes) => {
                 let str = format_tuple(subtypes, result);
                 println!("{}", str);
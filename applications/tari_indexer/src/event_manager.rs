@@ -20,21 +20,33 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    future::Future,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use log::*;
 use tari_crypto::tari_utilities::message_format::MessageFormat;
 use tari_dan_app_utilities::substate_file_cache::SubstateFileCache;
 use tari_dan_common_types::PeerAddress;
+use tari_dan_storage::StorageError;
 use tari_engine_types::{events::Event, substate::SubstateId};
 use tari_epoch_manager::base_layer::EpochManagerHandle;
-use tari_indexer_lib::substate_scanner::SubstateScanner;
+use tari_indexer_lib::{error::IndexerError, substate_scanner::SubstateScanner};
 use tari_template_lib::{
     models::{Metadata, TemplateAddress},
     Hash,
 };
 use tari_transaction::TransactionId;
 use tari_validator_node_rpc::client::TariValidatorNodeRpcClientFactory;
+use tokio::{sync::broadcast, time};
+use tokio_util::sync::CancellationToken;
 
 use crate::substate_storage_sqlite::{
     models::events::NewEvent,
@@ -48,10 +60,50 @@ use crate::substate_storage_sqlite::{
 
 const LOG_TARGET: &str = "tari::indexer::event_manager";
 
+/// The number of events buffered for each subscriber. A subscriber that falls further behind than this misses the
+/// oldest events.
+const EVENT_CHANNEL_CAPACITY: usize = 1000;
+
+/// Options for fetching substate events from validator nodes
+#[derive(Debug, Clone, Copy)]
+pub struct EventScanOptions {
+    /// The maximum time to wait for a single validator node to respond
+    pub request_timeout: Duration,
+    /// The number of other validator nodes to try if a request fails or times out
+    pub max_retries: usize,
+}
+
+impl Default for EventScanOptions {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(10),
+            max_retries: 2,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EventScanError {
+    #[error("Event scan was cancelled")]
+    Cancelled,
+    #[error("Invalid version range: from_version {from_version} is greater than to_version {to_version}")]
+    InvalidVersionRange { from_version: u32, to_version: u32 },
+    #[error("Failed to fetch events from all {} requested peers: {}", errors.len(), display_peer_errors(errors))]
+    AllPeersFailed { errors: Vec<(PeerAddress, String)> },
+    #[error("Storage error: {0}")]
+    StorageError(#[from] StorageError),
+    #[error(transparent)]
+    SubstateScanningError(#[from] IndexerError),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
 pub struct EventManager {
     substate_store: SqliteSubstateStore,
     substate_scanner:
         Arc<SubstateScanner<EpochManagerHandle<PeerAddress>, TariValidatorNodeRpcClientFactory, SubstateFileCache>>,
+    event_sender: broadcast::Sender<Event>,
+    lagged_event_count: AtomicU64,
 }
 
 impl EventManager {
@@ -61,12 +113,36 @@ impl EventManager {
             SubstateScanner<EpochManagerHandle<PeerAddress>, TariValidatorNodeRpcClientFactory, SubstateFileCache>,
         >,
     ) -> Self {
+        let (event_sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
         Self {
             substate_store,
             substate_scanner,
+            event_sender,
+            lagged_event_count: AtomicU64::new(0),
         }
     }
 
+    /// Returns a sender that publishes newly ingested events to all subscribers
+    pub fn event_sender(&self) -> broadcast::Sender<Event> {
+        self.event_sender.clone()
+    }
+
+    /// Subscribes to newly ingested events. Publishing never waits for subscribers: if a subscriber falls behind, the
+    /// oldest events are dropped and the receiver reports how many were missed.
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.event_sender.subscribe()
+    }
+
+    /// Records that a subscriber missed `count` events because it fell behind
+    pub fn record_lagged_events(&self, count: u64) {
+        self.lagged_event_count.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// The total number of events dropped across all subscribers because they fell behind
+    pub fn lagged_event_count(&self) -> u64 {
+        self.lagged_event_count.load(Ordering::Relaxed)
+    }
+
     pub fn save_event_to_db(
         &self,
         substate_id: &SubstateId,
@@ -82,17 +158,32 @@ impl EventManager {
                 substate_id: Some(substate_id.to_string()),
                 template_address: template_address.to_string(),
                 tx_hash: tx_hash.to_string(),
-                topic,
+                topic: topic.clone(),
                 payload: payload.to_json().expect("Failed to convert to JSON"),
                 version: version as i32,
                 timestamp: timestamp as i64,
             };
             tx.save_event(new_event)
         })?;
+
+        // An error only means that there are no subscribers
+        let _ignore = self.event_sender.send(Event::new(
+            Some(substate_id.clone()),
+            template_address,
+            Hash::from_array(tx_hash.into_array()),
+            topic,
+            payload.clone(),
+        ));
         Ok(())
     }
 
-    pub async fn scan_events_for_transaction(&self, tx_id: TransactionId) -> Result<Vec<Event>, anyhow::Error> {
+    /// Returns the events for the transaction, fetching them from the network if none are stored locally. Returns
+    /// [EventScanError::Cancelled] if `cancel` is triggered before the scan completes.
+    pub async fn scan_events_for_transaction(
+        &self,
+        tx_id: TransactionId,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Event>, EventScanError> {
         let events = {
             let mut tx = self.substate_store.create_read_tx()?;
             tx.get_events_for_transaction(tx_id)?
@@ -105,36 +196,54 @@ impl EventManager {
 
         // If we have no events locally, fetch from the network if possible.
         if events.is_empty() {
-            let network_events = self.substate_scanner.get_events_for_transaction(tx_id).await?;
+            let network_events =
+                run_cancellable(cancel, self.substate_scanner.get_events_for_transaction(tx_id)).await?;
             events.extend(network_events);
         }
 
         Ok(events)
     }
 
+    /// Returns the events for the substate with a version in the range `from_version..=to_version`, fetching any
+    /// versions not stored locally from the network. If `from_version` is None the range starts at version 0, and if
+    /// `to_version` is None all later versions are returned. Returns [EventScanError::Cancelled] if `cancel` is
+    /// triggered before the scan completes.
+    ///
+    /// Each version is requested from one committee member at a time. A request that fails or does not complete
+    /// within `options.request_timeout` is retried with up to `options.max_retries` other members, and
+    /// [EventScanError::AllPeersFailed] is returned if none of them return the events and any of them failed.
     pub async fn scan_events_for_substate_from_network(
         &self,
         substate_id: SubstateId,
-        version: Option<u32>,
-    ) -> Result<Vec<Event>, anyhow::Error> {
+        from_version: Option<u32>,
+        to_version: Option<u32>,
+        options: EventScanOptions,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Event>, EventScanError> {
+        let from_version = from_version.unwrap_or_default();
+        check_version_range(from_version, to_version)?;
+
         let mut events = vec![];
-        let version = version.unwrap_or_default();
 
         // check if database contains the events for this transaction, by querying
         // what is the latest version for the given component_address
         let stored_versions_in_db;
         {
             let mut tx = self.substate_store.create_read_tx()?;
-            stored_versions_in_db = tx.get_stored_versions_of_events(&substate_id, version)?;
+            stored_versions_in_db = tx
+                .get_stored_versions_of_events(&substate_id, from_version)?
+                .into_iter()
+                .collect::<HashSet<_>>();
 
-            let stored_events = match tx.get_all_events(&substate_id) {
+            let stored_events = match tx.get_events_for_substate(&substate_id, from_version, to_version) {
                 Ok(events) => events,
                 Err(e) => {
                     info!(
                         target: LOG_TARGET,
-                        "Failed to get all events for substate_id = {}, version = {} with error = {}",
+                        "Failed to get events for substate_id = {}, versions = {}..={:?} with error = {}",
                         substate_id,
-                        version,
+                        from_version,
+                        to_version,
                         e
                     );
                     return Err(e.into());
@@ -144,39 +253,58 @@ impl EventManager {
             let stored_events = stored_events
                 .iter()
                 .map(|e| e.clone().try_into())
-                .collect::<Result<Vec<_>, _>>()?;
+                .collect::<Result<Vec<_>, anyhow::Error>>()?;
             events.extend(stored_events);
         }
 
-        for v in 0..version {
-            if stored_versions_in_db.contains(&v) {
-                continue;
+        // Fetch every version in the range that is not stored, including gaps between stored versions
+        for version in versions_to_fetch(from_version, to_version, &stored_versions_in_db) {
+            if cancel.is_cancelled() {
+                return Err(EventScanError::Cancelled);
+            }
+            match self
+                .fetch_events_for_substate_version(&substate_id, version, options, cancel)
+                .await?
+            {
+                Some(network_version_events) => events.extend(network_version_events),
+                // There are no more versions of the substate
+                None => break,
             }
-            let network_version_events = self
-                .substate_scanner
-                .get_events_for_substate_and_version(&substate_id, v)
-                .await?;
-            events.extend(network_version_events);
-        }
-
-        let latest_version_in_db = stored_versions_in_db.into_iter().max().unwrap_or_default();
-        let version = version.max(latest_version_in_db);
-
-        // check if there are newest events for this component address in the network
-        let network_events = self
-            .substate_scanner
-            .get_events_for_substate(&substate_id, Some(version))
-            .await?;
-        // because the same substate_id with different version
-        // can be processed in the same transaction, we need to avoid
-        // duplicates
-        for (_, event) in network_events {
-            events.push(event);
         }
 
         Ok(events)
     }
 
+    /// Fetches the events for a single substate version from the committee, trying one member at a time. Returns None
+    /// if the members that responded do not have the version.
+    async fn fetch_events_for_substate_version(
+        &self,
+        substate_id: &SubstateId,
+        version: u32,
+        options: EventScanOptions,
+        cancel: &CancellationToken,
+    ) -> Result<Option<Vec<Event>>, EventScanError> {
+        let committee = run_cancellable(
+            cancel,
+            self.substate_scanner
+                .get_shuffled_committee_for_substate(substate_id, version),
+        )
+        .await?;
+
+        fetch_events_from_peers(
+            committee.iter().take(options.max_retries.saturating_add(1)),
+            substate_id,
+            version,
+            options,
+            cancel,
+            |vn_addr| {
+                self.substate_scanner
+                    .get_events_for_substate_and_version_from_vn(vn_addr, substate_id, version)
+            },
+        )
+        .await
+    }
+
     pub async fn scan_events_by_payload(
         &self,
         payload_key: String,
@@ -221,3 +349,256 @@ impl EventManager {
         Ok(events)
     }
 }
+
+/// Requests the events for a substate version from each peer in turn until one of them responds. Returns None if
+/// the peers that responded do not have the version.
+async fn fetch_events_from_peers<'a, I, F, Fut>(
+    peers: I,
+    substate_id: &SubstateId,
+    version: u32,
+    options: EventScanOptions,
+    cancel: &CancellationToken,
+    mut request: F,
+) -> Result<Option<Vec<Event>>, EventScanError>
+where
+    I: IntoIterator<Item = &'a PeerAddress>,
+    F: FnMut(&'a PeerAddress) -> Fut,
+    Fut: Future<Output = Result<Vec<Event>, IndexerError>>,
+{
+    let mut num_not_found = 0;
+    let mut errors = vec![];
+    for vn_addr in peers {
+        let result = tokio::select! {
+            _ = cancel.cancelled() => return Err(EventScanError::Cancelled),
+            result = time::timeout(options.request_timeout, request(vn_addr)) => result,
+        };
+
+        match result {
+            Ok(Ok(events)) => return Ok(Some(events)),
+            Ok(Err(IndexerError::NotFoundTransaction(..))) => {
+                // The node may be lagging behind, so we keep asking the other members
+                num_not_found += 1;
+            },
+            Ok(Err(e)) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to get events for {}:v{} from {}: {}", substate_id, version, vn_addr, e
+                );
+                errors.push((*vn_addr, e.to_string()));
+            },
+            Err(_) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Timed out after {:.2?} getting events for {}:v{} from {}",
+                    options.request_timeout,
+                    substate_id,
+                    version,
+                    vn_addr
+                );
+                errors.push((
+                    *vn_addr,
+                    format!("request timed out after {:.2?}", options.request_timeout),
+                ));
+            },
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(EventScanError::AllPeersFailed { errors });
+    }
+
+    Ok(None)
+}
+
+/// Runs the future to completion unless the token is cancelled first
+async fn run_cancellable<F, T, E>(cancel: &CancellationToken, future: F) -> Result<T, EventScanError>
+where
+    F: Future<Output = Result<T, E>>,
+    EventScanError: From<E>,
+{
+    tokio::select! {
+        _ = cancel.cancelled() => Err(EventScanError::Cancelled),
+        result = future => Ok(result?),
+    }
+}
+
+fn check_version_range(from_version: u32, to_version: Option<u32>) -> Result<(), EventScanError> {
+    match to_version {
+        Some(to_version) if from_version > to_version => Err(EventScanError::InvalidVersionRange {
+            from_version,
+            to_version,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the versions from `from_version` up to and including `to_version` (unbounded if None) that are not stored
+fn versions_to_fetch(
+    from_version: u32,
+    to_version: Option<u32>,
+    stored_versions: &HashSet<u32>,
+) -> impl Iterator<Item = u32> + '_ {
+    (from_version..=to_version.unwrap_or(u32::MAX)).filter(move |version| !stored_versions.contains(version))
+}
+
+fn display_peer_errors(errors: &[(PeerAddress, String)]) -> String {
+    errors
+        .iter()
+        .map(|(peer, err)| format!("{peer}: {err}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future;
+
+    use tari_dan_common_types::NodeAddressable;
+
+    use super::*;
+
+    fn substate_id() -> SubstateId {
+        SubstateId::from_str(&format!("component_{:056x}", 0)).unwrap()
+    }
+
+    #[test]
+    fn it_rejects_a_from_version_greater_than_the_to_version() {
+        let err = check_version_range(5, Some(4)).unwrap_err();
+        assert!(matches!(err, EventScanError::InvalidVersionRange {
+            from_version: 5,
+            to_version: 4
+        }));
+        check_version_range(4, Some(4)).unwrap();
+        check_version_range(5, None).unwrap();
+    }
+
+    #[test]
+    fn it_fetches_every_version_in_the_range_when_none_are_stored() {
+        let versions = versions_to_fetch(2, Some(5), &HashSet::new()).collect::<Vec<_>>();
+        assert_eq!(versions, [2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn it_fills_gaps_between_stored_versions() {
+        let stored = HashSet::from([0, 1, 3, 6]);
+        let versions = versions_to_fetch(0, Some(7), &stored).collect::<Vec<_>>();
+        assert_eq!(versions, [2, 4, 5, 7]);
+
+        let versions = versions_to_fetch(2, None, &stored).take(4).collect::<Vec<_>>();
+        assert_eq!(versions, [2, 4, 5, 7]);
+    }
+
+    #[tokio::test]
+    async fn it_stops_a_cancelled_scan() {
+        let peers = [PeerAddress::zero(), PeerAddress::zero()];
+        let cancel = CancellationToken::new();
+        let options = EventScanOptions {
+            request_timeout: Duration::from_secs(10),
+            max_retries: 1,
+        };
+
+        tokio::spawn({
+            let cancel = cancel.clone();
+            async move {
+                time::sleep(Duration::from_millis(10)).await;
+                cancel.cancel();
+            }
+        });
+        let mut num_requests = 0;
+        let err = fetch_events_from_peers(&peers, &substate_id(), 0, options, &cancel, |_| {
+            num_requests += 1;
+            future::pending()
+        })
+        .await
+        .unwrap_err();
+
+        assert!(matches!(err, EventScanError::Cancelled));
+        // The second peer is never asked
+        assert_eq!(num_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn it_fails_a_peer_that_does_not_respond_in_time() {
+        let peers = [PeerAddress::zero()];
+        let cancel = CancellationToken::new();
+        let options = EventScanOptions {
+            request_timeout: Duration::from_millis(10),
+            max_retries: 0,
+        };
+
+        let err = fetch_events_from_peers(&peers, &substate_id(), 0, options, &cancel, |_| future::pending())
+            .await
+            .unwrap_err();
+
+        let EventScanError::AllPeersFailed { errors } = err else {
+            panic!("expected AllPeersFailed, got {:?}", err);
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, PeerAddress::zero());
+        assert!(errors[0].1.contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn it_retries_with_the_next_peer_after_a_timeout_or_error() {
+        let peers = [PeerAddress::zero(), PeerAddress::zero(), PeerAddress::zero()];
+        let cancel = CancellationToken::new();
+        let options = EventScanOptions {
+            request_timeout: Duration::from_millis(10),
+            max_retries: 2,
+        };
+
+        let mut num_requests = 0;
+        let events = fetch_events_from_peers(&peers, &substate_id(), 0, options, &cancel, |_| {
+            num_requests += 1;
+            let n = num_requests;
+            async move {
+                match n {
+                    1 => future::pending().await,
+                    2 => Err(IndexerError::ValidatorNodeClientError("connection reset".to_string())),
+                    _ => Ok(vec![]),
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(events, Some(vec![]));
+        assert_eq!(num_requests, 3);
+    }
+
+    #[tokio::test]
+    async fn it_returns_all_peers_failed_if_any_peer_errors() {
+        let peers = [PeerAddress::zero(), PeerAddress::zero()];
+        let cancel = CancellationToken::new();
+        let options = EventScanOptions {
+            request_timeout: Duration::from_secs(10),
+            max_retries: 1,
+        };
+
+        let mut num_requests = 0;
+        let err = fetch_events_from_peers(&peers, &substate_id(), 0, options, &cancel, |_| {
+            num_requests += 1;
+            future::ready(if num_requests == 1 {
+                Err(IndexerError::NotFoundTransaction(substate_id(), 0))
+            } else {
+                Err(IndexerError::ValidatorNodeClientError("connection reset".to_string()))
+            })
+        })
+        .await
+        .unwrap_err();
+
+        let EventScanError::AllPeersFailed { errors } = err else {
+            panic!("expected AllPeersFailed, got {:?}", err);
+        };
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].1.contains("connection reset"));
+
+        // No events is not an error if every peer responded
+        let events = fetch_events_from_peers(&peers, &substate_id(), 0, options, &cancel, |_| {
+            future::ready(Err(IndexerError::NotFoundTransaction(substate_id(), 0)))
+        })
+        .await
+        .unwrap();
+        assert!(events.is_none());
+    }
+}
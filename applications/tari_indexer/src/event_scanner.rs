@@ -40,6 +40,7 @@ use tari_epoch_manager::EpochManagerReader;
 use tari_template_lib::models::{EntityId, TemplateAddress};
 use tari_transaction::{Transaction, TransactionId};
 use tari_validator_node_rpc::client::{TariValidatorNodeRpcClientFactory, ValidatorNodeClientFactory};
+use tokio::sync::broadcast;
 
 use crate::{
     config::EventFilterConfig,
@@ -100,6 +101,7 @@ pub struct EventScanner {
     client_factory: TariValidatorNodeRpcClientFactory,
     substate_store: SqliteSubstateStore,
     event_filters: Vec<EventFilter>,
+    event_sender: broadcast::Sender<Event>,
 }
 
 impl EventScanner {
@@ -109,6 +111,7 @@ impl EventScanner {
         client_factory: TariValidatorNodeRpcClientFactory,
         substate_store: SqliteSubstateStore,
         event_filters: Vec<EventFilter>,
+        event_sender: broadcast::Sender<Event>,
     ) -> Self {
         Self {
             network,
@@ -116,6 +119,7 @@ impl EventScanner {
             client_factory,
             substate_store,
             event_filters,
+            event_sender,
         }
     }
 
@@ -230,6 +234,7 @@ impl EventScanner {
         transaction: TransactionMetadata,
     ) -> Result<(), anyhow::Error> {
         let mut tx = self.substate_store.create_write_tx()?;
+        let mut saved_events = Vec::with_capacity(events_data.len());
 
         for data in events_data {
             let event_row = NewEvent {
@@ -261,6 +266,7 @@ impl EventScanner {
                 event_row
             );
             tx.save_event(event_row)?;
+            saved_events.push(data.event.clone());
 
             // store/update the related substate if any
             if let (Some(substate_id), Some(substate)) = (data.event.substate_id(), &data.substate) {
@@ -286,6 +292,11 @@ impl EventScanner {
 
         tx.commit()?;
 
+        // Only publish once the events are committed. An error only means that there are no subscribers.
+        for event in saved_events {
+            let _ignore = self.event_sender.send(event);
+        }
+
         Ok(())
     }
 
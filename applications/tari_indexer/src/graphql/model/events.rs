@@ -20,19 +20,25 @@
 //   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+use std::{collections::BTreeMap, str::FromStr, sync::Arc, time::Duration};
 
-use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+use async_graphql::{Context, EmptyMutation, Object, Schema, SimpleObject, Subscription};
+use futures::{stream, Stream, StreamExt};
 use log::*;
 use serde::{Deserialize, Serialize};
 use tari_engine_types::substate::SubstateId;
 use tari_template_lib::Hash;
 use tari_transaction::TransactionId;
+use tokio::{sync::broadcast::error::RecvError, time};
+use tokio_util::sync::CancellationToken;
 
-use crate::event_manager::EventManager;
+use crate::event_manager::{EventManager, EventScanOptions};
 
 const LOG_TARGET: &str = "tari::indexer::graphql::events";
 
+/// The maximum time a request may spend scanning the network for events before the scan is cancelled
+const EVENT_SCAN_TIMEOUT: Duration = Duration::from_secs(60);
+
 #[derive(SimpleObject, Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Event {
@@ -55,7 +61,22 @@ impl Event {
     }
 }
 
-pub(crate) type EventSchema = Schema<EventQuery, EmptyMutation, EmptySubscription>;
+/// Returns a token that is cancelled once `timeout` has elapsed, or earlier if the token is cancelled by the caller
+fn cancel_after(timeout: Duration) -> CancellationToken {
+    let cancel = CancellationToken::new();
+    tokio::spawn({
+        let cancel = cancel.clone();
+        async move {
+            tokio::select! {
+                _ = time::sleep(timeout) => cancel.cancel(),
+                _ = cancel.cancelled() => {},
+            }
+        }
+    });
+    cancel
+}
+
+pub(crate) type EventSchema = Schema<EventQuery, EmptyMutation, EventSubscription>;
 
 pub struct EventQuery;
 
@@ -69,14 +90,17 @@ impl EventQuery {
         info!(target: LOG_TARGET, "Querying events for transaction hash = {}", tx_hash);
         let event_manager = ctx.data_unchecked::<Arc<EventManager>>();
         let tx_id = TransactionId::from_hex(&tx_hash)?;
-        let events = match event_manager.scan_events_for_transaction(tx_id).await {
+        let cancel = cancel_after(EVENT_SCAN_TIMEOUT);
+        // Stops the timer once the request completes or is dropped
+        let _cancel_guard = cancel.clone().drop_guard();
+        let events = match event_manager.scan_events_for_transaction(tx_id, &cancel).await {
             Ok(events) => events,
             Err(e) => {
                 info!(
                     target: LOG_TARGET,
                     "Failed to scan events for transaction {} with error {}", tx_hash, e
                 );
-                return Err(e);
+                return Err(e.into());
             },
         };
 
@@ -92,16 +116,28 @@ impl EventQuery {
         &self,
         ctx: &Context<'_>,
         substate_id: String,
-        version: Option<u32>,
+        from_version: Option<u32>,
+        to_version: Option<u32>,
     ) -> Result<Vec<Event>, anyhow::Error> {
-        let version = version.unwrap_or_default();
         info!(
             target: LOG_TARGET,
-            "Querying events for substate_id = {}, starting from version = {}", substate_id, version
+            "Querying events for substate_id = {}, from_version = {:?}, to_version = {:?}",
+            substate_id,
+            from_version,
+            to_version
         );
         let event_manager = ctx.data_unchecked::<Arc<EventManager>>();
+        let cancel = cancel_after(EVENT_SCAN_TIMEOUT);
+        // Stops the timer once the request completes or is dropped
+        let _cancel_guard = cancel.clone().drop_guard();
         let events = event_manager
-            .scan_events_for_substate_from_network(SubstateId::from_str(&substate_id)?, Some(version))
+            .scan_events_for_substate_from_network(
+                SubstateId::from_str(&substate_id)?,
+                from_version,
+                to_version,
+                EventScanOptions::default(),
+                &cancel,
+            )
             .await?
             .iter()
             .map(|e| Event::from_engine_event(e.clone()))
@@ -198,3 +234,39 @@ impl EventQuery {
         })
     }
 }
+
+pub struct EventSubscription;
+
+#[Subscription]
+impl EventSubscription {
+    /// Streams events as they are ingested by the indexer, optionally only those with the given topic. A subscriber
+    /// that falls behind misses the oldest events rather than holding up ingestion.
+    pub async fn events(&self, ctx: &Context<'_>, topic: Option<String>) -> impl Stream<Item = Event> {
+        let event_manager = ctx.data_unchecked::<Arc<EventManager>>().clone();
+        let receiver = event_manager.subscribe();
+
+        stream::unfold((receiver, event_manager), |(mut receiver, event_manager)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, (receiver, event_manager))),
+                    Err(RecvError::Lagged(count)) => {
+                        warn!(target: LOG_TARGET, "Event subscriber fell behind and missed {} event(s)", count);
+                        event_manager.record_lagged_events(count);
+                    },
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+        .filter_map(move |event| {
+            let is_match = topic.as_ref().map_or(true, |topic| event.topic() == *topic);
+            let event = is_match
+                .then(|| Event::from_engine_event(event))
+                .transpose()
+                .unwrap_or_else(|e| {
+                    warn!(target: LOG_TARGET, "Failed to convert event for subscriber: {}", e);
+                    None
+                });
+            async move { event }
+        })
+    }
+}
@@ -25,10 +25,9 @@ use std::{net::SocketAddr, sync::Arc};
 use async_graphql::{
     http::{playground_source, GraphQLPlaygroundConfig},
     EmptyMutation,
-    EmptySubscription,
     Schema,
 };
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
 use axum::{
     extract::Extension,
     http::StatusCode,
@@ -42,7 +41,7 @@ use serde::Serialize;
 use tower_http::cors::CorsLayer;
 
 use crate::{
-    graphql::model::events::{EventQuery, EventSchema},
+    graphql::model::events::{EventQuery, EventSchema, EventSubscription},
     substate_manager::SubstateManager,
     EventManager,
 };
@@ -54,12 +53,13 @@ pub async fn run_graphql(
     substate_manager: Arc<SubstateManager>,
     event_manager: Arc<EventManager>,
 ) -> Result<(), anyhow::Error> {
-    let schema = Schema::build(EventQuery, EmptyMutation, EmptySubscription)
+    let schema = Schema::build(EventQuery, EmptyMutation, EventSubscription)
         .data(substate_manager)
         .data(event_manager)
         .finish();
     let router = Router::new()
         .route("/", get(graphql_playground).post(graphql_handler))
+        .route_service("/ws", GraphQLSubscription::new(schema.clone()))
         .route("/health", get(health))
         .layer(CorsLayer::permissive())
         .layer(Extension(schema));
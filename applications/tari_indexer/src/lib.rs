@@ -185,6 +185,7 @@ pub async fn run_indexer(config: ApplicationConfig, mut shutdown_signal: Shutdow
         services.validator_node_client_factory.clone(),
         services.substate_store.clone(),
         event_filters,
+        event_manager.event_sender(),
     ));
 
     // Run the GraphQL API
@@ -214,7 +214,14 @@ pub trait SubstateStoreReadTransaction {
     #[allow(dead_code)]
     fn get_events_by_version(&mut self, substate_id: &SubstateId, version: u32)
         -> Result<Vec<EventData>, StorageError>;
-    fn get_all_events(&mut self, substate_id: &SubstateId) -> Result<Vec<EventData>, StorageError>;
+    /// Returns the stored events for the substate with a version in the range `from_version..=to_version`. If
+    /// `to_version` is None, all versions from `from_version` are returned.
+    fn get_events_for_substate(
+        &mut self,
+        substate_id: &SubstateId,
+        from_version: u32,
+        to_version: Option<u32>,
+    ) -> Result<Vec<EventData>, StorageError>;
     fn get_events_by_payload(
         &mut self,
         payload_key: String,
@@ -527,13 +534,24 @@ impl SubstateStoreReadTransaction for SqliteSubstateStoreReadTransaction<'_> {
         Ok(res)
     }
 
-    fn get_all_events(&mut self, substate_id: &SubstateId) -> Result<Vec<EventData>, StorageError> {
-        let res = sql_query("SELECT substate_id, tx_hash, topic, payload FROM events WHERE substate_id = ?")
-            .bind::<Text, _>(substate_id.to_string())
-            .get_results::<EventData>(self.connection())
-            .map_err(|e| StorageError::QueryError {
-                reason: format!("get_events_by_version: {}", e),
-            })?;
+    fn get_events_for_substate(
+        &mut self,
+        substate_id: &SubstateId,
+        from_version: u32,
+        to_version: Option<u32>,
+    ) -> Result<Vec<EventData>, StorageError> {
+        let res = sql_query(
+            "SELECT substate_id, template_address, tx_hash, topic, payload, version FROM events WHERE substate_id = ? \
+             AND version >= ? AND (? IS NULL OR version <= ?) ORDER BY version ASC, id ASC",
+        )
+        .bind::<Text, _>(substate_id.to_string())
+        .bind::<Integer, _>(from_version as i32)
+        .bind::<Nullable<Integer>, _>(to_version.map(|v| v as i32))
+        .bind::<Nullable<Integer>, _>(to_version.map(|v| v as i32))
+        .get_results::<EventData>(self.connection())
+        .map_err(|e| StorageError::QueryError {
+            reason: format!("get_events_for_substate: {}", e),
+        })?;
         Ok(res)
     }
 
@@ -37,6 +37,11 @@ pub enum ProcessManagerRequest {
         instance_id: InstanceId,
         reply: Reply<()>,
     },
+    ResetValidatorNodeDatabase {
+        instance_id: InstanceId,
+        keep_identity: bool,
+        reply: Reply<Vec<PathBuf>>,
+    },
     MineBlocks {
         blocks: u64,
         reply: Reply<()>,
@@ -135,6 +140,11 @@ impl ProcessManagerHandle {
         Ok(intances.into_iter().find(|i| i.name == name))
     }
 
+    pub async fn get_instance(&self, instance_id: InstanceId) -> anyhow::Result<Option<InstanceInfo>> {
+        let instances = self.list_instances(None).await?;
+        Ok(instances.into_iter().find(|i| i.id == instance_id))
+    }
+
     // pub async fn list_minotari_nodes(&self) -> anyhow::Result<Vec<InstanceInfo>> {
     //     self.list_instances(Some(InstanceType::MinoTariNode)).await
     // }
@@ -216,6 +226,23 @@ impl ProcessManagerHandle {
         rx_reply.await?
     }
 
+    pub async fn reset_validator_node_database(
+        &self,
+        instance_id: InstanceId,
+        keep_identity: bool,
+    ) -> anyhow::Result<Vec<PathBuf>> {
+        let (tx_reply, rx_reply) = oneshot::channel();
+        self.tx_request
+            .send(ProcessManagerRequest::ResetValidatorNodeDatabase {
+                instance_id,
+                keep_identity,
+                reply: tx_reply,
+            })
+            .await?;
+
+        rx_reply.await?
+    }
+
     pub async fn register_validator_node(&self, instance_id: InstanceId) -> anyhow::Result<()> {
         let (tx_reply, rx_reply) = oneshot::channel();
         self.tx_request
@@ -4,7 +4,7 @@
 use std::{
     collections::HashMap,
     net::{IpAddr, Ipv4Addr},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{ExitStatus, Stdio},
     time::Duration,
 };
@@ -326,6 +326,64 @@ impl InstanceManager {
         Ok(())
     }
 
+    /// Deletes the SQLite database files under a stopped validator node's data directory, returning the paths of the
+    /// removed files. Unless `keep_identity` is set, the node's identity file is removed too so that a new identity is
+    /// created on the next start.
+    pub async fn reset_validator_node_database(
+        &mut self,
+        id: InstanceId,
+        keep_identity: bool,
+    ) -> anyhow::Result<Vec<PathBuf>> {
+        let instance = self
+            .validator_nodes
+            .get_mut(&id)
+            .ok_or_else(|| anyhow!("Validator node instance {id} not found"))?
+            .instance_mut();
+
+        if instance.check_running() {
+            return Err(anyhow!(
+                "Validator node {} must be stopped before its database is reset",
+                instance.name()
+            ));
+        }
+
+        let mut removed = vec![];
+        let data_path = get_definition(instance.instance_type())
+            .get_relative_data_path()
+            .map(|p| instance.base_path().join(p));
+        let mut dirs = data_path.into_iter().collect::<Vec<_>>();
+        while let Some(dir) = dirs.pop() {
+            if !fs::try_exists(&dir).await? {
+                continue;
+            }
+            let mut entries = fs::read_dir(&dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    dirs.push(path);
+                } else if is_sqlite_file(&path) {
+                    fs::remove_file(&path).await?;
+                    removed.push(path);
+                }
+            }
+        }
+
+        if !keep_identity {
+            let identity_file = instance.base_path().join(VALIDATOR_NODE_IDENTITY_FILE);
+            if fs::try_exists(&identity_file).await? {
+                fs::remove_file(&identity_file).await?;
+                removed.push(identity_file);
+            }
+        }
+
+        info!(
+            "Reset database for validator node {}: removed {} file(s)",
+            instance.name(),
+            removed.len()
+        );
+        Ok(removed)
+    }
+
     pub fn instances_mut(&mut self) -> impl Iterator<Item = &mut Instance> {
         self.minotari_nodes
             .values_mut()
@@ -370,3 +428,13 @@ fn forward_logs<R: AsyncRead + Unpin + Send + 'static>(path: PathBuf, reader: R,
         log::debug!(target: "swarm", "Process exited ({target})");
     });
 }
+
+const VALIDATOR_NODE_IDENTITY_FILE: &str = "validator_node_id.json";
+const SQLITE_FILE_SUFFIXES: &[&str] = &[".db", ".sqlite", "-wal", "-shm", "-journal"];
+
+/// Returns true if the path is an SQLite database, or one of its WAL, shared memory or journal files.
+fn is_sqlite_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| SQLITE_FILE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)))
+}
@@ -160,6 +160,19 @@ impl ProcessManager {
                     log::warn!("Request cancelled before response could be sent")
                 }
             },
+            ResetValidatorNodeDatabase {
+                instance_id,
+                keep_identity,
+                reply,
+            } => {
+                let result = self
+                    .instance_manager
+                    .reset_validator_node_database(instance_id, keep_identity)
+                    .await;
+                if reply.send(result).is_err() {
+                    log::warn!("Request cancelled before response could be sent")
+                }
+            },
             MineBlocks { blocks, reply } => {
                 let result = self.mine(blocks).await;
                 if reply.send(result).is_err() {
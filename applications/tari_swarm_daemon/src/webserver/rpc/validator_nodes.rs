@@ -1,12 +1,27 @@
 //   Copyright 2024 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use anyhow::anyhow;
+use axum_jrpc::error::{JsonRpcError, JsonRpcErrorReason};
+use futures::future;
 use serde::{Deserialize, Serialize};
+use tari_validator_node_client::ValidatorNodeClient;
+use tokio::{task, time};
 
-use crate::{config::InstanceType, process_manager::InstanceId, webserver::context::HandlerContext};
+use crate::{
+    config::InstanceType,
+    process_manager::{InstanceId, InstanceInfo},
+    webserver::context::HandlerContext,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListValidatorNodesRequest {}
@@ -22,8 +37,12 @@ pub struct ValidatorNodeInfo {
     pub web: String,
     pub jrpc: String,
     pub is_running: bool,
+    /// Whether the node responded to a JSON-RPC request. None if the node could not be probed.
+    pub is_healthy: Option<bool>,
 }
 
+const HEALTH_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
 pub async fn list(
     context: &HandlerContext,
     _req: ListValidatorNodesRequest,
@@ -46,17 +65,54 @@ pub async fn list(
                 web,
                 jrpc,
                 is_running: instance.is_running,
+                is_healthy: None,
             })
         })
-        .collect::<anyhow::Result<_>>()?;
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let nodes = future::join_all(nodes.into_iter().map(|mut node| async move {
+        node.is_healthy = probe_health(&node).await;
+        node
+    }))
+    .await;
 
     Ok(ListValidatorNodesResponse { nodes })
 }
 
+async fn probe_health(node: &ValidatorNodeInfo) -> Option<bool> {
+    if !node.is_running {
+        return Some(false);
+    }
+
+    let mut client = match ValidatorNodeClient::connect(format!("{}/json_rpc", node.jrpc)) {
+        Ok(client) => client,
+        Err(err) => {
+            log::warn!("Unable to create client to probe validator node {}: {}", node.name, err);
+            return None;
+        },
+    };
+
+    match time::timeout(HEALTH_PROBE_TIMEOUT, client.get_identity()).await {
+        Ok(Ok(_)) => Some(true),
+        Ok(Err(err)) => {
+            log::debug!("Validator node {} health probe failed: {}", node.name, err);
+            Some(false)
+        },
+        Err(_) => {
+            log::debug!("Validator node {} health probe timed out", node.name);
+            Some(false)
+        },
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidatorNodeCreateRequest {
     pub name: String,
     pub register: bool,
+    /// The delay before the first retry if mining blocks after registration fails. The delay doubles for each
+    /// subsequent retry. Defaults to 500ms.
+    #[serde(default)]
+    pub mine_retry_initial_delay_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,8 +131,237 @@ pub async fn create(
 
     if req.register {
         context.process_manager().register_validator_node(instance_id).await?;
-        context.process_manager().mine_blocks(10).await?;
+        let initial_delay = req
+            .mine_retry_initial_delay_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_MINE_RETRY_INITIAL_DELAY);
+        mine_blocks_with_retry(context, 10, initial_delay).await?;
     }
 
     Ok(ValidatorNodeCreateResponse { instance_id })
 }
+
+const MINE_BLOCKS_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_MINE_RETRY_INITIAL_DELAY: Duration = Duration::from_millis(500);
+
+/// Mines blocks, retrying with exponential backoff because the base node may not be ready yet in a fresh swarm.
+async fn mine_blocks_with_retry(
+    context: &HandlerContext,
+    blocks: u64,
+    initial_delay: Duration,
+) -> Result<(), anyhow::Error> {
+    let mut delay = initial_delay;
+    let mut attempt = 1;
+    loop {
+        match context.process_manager().mine_blocks(blocks).await {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt < MINE_BLOCKS_MAX_ATTEMPTS => {
+                log::warn!(
+                    "Failed to mine {blocks} blocks (attempt {attempt}/{MINE_BLOCKS_MAX_ATTEMPTS}): {err}. Retrying \
+                     in {delay:.2?}"
+                );
+                time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            },
+            Err(err) => {
+                return Err(anyhow!(
+                    "Failed to mine {blocks} blocks after {attempt} attempts: {err}"
+                ));
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorNodeStopRequest {
+    pub instance_id: InstanceId,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorNodeStopResponse {
+    pub is_running: bool,
+}
+
+pub async fn stop(
+    context: &HandlerContext,
+    req: ValidatorNodeStopRequest,
+) -> Result<ValidatorNodeStopResponse, anyhow::Error> {
+    get_validator_node(context, req.instance_id).await?;
+    context.process_manager().stop_instance(req.instance_id).await?;
+    let instance = get_validator_node(context, req.instance_id).await?;
+
+    Ok(ValidatorNodeStopResponse {
+        is_running: instance.is_running,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorNodeRestartRequest {
+    pub instance_id: InstanceId,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorNodeRestartResponse {
+    pub is_running: bool,
+}
+
+/// Terminates and relaunches a single validator node, keeping its allocated ports. A node that is not running is
+/// simply started.
+pub async fn restart(
+    context: &HandlerContext,
+    req: ValidatorNodeRestartRequest,
+) -> Result<ValidatorNodeRestartResponse, anyhow::Error> {
+    let instance = get_validator_node(context, req.instance_id).await?;
+    if instance.is_running {
+        context.process_manager().stop_instance(req.instance_id).await?;
+    }
+    context.process_manager().start_instance(req.instance_id).await?;
+    let instance = get_validator_node(context, req.instance_id).await?;
+
+    Ok(ValidatorNodeRestartResponse {
+        is_running: instance.is_running,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorNodeResetRequest {
+    pub instance_id: InstanceId,
+    /// Keep the node's identity file so that it restarts with the same identity
+    pub keep_identity: bool,
+    /// Stop the node first if it is running. Otherwise, resetting a running node is refused.
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorNodeResetResponse {
+    pub removed_files: Vec<PathBuf>,
+    pub is_running: bool,
+}
+
+/// Deletes the SQLite databases of a validator node (and optionally its identity) and starts it again, so that it
+/// begins from a clean state while keeping its allocated ports.
+pub async fn reset(
+    context: &HandlerContext,
+    req: ValidatorNodeResetRequest,
+) -> Result<ValidatorNodeResetResponse, anyhow::Error> {
+    let instance = get_validator_node(context, req.instance_id).await?;
+    if instance.is_running {
+        if !req.force {
+            return Err(JsonRpcError::new(
+                JsonRpcErrorReason::ApplicationError(400),
+                format!(
+                    "Validator node instance {} is running. Stop it first or set force to true",
+                    req.instance_id
+                ),
+                serde_json::Value::Null,
+            )
+            .into());
+        }
+        context.process_manager().stop_instance(req.instance_id).await?;
+    }
+
+    let removed_files = context
+        .process_manager()
+        .reset_validator_node_database(req.instance_id, req.keep_identity)
+        .await?;
+    context.process_manager().start_instance(req.instance_id).await?;
+    let instance = get_validator_node(context, req.instance_id).await?;
+
+    Ok(ValidatorNodeResetResponse {
+        removed_files,
+        is_running: instance.is_running,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorNodeLogsRequest {
+    pub instance_id: InstanceId,
+    /// The maximum number of lines to return from the end of the log
+    pub lines: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorNodeLogsResponse {
+    pub lines: Vec<String>,
+    /// False if the node has not written its log file yet, in which case no lines are returned
+    pub log_file_exists: bool,
+}
+
+/// The application log of a validator node, relative to its base path
+const VALIDATOR_NODE_LOG_FILE: &str = "log/validator-node/dan_layer.log";
+/// The number of bytes read at a time when scanning backwards from the end of a log file
+const TAIL_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// Returns the last lines of a validator node's application log
+pub async fn logs(
+    context: &HandlerContext,
+    req: ValidatorNodeLogsRequest,
+) -> Result<ValidatorNodeLogsResponse, anyhow::Error> {
+    let instance = get_validator_node(context, req.instance_id).await?;
+    let log_file = instance.base_path.join(VALIDATOR_NODE_LOG_FILE);
+    if !log_file.exists() {
+        return Ok(ValidatorNodeLogsResponse {
+            lines: vec![],
+            log_file_exists: false,
+        });
+    }
+
+    let lines = task::spawn_blocking(move || tail_lines(&log_file, req.lines)).await??;
+
+    Ok(ValidatorNodeLogsResponse {
+        lines,
+        log_file_exists: true,
+    })
+}
+
+/// Reads the last `num_lines` lines of the file without reading the whole file, since logs can grow large
+fn tail_lines(path: &Path, num_lines: usize) -> io::Result<Vec<String>> {
+    if num_lines == 0 {
+        return Ok(vec![]);
+    }
+
+    let mut file = File::open(path)?;
+    let len = file.seek(SeekFrom::End(0))?;
+    // Scan backwards until the chunk contains more newlines than requested lines (the extra newline marks the start of
+    // the first line we want) or we reach the start of the file
+    let mut start = len;
+    let mut num_newlines = 0;
+    let mut buf = vec![0u8; TAIL_CHUNK_SIZE as usize];
+    while start > 0 && num_newlines <= num_lines {
+        let chunk_len = TAIL_CHUNK_SIZE.min(start);
+        start -= chunk_len;
+        file.seek(SeekFrom::Start(start))?;
+        let chunk = &mut buf[..chunk_len as usize];
+        file.read_exact(chunk)?;
+        num_newlines += chunk.iter().filter(|b| **b == b'\n').count();
+    }
+
+    file.seek(SeekFrom::Start(start))?;
+    let mut lines = VecDeque::with_capacity(num_lines + 1);
+    for line in BufReader::new(file).split(b'\n') {
+        if lines.len() == num_lines {
+            lines.pop_front();
+        }
+        lines.push_back(String::from_utf8_lossy(&line?).into_owned());
+    }
+
+    Ok(lines.into())
+}
+
+async fn get_validator_node(context: &HandlerContext, instance_id: InstanceId) -> Result<InstanceInfo, anyhow::Error> {
+    let instance = context
+        .process_manager()
+        .get_instance(instance_id)
+        .await?
+        .filter(|i| i.instance_type == InstanceType::TariValidatorNode)
+        .ok_or_else(|| {
+            JsonRpcError::new(
+                JsonRpcErrorReason::ApplicationError(404),
+                format!("Validator node instance {instance_id} not found"),
+                serde_json::Value::Null,
+            )
+        })?;
+    Ok(instance)
+}
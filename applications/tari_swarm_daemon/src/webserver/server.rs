@@ -107,6 +107,10 @@ async fn json_rpc_handler(Extension(context): Extension<Arc<HandlerContext>>, va
         "add_asset_wallet" | "add_wallet_daemon" => call_handler(context, value, rpc::dan_wallets::create).await,
         "add_indexer" => call_handler(context, value, rpc::indexers::create).await,
         "add_validator_node" => call_handler(context, value, rpc::validator_nodes::create).await,
+        "stop_validator_node" => call_handler(context, value, rpc::validator_nodes::stop).await,
+        "restart_validator_node" => call_handler(context, value, rpc::validator_nodes::restart).await,
+        "reset_validator_node" => call_handler(context, value, rpc::validator_nodes::reset).await,
+        "validator_node_logs" => call_handler(context, value, rpc::validator_nodes::logs).await,
         "start" => call_handler(context, value, rpc::instances::start).await,
         "stop" => call_handler(context, value, rpc::instances::stop).await,
         "list_instances" => call_handler(context, value, rpc::instances::list).await,
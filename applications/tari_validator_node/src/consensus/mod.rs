@@ -87,6 +87,7 @@ pub async fn spawn(
         max_base_layer_blocks_ahead: consensus_constants.max_base_layer_blocks_ahead,
         num_preshards: consensus_constants.num_preshards,
         pacemaker_max_base_time: consensus_constants.pacemaker_max_base_time,
+        max_commands_per_block: consensus_constants.max_commands_per_block,
     };
 
     let hotstuff_worker = HotstuffWorker::<TariConsensusSpec>::new(
@@ -2,9 +2,11 @@
 //    SPDX-License-Identifier: BSD-3-Clause
 
 use rand::rngs::OsRng;
+use tari_common::configuration::Network;
 use tari_common_types::types::{FixedHash, PublicKey};
 use tari_consensus::traits::{ValidatorSignatureService, VoteSignatureService};
 use tari_dan_app_utilities::keypair::RistrettoKeypair;
+use tari_dan_common_types::Epoch;
 use tari_dan_storage::consensus_models::{BlockId, QuorumDecision, ValidatorSchnorrSignature, ValidatorSignature};
 
 #[derive(Debug, Clone)]
@@ -32,11 +34,13 @@ impl VoteSignatureService for TariSignatureService {
     fn verify(
         &self,
         signature: &ValidatorSignature,
+        network: Network,
+        epoch: Epoch,
         leaf_hash: &FixedHash,
         block_id: &BlockId,
         decision: &QuorumDecision,
     ) -> bool {
-        let message = self.create_message(leaf_hash, block_id, decision);
+        let message = self.create_message(network, epoch, leaf_hash, block_id, decision);
         signature.verify(message)
     }
 }
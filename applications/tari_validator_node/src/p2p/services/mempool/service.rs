@@ -25,7 +25,10 @@ use std::{collections::HashSet, fmt::Display, iter};
 use log::*;
 use tari_dan_common_types::{optional::Optional, NumPreshards, PeerAddress, ShardGroup, SubstateAddress};
 use tari_dan_p2p::{DanMessage, NewTransactionMessage};
-use tari_dan_storage::{consensus_models::TransactionRecord, StateStore};
+use tari_dan_storage::{
+    consensus_models::{AbortReason, TransactionRecord},
+    StateStore,
+};
 use tari_epoch_manager::{base_layer::EpochManagerHandle, EpochManagerEvent, EpochManagerReader};
 use tari_state_store_sqlite::SqliteStateStore;
 use tari_transaction::{Transaction, TransactionId};
@@ -252,7 +255,10 @@ where TValidator: Validator<Transaction, Context = (), Error = TransactionValida
             let transaction_id = *transaction.id();
             self.state_store.with_write_tx(|tx| {
                 TransactionRecord::new(transaction)
-                    .set_abort(format!("Mempool validation failed: {e}"))
+                    .set_abort(
+                        AbortReason::TransactionValidationFailed,
+                        format!("Mempool validation failed: {e}"),
+                    )
                     .insert(tx)
             })?;
 
@@ -23,6 +23,11 @@ impl Validator<Transaction> for EpochRangeValidator {
     type Error = TransactionValidationError;
 
     fn validate(&self, &current_epoch: &Epoch, transaction: &Transaction) -> Result<(), TransactionValidationError> {
+        if let Err(err) = transaction.validate_epoch_bounds() {
+            warn!(target: LOG_TARGET, "EpochRangeValidator - FAIL: {err}");
+            return Err(err.into());
+        }
+
         if let Some(min_epoch) = transaction.min_epoch() {
             if current_epoch < min_epoch {
                 warn!(target: LOG_TARGET, "EpochRangeValidator - FAIL: Current epoch {current_epoch} less than minimum epoch {min_epoch}.");
@@ -5,7 +5,7 @@ use tari_dan_app_utilities::template_manager::interface::TemplateManagerError;
 use tari_dan_common_types::Epoch;
 use tari_dan_storage::{consensus_models::TransactionPoolError, StorageError};
 use tari_networking::NetworkingError;
-use tari_transaction::TransactionId;
+use tari_transaction::{InvalidEpochRangeError, TransactionId};
 
 use crate::virtual_substate::VirtualSubstateError;
 
@@ -34,6 +34,8 @@ pub enum TransactionValidationError {
     CurrentEpochLessThanMinimum { current_epoch: Epoch, min_epoch: Epoch },
     #[error("Current epoch ({current_epoch}) is greater than maximum epoch ({max_epoch}) required for transaction")]
     CurrentEpochGreaterThanMaximum { current_epoch: Epoch, max_epoch: Epoch },
+    #[error(transparent)]
+    InvalidEpochRange(#[from] InvalidEpochRangeError),
     #[error("Transaction {transaction_id} does not have any inputs")]
     NoInputs { transaction_id: TransactionId },
     #[error("Executed transaction {transaction_id} does not involved any shards")]
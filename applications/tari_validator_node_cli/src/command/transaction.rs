@@ -467,6 +467,9 @@ fn summarize_finalize_result(finalize: &FinalizeResult) {
                     },
                 }
             },
+            Type::Option(ty) => {
+                println!("Option<{}>: {}", ty, serde_json::to_string(&result.indexed).unwrap());
+            },
             Type::Tuple(subtypes) => {
                 let str = format_tuple(subtypes, result);
                 println!("{}", str);
@@ -545,6 +548,9 @@ fn display_vec<W: fmt::Write>(writer: &mut W, ty: &Type, result: &InstructionRes
                 },
             }
         },
+        Type::Option(_) => {
+            write!(writer, "{}", serde_json::to_string(&result.indexed).unwrap())?;
+        },
         Type::Tuple(subtypes) => {
             let str = format_tuple(subtypes, result);
             write!(writer, "{}", str)?;
@@ -1,6 +1,7 @@
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
+export * from "./types/AbortReason";
 export * from "./types/AccessRule";
 export * from "./types/Account";
 export * from "./types/Amount";
@@ -11,6 +12,7 @@ export * from "./types/Block";
 export * from "./types/BucketId";
 export * from "./types/Claims";
 export * from "./types/Command";
+export * from "./types/CommandSummary";
 export * from "./types/CommitteeInfo";
 export * from "./types/CommitteeShardInfo";
 export * from "./types/Committee";
@@ -121,6 +123,7 @@ export * from "./helpers/helpers";
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
+export * from "./types/AbortReason";
 export * from "./types/AccessRule";
 export * from "./types/Account";
 export * from "./types/Amount";
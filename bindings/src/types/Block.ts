@@ -17,6 +17,7 @@ export interface Block {
   proposed_by: string;
   total_leader_fee: number;
   merkle_root: string;
+  command_merkle_root: string;
   commands: Array<Command>;
   is_dummy: boolean;
   is_processed: boolean;
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface CommandSummary {
+  prepare: number;
+  local_prepared: number;
+  accept: number;
+  foreign_proposal: number;
+  local_only: number;
+  end_epoch: number;
+}
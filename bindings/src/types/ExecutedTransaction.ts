@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AbortReason } from "./AbortReason";
 import type { Decision } from "./Decision";
 import type { ExecuteResult } from "./ExecuteResult";
 import type { Transaction } from "./Transaction";
@@ -14,4 +15,5 @@ export interface ExecutedTransaction {
   final_decision: Decision | null;
   finalized_time: { secs: number; nanos: number } | null;
   abort_details: string | null;
+  abort_reason: AbortReason | null;
 }
@@ -7,4 +7,5 @@ export interface FunctionDef {
   arguments: Array<ArgDef>;
   output: Type;
   is_mut: boolean;
+  is_constructor: boolean;
 }
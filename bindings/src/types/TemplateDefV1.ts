@@ -4,5 +4,6 @@ import type { FunctionDef } from "./FunctionDef";
 export interface TemplateDefV1 {
   template_name: string;
   tari_version: string;
+  version: number;
   functions: Array<FunctionDef>;
 }
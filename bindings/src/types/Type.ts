@@ -15,5 +15,6 @@ export type Type =
   | "U128"
   | "String"
   | { Vec: Type }
+  | { Option: Type }
   | { Tuple: Array<Type> }
   | { Other: { name: string } };
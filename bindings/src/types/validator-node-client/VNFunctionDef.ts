@@ -6,4 +6,5 @@ export interface VNFunctionDef {
   arguments: Array<VNArgDef>;
   output: string;
   is_mut: boolean;
+  is_constructor: boolean;
 }
@@ -128,6 +128,7 @@ pub struct FunctionDef {
     pub arguments: Vec<ArgDef>,
     pub output: String,
     pub is_mut: bool,
+    pub is_constructor: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +36,10 @@ pub fn block_hasher() -> TariHasher {
     dan_hasher("Block")
 }
 
+pub fn command_merkle_tree_hasher() -> TariHasher {
+    dan_hasher("CommandMerkleTree")
+}
+
 pub fn quorum_certificate_hasher() -> TariHasher {
     dan_hasher("QuorumCertificate")
 }
@@ -2,11 +2,12 @@
 //   SPDX-License-Identifier: BSD-3-Clause
 
 use tari_common::configuration::Network;
-use tari_dan_common_types::{committee::Committee, DerivableFromPublicKey};
-use tari_dan_storage::consensus_models::Block;
+use tari_dan_common_types::{committee::Committee, vn_node_hash, DerivableFromPublicKey};
+use tari_dan_storage::{consensus_models::Block, StateStoreReadTransaction};
 use tari_epoch_manager::EpochManagerReader;
 
 use crate::{
+    committee_cache::{CachedValidator, CommitteeCache},
     hotstuff::{HotStuffError, HotstuffConfig, ProposalValidationError},
     traits::{ConsensusSpec, LeaderStrategy, VoteSignatureService},
 };
@@ -17,6 +18,7 @@ pub async fn check_proposal<TConsensusSpec: ConsensusSpec>(
     vote_signing_service: &TConsensusSpec::SignatureService,
     leader_strategy: &TConsensusSpec::LeaderStrategy,
     config: &HotstuffConfig,
+    committee_cache: &mut CommitteeCache<TConsensusSpec::Addr>,
 ) -> Result<(), HotStuffError> {
     // TODO: in order to do the base layer block has validation, we need to ensure that we have synced to the tip.
     //       If not, we need some strategy for "parking" the blocks until we are at least at the provided hash or the
@@ -24,12 +26,31 @@ pub async fn check_proposal<TConsensusSpec: ConsensusSpec>(
     // check_base_layer_block_hash::<TConsensusSpec>(block, epoch_manager, config).await?;
     check_network(block, config.network)?;
     check_hash_and_height(block)?;
+    check_merkle_root(block)?;
+    check_command_count(block, config)?;
     let committee_for_block = epoch_manager
         .get_committee_by_validator_public_key(block.epoch(), block.proposed_by())
         .await?;
     check_proposed_by_leader(leader_strategy, &committee_for_block, block)?;
     check_signature(block)?;
-    check_quorum_certificate::<TConsensusSpec>(block, vote_signing_service, epoch_manager).await?;
+    check_quorum_certificate::<TConsensusSpec>(block, vote_signing_service, epoch_manager, committee_cache).await?;
+    Ok(())
+}
+
+/// Runs the structural checks of [check_proposal] against the given committee, without querying the epoch manager. This
+/// allows a block to be verified offline. The quorum certificate is not checked.
+pub fn check_proposal_offline<TAddr: DerivableFromPublicKey, TLeaderStrategy: LeaderStrategy<TAddr>>(
+    block: &Block,
+    config: &HotstuffConfig,
+    leader_strategy: &TLeaderStrategy,
+    committee: &Committee<TAddr>,
+) -> Result<(), ProposalValidationError> {
+    check_network(block, config.network)?;
+    check_hash_and_height(block)?;
+    check_merkle_root(block)?;
+    check_command_count(block, config)?;
+    check_proposed_by_leader(leader_strategy, committee, block)?;
+    check_signature(block)?;
     Ok(())
 }
 
@@ -112,6 +133,31 @@ pub fn check_hash_and_height(candidate_block: &Block) -> Result<(), ProposalVali
     Ok(())
 }
 
+/// Checks that the command merkle root declared in the block matches the root rebuilt from the block's commands.
+pub fn check_merkle_root(candidate_block: &Block) -> Result<(), ProposalValidationError> {
+    let calculated = candidate_block.recompute_merkle_root();
+    if calculated != *candidate_block.command_merkle_root() {
+        return Err(ProposalValidationError::InvalidMerkleRoot {
+            block_id: *candidate_block.id(),
+            calculated,
+            from_block: *candidate_block.command_merkle_root(),
+        });
+    }
+    Ok(())
+}
+
+pub fn check_command_count(candidate_block: &Block, config: &HotstuffConfig) -> Result<(), ProposalValidationError> {
+    if candidate_block.command_count() > config.max_commands_per_block {
+        return Err(ProposalValidationError::TooManyCommands {
+            proposed_by: candidate_block.proposed_by().to_string(),
+            block_id: *candidate_block.id(),
+            command_count: candidate_block.command_count(),
+            max_commands_per_block: config.max_commands_per_block,
+        });
+    }
+    Ok(())
+}
+
 pub fn check_proposed_by_leader<TAddr: DerivableFromPublicKey, TLeaderStrategy: LeaderStrategy<TAddr>>(
     leader_strategy: &TLeaderStrategy,
     local_committee: &Committee<TAddr>,
@@ -152,10 +198,14 @@ pub fn check_signature(candidate_block: &Block) -> Result<(), ProposalValidation
     Ok(())
 }
 
+/// Checks the quorum certificate that justifies the candidate block. Validators that signed the certificate are
+/// looked up through the committee cache, so repeated checks within an epoch do not query the epoch manager for every
+/// signer.
 pub async fn check_quorum_certificate<TConsensusSpec: ConsensusSpec>(
     candidate_block: &Block,
     vote_signing_service: &TConsensusSpec::SignatureService,
     epoch_manager: &TConsensusSpec::EpochManager,
+    committee_cache: &mut CommitteeCache<TConsensusSpec::Addr>,
 ) -> Result<(), HotStuffError> {
     let qc = candidate_block.justify();
     if qc.is_zero() {
@@ -176,45 +226,333 @@ pub async fn check_quorum_certificate<TConsensusSpec: ConsensusSpec>(
         return Err(ProposalValidationError::QuorumWasNotReached { qc: qc.clone() }.into());
     }
 
-    let mut vns = vec![];
+    let mut signers = Vec::with_capacity(qc.signatures().len());
     for signature in qc.signatures() {
-        let vn = epoch_manager
-            .get_validator_node_by_public_key(qc.epoch(), signature.public_key())
-            .await?;
-        let committee_info = epoch_manager
-            .get_committee_info_for_substate(qc.epoch(), vn.shard_key)
+        let signer = committee_cache
+            .get_or_fetch(epoch_manager, qc.epoch(), signature.public_key())
             .await?;
+        signers.push(signer);
+    }
+
+    check_quorum_certificate_signers(candidate_block, vote_signing_service, &signers)?;
+    Ok(())
+}
+
+/// Checks that the block justified by the candidate block's quorum certificate is known to this node, either as a block
+/// or as a parked block. Unlike [check_quorum_certificate], this requires access to the state store.
+pub fn check_justify_block_exists<TTx: StateStoreReadTransaction + ?Sized>(
+    tx: &TTx,
+    candidate_block: &Block,
+) -> Result<(), ProposalValidationError> {
+    let qc = candidate_block.justify();
+    if qc.is_zero() {
+        return Ok(());
+    }
+    if tx.blocks_exists(qc.block_id())? || tx.parked_blocks_exists(qc.block_id())? {
+        return Ok(());
+    }
+    Err(ProposalValidationError::JustifyBlockNotFound {
+        proposed_by: candidate_block.proposed_by().to_string(),
+        block_description: candidate_block.to_string(),
+        justify_block: qc.as_leaf_block(),
+    })
+}
+
+/// Checks the signatures of the candidate block's quorum certificate against the given signers, which must be in the
+/// same order as the signatures. Every signer must belong to the certificate's shard group and there must be enough of
+/// them to reach the committee's quorum threshold.
+pub fn check_quorum_certificate_signers<TAddr, TSignatureService: VoteSignatureService>(
+    candidate_block: &Block,
+    vote_signing_service: &TSignatureService,
+    signers: &[CachedValidator<TAddr>],
+) -> Result<(), ProposalValidationError> {
+    let qc = candidate_block.justify();
+    if signers.len() != qc.signatures().len() {
+        return Err(ProposalValidationError::QCInvalidSignature { qc: qc.clone() });
+    }
+
+    for (signature, signer) in qc.signatures().iter().zip(signers) {
+        let committee_info = &signer.committee_info;
         if committee_info.shard_group() != qc.shard_group() {
             return Err(ProposalValidationError::ValidatorNotInCommittee {
                 validator: signature.public_key().to_string(),
                 expected_shard: qc.shard_group().to_string(),
                 actual_shard: committee_info.shard_group().to_string(),
-            }
-            .into());
+            });
         }
-        vns.push(vn.get_node_hash(candidate_block.network()));
     }
 
-    for (sign, leaf) in qc.signatures().iter().zip(vns.iter()) {
-        let challenge = vote_signing_service.create_message(leaf, qc.block_id(), &qc.decision());
+    for (sign, signer) in qc.signatures().iter().zip(signers) {
+        let leaf = vn_node_hash(
+            candidate_block.network(),
+            &signer.validator.public_key,
+            &signer.validator.shard_key,
+        );
+        let challenge = vote_signing_service.create_message(
+            candidate_block.network(),
+            qc.epoch(),
+            &leaf,
+            qc.block_id(),
+            &qc.decision(),
+        );
         if !sign.verify(challenge) {
-            return Err(ProposalValidationError::QCInvalidSignature { qc: qc.clone() }.into());
+            return Err(ProposalValidationError::QCInvalidSignature { qc: qc.clone() });
         }
     }
-    let committee_shard = epoch_manager
-        .get_committee_info_by_validator_public_key(
-            qc.epoch(),
-            qc.signatures()
-                .first()
-                .ok_or::<HotStuffError>(ProposalValidationError::QuorumWasNotReached { qc: qc.clone() }.into())?
-                .public_key(),
-        )
-        .await?;
 
+    let committee_shard = &signers
+        .first()
+        .ok_or_else(|| ProposalValidationError::QuorumWasNotReached { qc: qc.clone() })?
+        .committee_info;
     if committee_shard.quorum_threshold() >
         u32::try_from(qc.signatures().len()).map_err(|_| ProposalValidationError::QCConversionError)?
     {
-        return Err(ProposalValidationError::QuorumWasNotReached { qc: qc.clone() }.into());
+        return Err(ProposalValidationError::QuorumWasNotReached { qc: qc.clone() });
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, time::Duration};
+
+    use indexmap::IndexMap;
+    use tari_common_types::types::{FixedHash, PrivateKey, PublicKey};
+    use tari_crypto::keys::PublicKey as _;
+    use tari_dan_common_types::{Epoch, NodeHeight, NumPreshards, ShardGroup};
+    use tari_dan_storage::{
+        consensus_models::{
+            BlockId,
+            Command,
+            Decision,
+            QuorumCertificate,
+            QuorumDecision,
+            TransactionAtom,
+            ValidatorSignature,
+        },
+        StateStore,
+        StateStoreWriteTransaction,
+    };
+    use tari_state_store_sqlite::SqliteStateStore;
+    use tari_transaction::TransactionId;
+
+    use super::*;
+
+    const MAX_COMMANDS: usize = 10;
+
+    fn config() -> HotstuffConfig {
+        HotstuffConfig {
+            network: Network::LocalNet,
+            max_base_layer_blocks_ahead: 5,
+            max_base_layer_blocks_behind: 5,
+            num_preshards: NumPreshards::P64,
+            pacemaker_max_base_time: Duration::from_secs(10),
+            max_commands_per_block: MAX_COMMANDS,
+        }
+    }
+
+    fn create_block_with_commands(num_commands: usize) -> Block {
+        create_block_proposed_by(PublicKey::default(), num_commands)
+    }
+
+    fn create_block_proposed_by(proposed_by: PublicKey, num_commands: usize) -> Block {
+        let shard_group = ShardGroup::all_shards(NumPreshards::P64);
+        let commands = (0..num_commands)
+            .map(|i| {
+                let mut id = [0u8; 32];
+                id[..8].copy_from_slice(&(i as u64).to_le_bytes());
+                Command::LocalOnly(TransactionAtom {
+                    id: TransactionId::new(id),
+                    decision: Decision::Commit,
+                    evidence: Default::default(),
+                    transaction_fee: 0,
+                    leader_fee: None,
+                })
+            })
+            .collect::<BTreeSet<_>>();
+        Block::new(
+            Network::LocalNet,
+            BlockId::zero(),
+            QuorumCertificate::genesis(Epoch::zero(), shard_group),
+            NodeHeight(1),
+            Epoch::zero(),
+            shard_group,
+            proposed_by,
+            commands,
+            FixedHash::zero(),
+            0,
+            IndexMap::new(),
+            None,
+            0,
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    #[test]
+    fn it_accepts_a_block_at_the_command_limit() {
+        let block = create_block_with_commands(MAX_COMMANDS);
+        assert_eq!(block.command_count(), MAX_COMMANDS);
+        check_command_count(&block, &config()).unwrap();
+
+        let genesis = Block::genesis(
+            Network::LocalNet,
+            Epoch::zero(),
+            ShardGroup::all_shards(NumPreshards::P64),
+        );
+        check_command_count(&genesis, &config()).unwrap();
+    }
+
+    #[test]
+    fn it_rejects_a_block_over_the_command_limit() {
+        let block = create_block_with_commands(MAX_COMMANDS + 1);
+        let err = check_command_count(&block, &config()).unwrap_err();
+        assert!(matches!(err, ProposalValidationError::TooManyCommands {
+            command_count,
+            max_commands_per_block: MAX_COMMANDS,
+            ..
+        } if command_count == MAX_COMMANDS + 1));
+    }
+
+    #[test]
+    fn it_rejects_a_block_with_a_tampered_command_list() {
+        let block = create_block_with_commands(3);
+        check_merkle_root(&block).unwrap();
+
+        // Drop one of the commands but keep the root that the proposer declared for the original list. The block hash
+        // commits to the declared root, so only the merkle root check detects the tampered commands.
+        let mut tampered = create_block_with_commands(2);
+        tampered.set_command_merkle_root(*block.command_merkle_root());
+        check_hash_and_height(&tampered).unwrap();
+        let err = check_merkle_root(&tampered).unwrap_err();
+        assert!(matches!(err, ProposalValidationError::InvalidMerkleRoot {
+            calculated,
+            from_block,
+            ..
+        } if calculated == tampered.recompute_merkle_root() && from_block == *block.command_merkle_root()));
+    }
+
+    fn create_block_justified_by(parent: &Block, justify: QuorumCertificate) -> Block {
+        Block::new(
+            Network::LocalNet,
+            *parent.id(),
+            justify,
+            parent.height() + NodeHeight(1),
+            Epoch::zero(),
+            parent.shard_group(),
+            PublicKey::default(),
+            BTreeSet::new(),
+            FixedHash::zero(),
+            0,
+            IndexMap::new(),
+            None,
+            0,
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    #[test]
+    fn it_rejects_a_block_that_justifies_an_unknown_block() {
+        let store = SqliteStateStore::<String>::connect(":memory:").unwrap();
+        store.foreign_keys_off().unwrap();
+        let mut tx = store.create_write_tx().unwrap();
+        let zero_block = Block::zero_block(Network::LocalNet, NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        let block = create_block_with_commands(0);
+        block.insert(&mut tx).unwrap();
+
+        let known_qc = QuorumCertificate::new(
+            *block.id(),
+            block.height(),
+            Epoch::zero(),
+            block.shard_group(),
+            vec![],
+            vec![],
+            QuorumDecision::Accept,
+        );
+        let candidate = create_block_justified_by(&block, known_qc);
+        check_justify_block_exists(&*tx, &candidate).unwrap();
+
+        let dangling_qc = QuorumCertificate::new(
+            BlockId::new(FixedHash::from([1u8; 32])),
+            block.height(),
+            Epoch::zero(),
+            block.shard_group(),
+            vec![],
+            vec![],
+            QuorumDecision::Accept,
+        );
+        let candidate = create_block_justified_by(&block, dangling_qc);
+        let err = check_justify_block_exists(&*tx, &candidate).unwrap_err();
+        assert!(matches!(err, ProposalValidationError::JustifyBlockNotFound { .. }));
+
+        tx.rollback().unwrap();
+    }
+
+    struct FirstMemberLeaderStrategy;
+
+    impl LeaderStrategy<PublicKey> for FirstMemberLeaderStrategy {
+        fn calculate_leader(&self, _committee: &Committee<PublicKey>, _height: NodeHeight) -> u32 {
+            0
+        }
+    }
+
+    fn committee_of(public_key: PublicKey) -> Committee<PublicKey> {
+        Committee::new(vec![(public_key.clone(), public_key)])
+    }
+
+    #[test]
+    fn it_checks_a_proposal_offline_against_the_given_committee() {
+        let block = create_block_with_commands(1);
+        let leader_strategy = FirstMemberLeaderStrategy;
+
+        // The block is proposed by the leader but is not signed, so the signature check is reached and fails
+        let err = check_proposal_offline(
+            &block,
+            &config(),
+            &leader_strategy,
+            &committee_of(block.proposed_by().clone()),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ProposalValidationError::MissingSignature { .. }));
+
+        let other_validator = PublicKey::from_secret_key(&PrivateKey::from(1u64));
+        let err =
+            check_proposal_offline(&block, &config(), &leader_strategy, &committee_of(other_validator)).unwrap_err();
+        assert!(matches!(err, ProposalValidationError::NotLeader { .. }));
+
+        let mut config = config();
+        config.network = Network::Igor;
+        let err = check_proposal_offline(
+            &block,
+            &config,
+            &leader_strategy,
+            &committee_of(block.proposed_by().clone()),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ProposalValidationError::InvalidNetwork { .. }));
+    }
+
+    #[test]
+    fn it_accepts_a_valid_proposal_offline() {
+        let secret_key = PrivateKey::from(1u64);
+        let public_key = PublicKey::from_secret_key(&secret_key);
+        let mut block = create_block_proposed_by(public_key.clone(), MAX_COMMANDS);
+        block.set_signature(ValidatorSignature::sign(&secret_key, block.id()).signature);
+
+        check_proposal_offline(&block, &config(), &FirstMemberLeaderStrategy, &committee_of(public_key)).unwrap();
+
+        // A signature by any other key is rejected
+        let other_secret_key = PrivateKey::from(2u64);
+        block.set_signature(ValidatorSignature::sign(&other_secret_key, block.id()).signature);
+        let err = check_proposal_offline(
+            &block,
+            &config(),
+            &FirstMemberLeaderStrategy,
+            &committee_of(block.proposed_by().clone()),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ProposalValidationError::InvalidSignature { .. }));
+    }
+}
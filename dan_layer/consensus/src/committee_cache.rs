@@ -0,0 +1,139 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use std::collections::HashMap;
+
+use tari_common_types::types::PublicKey;
+use tari_dan_common_types::{committee::CommitteeInfo, Epoch, NodeAddressable};
+use tari_dan_storage::global::models::ValidatorNode;
+use tari_epoch_manager::{EpochManagerError, EpochManagerReader};
+
+/// The maximum number of validators held in the cache before it is cleared.
+pub const DEFAULT_COMMITTEE_CACHE_CAPACITY: usize = 1024;
+
+/// A validator together with the committee it belongs to in the cached epoch.
+#[derive(Debug, Clone)]
+pub struct CachedValidator<TAddr> {
+    pub validator: ValidatorNode<TAddr>,
+    pub committee_info: CommitteeInfo,
+}
+
+/// Caches validator and committee lookups for a single epoch, so that validating many quorum certificates in the same
+/// epoch does not query the epoch manager for every signer. The cache is cleared whenever a lookup for a different
+/// epoch is made, or when it reaches its capacity.
+#[derive(Debug, Clone)]
+pub struct CommitteeCache<TAddr> {
+    epoch: Option<Epoch>,
+    capacity: usize,
+    validators: HashMap<PublicKey, CachedValidator<TAddr>>,
+}
+
+impl<TAddr: NodeAddressable> CommitteeCache<TAddr> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            epoch: None,
+            capacity,
+            validators: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, epoch: Epoch, public_key: &PublicKey) -> Option<&CachedValidator<TAddr>> {
+        if self.epoch != Some(epoch) {
+            return None;
+        }
+        self.validators.get(public_key)
+    }
+
+    pub fn insert(&mut self, epoch: Epoch, public_key: PublicKey, validator: CachedValidator<TAddr>) {
+        if self.epoch != Some(epoch) {
+            self.validators.clear();
+            self.epoch = Some(epoch);
+        }
+        if self.validators.len() >= self.capacity && !self.validators.contains_key(&public_key) {
+            self.validators.clear();
+        }
+        self.validators.insert(public_key, validator);
+    }
+
+    /// Returns the cached validator for the public key in the given epoch, fetching it from the epoch manager if it is
+    /// not cached.
+    pub async fn get_or_fetch<TEpochManager: EpochManagerReader<Addr = TAddr>>(
+        &mut self,
+        epoch_manager: &TEpochManager,
+        epoch: Epoch,
+        public_key: &PublicKey,
+    ) -> Result<CachedValidator<TAddr>, EpochManagerError> {
+        if let Some(cached) = self.get(epoch, public_key) {
+            return Ok(cached.clone());
+        }
+
+        let validator = epoch_manager
+            .get_validator_node_by_public_key(epoch, public_key)
+            .await?;
+        let committee_info = epoch_manager
+            .get_committee_info_for_substate(epoch, validator.shard_key)
+            .await?;
+        let cached = CachedValidator {
+            validator,
+            committee_info,
+        };
+        self.insert(epoch, public_key.clone(), cached.clone());
+        Ok(cached)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tari_common_types::types::PrivateKey;
+    use tari_crypto::keys::PublicKey as _;
+    use tari_dan_common_types::{NumPreshards, ShardGroup, SubstateAddress};
+
+    use super::*;
+
+    fn public_key(n: u64) -> PublicKey {
+        PublicKey::from_secret_key(&PrivateKey::from(n))
+    }
+
+    fn cached_validator(public_key: PublicKey) -> CachedValidator<PublicKey> {
+        CachedValidator {
+            validator: ValidatorNode {
+                address: public_key.clone(),
+                public_key: public_key.clone(),
+                shard_key: SubstateAddress::zero(),
+                registered_at_base_height: 0,
+                start_epoch: Epoch(0),
+                end_epoch: Epoch(10),
+                fee_claim_public_key: public_key,
+                sidechain_id: None,
+            },
+            committee_info: CommitteeInfo::new(NumPreshards::P64, 4, 1, ShardGroup::all_shards(NumPreshards::P64)),
+        }
+    }
+
+    #[test]
+    fn it_invalidates_on_epoch_change() {
+        let mut cache = CommitteeCache::new(10);
+        cache.insert(Epoch(1), public_key(1), cached_validator(public_key(1)));
+        assert!(cache.get(Epoch(1), &public_key(1)).is_some());
+        assert!(cache.get(Epoch(2), &public_key(1)).is_none());
+
+        cache.insert(Epoch(2), public_key(2), cached_validator(public_key(2)));
+        assert!(cache.get(Epoch(1), &public_key(1)).is_none());
+        assert!(cache.get(Epoch(2), &public_key(2)).is_some());
+    }
+
+    #[test]
+    fn it_is_bounded_by_its_capacity() {
+        let mut cache = CommitteeCache::new(2);
+        for n in 1..=2 {
+            cache.insert(Epoch(1), public_key(n), cached_validator(public_key(n)));
+        }
+        assert!(cache.get(Epoch(1), &public_key(1)).is_some());
+        assert!(cache.get(Epoch(1), &public_key(2)).is_some());
+
+        // Inserting past the capacity clears the cache
+        cache.insert(Epoch(1), public_key(3), cached_validator(public_key(3)));
+        assert!(cache.get(Epoch(1), &public_key(1)).is_none());
+        assert!(cache.get(Epoch(1), &public_key(3)).is_some());
+    }
+}
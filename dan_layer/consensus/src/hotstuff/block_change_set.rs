@@ -7,6 +7,7 @@ use indexmap::IndexMap;
 use tari_dan_common_types::{shard::Shard, Epoch};
 use tari_dan_storage::{
     consensus_models::{
+        AbortReason,
         Block,
         BlockDiff,
         LeafBlock,
@@ -20,6 +21,7 @@ use tari_dan_storage::{
         TransactionPoolRecord,
         TransactionPoolStage,
         TransactionPoolStatusUpdate,
+        TransactionRecord,
         VersionedStateHashTreeDiff,
     },
     StateStoreReadTransaction,
@@ -129,6 +131,19 @@ impl ProposedBlockChangeSet {
         });
         self
     }
+
+    /// Records the reason that the transaction was aborted in this block. The abort is saved as pending and is only
+    /// applied to the transaction once this block is committed.
+    pub fn set_transaction_abort(
+        &mut self,
+        transaction_id: TransactionId,
+        reason: AbortReason,
+        details: String,
+    ) -> &mut Self {
+        let change_mut = self.transaction_changes.entry(transaction_id).or_default();
+        change_mut.abort = Some((reason, details));
+        self
+    }
 }
 
 impl ProposedBlockChangeSet {
@@ -150,7 +165,7 @@ impl ProposedBlockChangeSet {
         // Save locks
         SubstateRecord::insert_all_locks(tx, self.block.block_id, self.substate_locks)?;
 
-        for change in self.transaction_changes.values() {
+        for (transaction_id, change) in &self.transaction_changes {
             // Save any transaction executions for the block
             if let Some(ref execution) = change.execution {
                 // This may already exist if we proposed the block
@@ -161,6 +176,17 @@ impl ProposedBlockChangeSet {
             if let Some(ref update) = change.next_update {
                 update.insert(tx)?;
             }
+
+            if let Some((reason, ref details)) = change.abort {
+                TransactionRecord::add_pending_abort(
+                    tx,
+                    self.block.block_id(),
+                    self.block.height(),
+                    transaction_id,
+                    reason,
+                    details,
+                )?;
+            }
         }
 
         Ok(())
@@ -171,4 +197,5 @@ impl ProposedBlockChangeSet {
 pub struct TransactionChangeSet {
     execution: Option<TransactionExecution>,
     next_update: Option<TransactionPoolStatusUpdate>,
+    abort: Option<(AbortReason, String)>,
 }
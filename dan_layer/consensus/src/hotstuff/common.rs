@@ -25,6 +25,7 @@ use tari_dan_storage::{
     StateStoreWriteTransaction,
     StorageError,
 };
+pub use tari_engine_types::fees::EXHAUST_DIVISOR;
 use tari_state_tree::{Hash, JellyfishMerkleTree, StateTreeError};
 
 use crate::{
@@ -37,10 +38,6 @@ use crate::{
 
 const LOG_TARGET: &str = "tari::dan::consensus::hotstuff::common";
 
-/// The value that fees are divided by to determine the amount of fees to burn. 0 means no fees are burned.
-/// This is a placeholder for the fee exhaust consensus constant so that we know where it's used later.
-pub const EXHAUST_DIVISOR: u64 = 20; // 5%
-
 /// Calculates the dummy block required to reach the new height and returns the last dummy block (parent for next
 /// proposal).
 pub fn calculate_last_dummy_block<TAddr: NodeAddressable, TLeaderStrategy: LeaderStrategy<TAddr>>(
@@ -13,4 +13,6 @@ pub struct HotstuffConfig {
     pub max_base_layer_blocks_behind: u64,
     pub num_preshards: NumPreshards,
     pub pacemaker_max_base_time: Duration,
+    /// The maximum number of commands a proposed block may contain
+    pub max_commands_per_block: usize,
 }
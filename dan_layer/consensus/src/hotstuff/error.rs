@@ -189,6 +189,12 @@ pub enum ProposalValidationError {
         block_network: String,
         block_id: BlockId,
     },
+    #[error("Invalid command merkle root for block {block_id}: calculated {calculated} but block has {from_block}")]
+    InvalidMerkleRoot {
+        block_id: BlockId,
+        calculated: FixedHash,
+        from_block: FixedHash,
+    },
     #[error("Invalid state merkle root for block {block_id}: calculated {calculated} but block has {from_block}")]
     InvalidStateMerkleRoot {
         block_id: BlockId,
@@ -211,6 +217,16 @@ pub enum ProposalValidationError {
     BlockHashNotFound { hash: FixedHash },
     #[error("Base layer block height {height} does not match the real height {real_height}")]
     BlockHeightMismatch { height: u64, real_height: u64 },
+    #[error(
+        "Block {block_id} proposed by {proposed_by} has {command_count} commands, exceeding the maximum of \
+         {max_commands_per_block}"
+    )]
+    TooManyCommands {
+        proposed_by: String,
+        block_id: BlockId,
+        command_count: usize,
+        max_commands_per_block: usize,
+    },
     #[error("Base layer block with height {base_layer_block_height} is not the last block of the epoch")]
     NotLastBlockOfEpoch {
         block_id: BlockId,
@@ -36,5 +36,6 @@ pub use config::HotstuffConfig;
 pub use current_view::*;
 pub use error::*;
 pub use event::*;
+pub use on_beat::BeatReason;
 pub use state_machine::*;
 pub use worker::*;
@@ -1,31 +1,82 @@
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
-use std::sync::Arc;
+use std::{fmt::Display, sync::Arc};
 
 use tokio::sync::watch;
 
+/// The reason that a beat was triggered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeatReason {
+    /// A new transaction is ready for consensus
+    NewTransaction,
+    /// The leader timeout elapsed
+    Timeout,
+    /// A foreign proposal was received
+    ForeignProposal,
+    /// Any other reason e.g. the pacemaker was started or a quorum of votes was received
+    Manual,
+}
+
+impl Display for BeatReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BeatReason::NewTransaction => write!(f, "NewTransaction"),
+            BeatReason::Timeout => write!(f, "Timeout"),
+            BeatReason::ForeignProposal => write!(f, "ForeignProposal"),
+            BeatReason::Manual => write!(f, "Manual"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OnBeat {
-    receiver: watch::Receiver<()>,
-    sender: Arc<watch::Sender<()>>,
+    receiver: watch::Receiver<BeatReason>,
+    sender: Arc<watch::Sender<BeatReason>>,
 }
 
 impl OnBeat {
     pub fn new() -> Self {
-        let (sender, receiver) = watch::channel(());
+        let (sender, receiver) = watch::channel(BeatReason::Manual);
         Self {
             receiver,
             sender: Arc::new(sender),
         }
     }
 
-    pub async fn wait(&mut self) {
-        self.receiver.changed().await.expect("sender can never be dropped")
+    pub async fn wait(&mut self) -> BeatReason {
+        self.receiver.changed().await.expect("sender can never be dropped");
+        *self.receiver.borrow()
+    }
+
+    pub fn beat(&self, reason: BeatReason) {
+        self.sender.send(reason).expect("receiver can never be dropped")
+    }
+
+    /// Triggers a beat only if there are receivers for it, returning true if the beat was sent. Unlike [OnBeat::beat],
+    /// this never panics, so it can be used in a tight loop without knowing whether consensus is running.
+    ///
+    /// Note that every `OnBeat` handle (including this one) holds a receiver, so receivers remain for as long as any
+    /// handle is alive, regardless of whether that handle is currently waiting.
+    pub fn try_beat(&self, reason: BeatReason) -> bool {
+        if self.sender.receiver_count() == 0 {
+            return false;
+        }
+        self.sender.send(reason).is_ok()
+    }
+
+    /// Triggers a [BeatReason::Manual] beat if anyone is listening for it, returning true if the beat was sent. See
+    /// [OnBeat::try_beat].
+    ///
+    /// Beats are not queued: a beat sent while consensus is busy is seen once when it next calls [OnBeat::wait], and
+    /// several beats sent in the meantime wake it only once. Calling this when consensus is idle therefore causes at
+    /// most one extra wakeup, and calling it when nothing is listening is a no-op.
+    pub fn beat_if_idle(&self) -> bool {
+        self.try_beat(BeatReason::Manual)
     }
 
-    pub fn beat(&self) {
-        self.sender.send(()).expect("receiver can never be dropped")
+    pub fn beat_default(&self) {
+        self.beat(BeatReason::Manual)
     }
 }
 
@@ -34,3 +85,31 @@ impl Default for OnBeat {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_sends_a_beat_when_there_are_receivers() {
+        let mut on_beat = OnBeat::new();
+        let sender = on_beat.clone();
+
+        assert!(sender.try_beat(BeatReason::NewTransaction));
+        assert_eq!(on_beat.wait().await, BeatReason::NewTransaction);
+
+        assert!(sender.beat_if_idle());
+        assert_eq!(on_beat.wait().await, BeatReason::Manual);
+    }
+
+    #[tokio::test]
+    async fn it_wakes_the_receiver_once_for_beats_sent_while_it_is_busy() {
+        let mut on_beat = OnBeat::new();
+        let sender = on_beat.clone();
+
+        assert!(sender.try_beat(BeatReason::NewTransaction));
+        assert!(sender.beat_if_idle());
+        assert_eq!(on_beat.wait().await, BeatReason::Manual);
+        assert!(!on_beat.receiver.has_changed().unwrap());
+    }
+}
@@ -18,7 +18,8 @@ use tokio::sync::broadcast;
 use super::config::HotstuffConfig;
 use crate::{
     block_validations,
-    hotstuff::{error::HotStuffError, HotstuffEvent},
+    committee_cache::{CommitteeCache, DEFAULT_COMMITTEE_CACHE_CAPACITY},
+    hotstuff::{error::HotStuffError, HotstuffEvent, ProposalValidationError},
     messages::{HotstuffMessage, MissingTransactionsRequest, ProposalMessage},
     traits::{ConsensusSpec, OutboundMessaging},
 };
@@ -37,6 +38,7 @@ pub struct OnMessageValidate<TConsensusSpec: ConsensusSpec> {
     /// Keep track of max 16 in-flight requests
     active_missing_transaction_requests: SimpleFixedArray<u32, 16>,
     current_request_id: u32,
+    committee_cache: CommitteeCache<TConsensusSpec::Addr>,
 }
 
 impl<TConsensusSpec: ConsensusSpec> OnMessageValidate<TConsensusSpec> {
@@ -61,6 +63,7 @@ impl<TConsensusSpec: ConsensusSpec> OnMessageValidate<TConsensusSpec> {
             tx_events,
             active_missing_transaction_requests: SimpleFixedArray::new(),
             current_request_id: 0,
+            committee_cache: CommitteeCache::new(DEFAULT_COMMITTEE_CACHE_CAPACITY),
         }
     }
 
@@ -200,16 +203,57 @@ impl<TConsensusSpec: ConsensusSpec> OnMessageValidate<TConsensusSpec> {
         )))
     }
 
-    async fn check_proposal(&self, block: &Block) -> Result<(), HotStuffError> {
-        block_validations::check_proposal::<TConsensusSpec>(
+    async fn check_proposal(&mut self, block: &Block) -> Result<(), HotStuffError> {
+        let result = block_validations::check_proposal::<TConsensusSpec>(
             block,
             &self.epoch_manager,
             &self.vote_signing_service,
             &self.leader_strategy,
             &self.config,
+            &mut self.committee_cache,
         )
-        .await?;
-        Ok(())
+        .await;
+
+        if let Err(HotStuffError::ProposalValidationError(ref err)) = result {
+            // Only proposals from an authenticated validator are recorded, otherwise any peer could fill the table
+            if self.is_proposal_authenticated(block).await {
+                self.record_rejected_block(block, err);
+            }
+        }
+
+        result
+    }
+
+    /// Returns true if the block is signed by its proposer and the proposer is a registered validator in the block's
+    /// epoch.
+    async fn is_proposal_authenticated(&self, block: &Block) -> bool {
+        let Some(signature) = block.signature() else {
+            return false;
+        };
+        if !signature.verify(block.proposed_by(), block.id()) {
+            return false;
+        }
+        self.epoch_manager
+            .get_committee_by_validator_public_key(block.epoch(), block.proposed_by())
+            .await
+            .is_ok()
+    }
+
+    /// Records the rejected block so that the reason is available for later analysis. Failing to record the block
+    /// does not affect consensus, so errors are only logged.
+    fn record_rejected_block(&self, block: &Block, err: &ProposalValidationError) {
+        let reason = err.to_string();
+        if let Err(err) = self
+            .store
+            .with_write_tx(|tx| tx.rejected_blocks_insert(block.id(), block.height(), &reason))
+        {
+            warn!(
+                target: LOG_TARGET,
+                "Failed to record rejected block {}: {}",
+                block,
+                err
+            );
+        }
     }
 
     async fn handle_missing_transactions(
@@ -255,7 +299,9 @@ impl<TConsensusSpec: ConsensusSpec> OnMessageValidate<TConsensusSpec> {
             );
             return Ok(HashSet::new());
         }
-        let missing_tx_ids = TransactionRecord::get_missing(&**tx, block.all_transaction_ids())?;
+        // Look up all referenced transactions at once so that every missing transaction can be requested together
+        let referenced_tx_ids = block.referenced_transaction_ids();
+        let missing_tx_ids = TransactionRecord::get_missing(&**tx, &referenced_tx_ids)?;
 
         if missing_tx_ids.is_empty() {
             debug!(
@@ -267,7 +313,7 @@ impl<TConsensusSpec: ConsensusSpec> OnMessageValidate<TConsensusSpec> {
 
         info!(
             target: LOG_TARGET,
-            "⏳ Block {} has {} missing transactions", block, missing_tx_ids.len(),
+            "⏳ Block {} has {} of {} transactions missing", block, missing_tx_ids.len(), referenced_tx_ids.len(),
         );
 
         tx.missing_transactions_insert(block, &missing_tx_ids, &[])?;
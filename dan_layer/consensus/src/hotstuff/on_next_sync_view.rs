@@ -2,7 +2,7 @@
 //  SPDX-License-Identifier: BSD-3-Clause
 
 use log::*;
-use tari_dan_common_types::{optional::Optional, Epoch, NodeHeight};
+use tari_dan_common_types::{Epoch, NodeHeight};
 use tari_dan_storage::{
     consensus_models::{HighQc, LastSentVote},
     StateStore,
@@ -52,9 +52,7 @@ impl<TConsensusSpec: ConsensusSpec> OnNextSyncViewHandler<TConsensusSpec> {
 
         let (high_qc, last_sent_vote) = self.store.with_read_tx(|tx| {
             let high_qc = HighQc::get(tx)?.get_quorum_certificate(tx)?;
-            let last_sent_vote = LastSentVote::get(tx)
-                .optional()?
-                .filter(|vote| high_qc.block_height() < vote.block_height);
+            let last_sent_vote = LastSentVote::get(tx)?.filter(|vote| high_qc.block_height() < vote.block_height);
             Ok::<_, HotStuffError>((high_qc, last_sent_vote))
         })?;
 
@@ -19,6 +19,7 @@ use tari_dan_common_types::{
 };
 use tari_dan_storage::{
     consensus_models::{
+        AbortReason,
         Block,
         Command,
         ExecutedTransaction,
@@ -178,7 +179,10 @@ where TConsensusSpec: ConsensusSpec
                 if local_committee_info.count_distinct_shard_groups(executed.involved_addresses_iter()) == 0 {
                     self.transaction_pool.remove(tx, *executed.id())?;
                     executed
-                        .set_abort("Transaction has no involved shards after execution")
+                        .set_abort(
+                            AbortReason::NoInvolvedShards,
+                            "Transaction has no involved shards after execution",
+                        )
                         .update(tx)?;
                 } else {
                     executed
@@ -455,13 +459,7 @@ where TConsensusSpec: ConsensusSpec
         base_layer_block_hash: FixedHash,
         propose_epoch_end: bool,
     ) -> Result<(Block, HashMap<TransactionId, ExecutedTransaction>), HotStuffError> {
-        // TODO: Configure
-        const TARGET_BLOCK_SIZE: usize = 500;
-        let batch = if dont_propose_transactions || propose_epoch_end {
-            vec![]
-        } else {
-            self.transaction_pool.get_batch_for_next_block(tx, TARGET_BLOCK_SIZE)?
-        };
+        let max_commands = self.config.max_commands_per_block;
         let next_height = parent_block.height() + NodeHeight(1);
 
         let mut total_leader_fee = 0;
@@ -481,6 +479,9 @@ where TConsensusSpec: ConsensusSpec
                                 pending_proposal.block_id == foreign_proposal.block_id
                         })
                 })
+                // Replicas reject blocks with more than max_commands_per_block commands, so any remaining foreign
+                // proposals are left for a later block
+                .take(max_commands)
                 .map(|mut foreign_proposal| {
                     foreign_proposal.set_proposed_height(parent_block.height().saturating_add(NodeHeight(1)));
                     Command::ForeignProposal(foreign_proposal)
@@ -488,6 +489,14 @@ where TConsensusSpec: ConsensusSpec
                 .collect()
         };
 
+        // Each transaction in the batch results in at most one command
+        let batch = if dont_propose_transactions || propose_epoch_end || commands.len() >= max_commands {
+            vec![]
+        } else {
+            self.transaction_pool
+                .get_batch_for_next_block(tx, max_commands - commands.len())?
+        };
+
         // batch is empty for is_empty, is_epoch_end and is_epoch_start blocks
         let mut substate_store = PendingSubstateStore::new(tx, *parent_block.block_id(), self.config.num_preshards);
         let mut executed_transactions = HashMap::new();
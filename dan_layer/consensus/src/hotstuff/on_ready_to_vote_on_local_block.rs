@@ -8,6 +8,7 @@ use log::*;
 use tari_dan_common_types::{committee::CommitteeInfo, optional::Optional, Epoch};
 use tari_dan_storage::{
     consensus_models::{
+        AbortReason,
         Block,
         BlockDiff,
         BlockId,
@@ -349,9 +350,12 @@ where TConsensusSpec: ConsensusSpec
                                     block,
                                     tx_rec.transaction_id(),
                                 );
-                                // TODO: Add a reason for the ABORT. Perhaps a reason enum
-                                //       Decision::Abort(AbortReason::LockConflict)
                                 tx_rec.set_local_decision(Decision::Abort);
+                                proposed_block_change_set.set_transaction_abort(
+                                    *tx_rec.transaction_id(),
+                                    AbortReason::LockConflict,
+                                    format!("failed to obtain substate locks in block {}", block),
+                                );
                                 proposed_block_change_set.set_next_transaction_update(
                                     &tx_rec,
                                     TransactionPoolStage::LocalOnly,
@@ -493,6 +497,11 @@ where TConsensusSpec: ConsensusSpec
                             // we also have.
                             if t.decision.is_abort() {
                                 tx_rec.set_local_decision(Decision::Abort);
+                                proposed_block_change_set.set_transaction_abort(
+                                    *tx_rec.transaction_id(),
+                                    AbortReason::LockConflict,
+                                    format!("failed to obtain substate locks in block {}", block),
+                                );
                                 proposed_block_change_set.set_next_transaction_update(
                                     &tx_rec,
                                     TransactionPoolStage::Prepared,
@@ -894,6 +903,8 @@ where TConsensusSpec: ConsensusSpec
 
         let local_diff = diff.into_filtered(local_committee_info);
         block.commit_diff(tx, local_diff)?;
+        // Aborts decided in this block only take effect now that it is committed
+        TransactionRecord::commit_pending_aborts(tx, block.id(), block.height())?;
 
         let finalized_transactions = self
             .transaction_pool
@@ -910,6 +921,14 @@ where TConsensusSpec: ConsensusSpec
 
         // Remove locks for finalized transactions
         tx.substate_locks_remove_many_for_transactions(block.all_accepted_transactions_ids())?;
+        // Reclaim any locks leaked by transactions that left the pool without releasing them
+        let num_expired = tx.substate_locks_remove_expired(*block.id())?;
+        if num_expired > 0 {
+            warn!(
+                target: LOG_TARGET,
+                "🔓 Removed {} leaked substate lock(s) at or below block {}", num_expired, block
+            );
+        }
 
         let total_transaction_fee = block.total_transaction_fee();
         if total_transaction_fee > 0 {
@@ -18,7 +18,7 @@ use tari_epoch_manager::EpochManagerReader;
 use tari_transaction::TransactionId;
 
 use crate::{
-    hotstuff::{error::HotStuffError, pacemaker_handle::PaceMakerHandle, ProposalValidationError},
+    hotstuff::{error::HotStuffError, on_beat::BeatReason, pacemaker_handle::PaceMakerHandle, ProposalValidationError},
     messages::ProposalMessage,
     traits::ConsensusSpec,
 };
@@ -132,7 +132,7 @@ where TConsensusSpec: ConsensusSpec
         })?;
 
         // We could have ready transactions at this point, so if we're the leader for the next block we can propose
-        self.pacemaker.beat();
+        self.pacemaker.beat(BeatReason::ForeignProposal);
 
         Ok(())
     }
@@ -27,6 +27,7 @@ use tokio::{sync::broadcast, task};
 
 use super::proposer::Proposer;
 use crate::{
+    block_validations,
     hotstuff::{
         calculate_dummy_blocks,
         create_epoch_checkpoint,
@@ -334,7 +335,9 @@ impl<TConsensusSpec: ConsensusSpec> OnReceiveLocalProposalHandler<TConsensusSpec
             .await?;
         let leaf_hash = vn.get_node_hash(self.config.network);
 
-        let signature = self.vote_signing_service.sign_vote(&leaf_hash, block.id(), &decision);
+        let signature =
+            self.vote_signing_service
+                .sign_vote(self.config.network, block.epoch(), &leaf_hash, block.id(), &decision);
 
         Ok(VoteMessage {
             epoch: block.epoch(),
@@ -516,9 +519,11 @@ impl<TConsensusSpec: ConsensusSpec> OnReceiveLocalProposalHandler<TConsensusSpec
             .into());
         }
 
-        // Check that details included in the justify match previously added blocks
+        // Check that details included in the justify match previously added blocks. An unknown justify block will
+        // trigger a sync.
+        block_validations::check_justify_block_exists(tx, &candidate_block)?;
         let Some(justify_block) = candidate_block.justify().get_block(tx).optional()? else {
-            // This will trigger a sync
+            // The justify block is parked waiting for transactions, this will also trigger a sync
             return Err(ProposalValidationError::JustifyBlockNotFound {
                 proposed_by: candidate_block.proposed_by().to_string(),
                 block_description: candidate_block.to_string(),
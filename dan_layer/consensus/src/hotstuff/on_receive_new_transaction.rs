@@ -4,7 +4,7 @@
 use log::*;
 use tari_dan_common_types::{optional::Optional, Epoch};
 use tari_dan_storage::{
-    consensus_models::{TransactionPool, TransactionRecord},
+    consensus_models::{AbortReason, TransactionPool, TransactionRecord},
     StateStore,
 };
 use tari_transaction::{Transaction, TransactionId};
@@ -105,7 +105,8 @@ where TConsensusSpec: ConsensusSpec
                 target: LOG_TARGET,
                 "Transaction {} failed validation: {}", rec.id(), err
             );
-            rec.set_current_decision_to_abort(err.to_string()).insert(tx)?;
+            rec.set_current_decision_to_abort(AbortReason::TransactionValidationFailed, err.to_string())
+                .insert(tx)?;
             self.add_to_pool(tx, &rec)?;
             return Ok(Some(rec));
         }
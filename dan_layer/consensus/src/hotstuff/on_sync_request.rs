@@ -2,7 +2,6 @@
 //   SPDX-License-Identifier: BSD-3-Clause
 
 use log::*;
-use tari_dan_common_types::optional::Optional;
 use tari_dan_storage::{
     consensus_models::{Block, LastSentVote, LeafBlock},
     StateStore,
@@ -107,7 +106,7 @@ impl<TConsensusSpec: ConsensusSpec> OnSyncRequest<TConsensusSpec> {
             }
 
             // Send last vote.
-            let maybe_last_vote = match store.with_read_tx(|tx| LastSentVote::get(tx)).optional() {
+            let maybe_last_vote = match store.with_read_tx(|tx| LastSentVote::get(tx)) {
                 Ok(last_vote) => last_vote,
                 Err(err) => {
                     warn!(target: LOG_TARGET, "Failed to fetch last vote for catch-up request: {}", err);
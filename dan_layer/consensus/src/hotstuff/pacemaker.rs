@@ -12,7 +12,7 @@ use tokio::sync::mpsc;
 
 use crate::hotstuff::{
     current_view::CurrentView,
-    on_beat::OnBeat,
+    on_beat::{BeatReason, OnBeat},
     on_force_beat::OnForceBeat,
     on_leader_timeout::OnLeaderTimeout,
     pacemaker_handle::{PaceMakerHandle, PacemakerRequest},
@@ -115,7 +115,7 @@ impl PaceMaker {
                                 info!(target: LOG_TARGET, "Reset! Current height: {}, Delta: {:.2?}", self.current_view, delta);
                                 leader_timeout.as_mut().reset(tokio::time::Instant::now() + delta);
                                 block_timer.as_mut().reset(tokio::time::Instant::now() + self.block_time);
-                                on_beat.beat();
+                                on_beat.beat_default();
                                 started = true;
                             }
                             PacemakerRequest::Stop => {
@@ -143,6 +143,7 @@ impl PaceMaker {
                     info!(target: LOG_TARGET, "⚠️ Leader timeout! Current view: {}, Delta: {:.2?}", self.current_view, delta);
                     self.current_view.set_next_height();
                     on_leader_timeout.leader_timed_out(self.current_view.get_height());
+                    on_beat.beat(BeatReason::Timeout);
                 },
 
             }
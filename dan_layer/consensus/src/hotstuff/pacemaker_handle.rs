@@ -7,7 +7,7 @@ use tokio::sync::mpsc;
 
 use crate::hotstuff::{
     current_view::CurrentView,
-    on_beat::OnBeat,
+    on_beat::{BeatReason, OnBeat},
     on_force_beat::OnForceBeat,
     on_leader_timeout::OnLeaderTimeout,
     HotStuffError,
@@ -68,8 +68,14 @@ impl PaceMakerHandle {
     }
 
     /// Signal the pacemaker trigger a beat. If the pacemaker has not been started, this is a no-op
-    pub fn beat(&self) {
-        self.on_beat.beat();
+    pub fn beat(&self, reason: BeatReason) {
+        self.on_beat.beat(reason);
+    }
+
+    /// Signal the pacemaker trigger a beat with [BeatReason::Manual]. If the pacemaker has not been started, this is
+    /// a no-op
+    pub fn beat_default(&self) {
+        self.on_beat.beat_default();
     }
 
     /// Signal the pacemaker trigger a forced beat. If the pacemaker has not been started, this is a no-op
@@ -82,7 +88,7 @@ impl PaceMakerHandle {
     }
 
     pub fn on_beat(&self) {
-        self.on_beat.beat()
+        self.on_beat.beat_default()
     }
 
     pub fn get_on_force_beat(&self) -> OnForceBeat {
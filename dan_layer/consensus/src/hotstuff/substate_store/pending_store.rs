@@ -8,6 +8,7 @@ use log::*;
 use tari_dan_common_types::{optional::Optional, NumPreshards, SubstateAddress};
 use tari_dan_storage::{
     consensus_models::{
+        build_substate_diff,
         BlockDiff,
         BlockId,
         LockedSubstate,
@@ -103,25 +104,8 @@ impl<'a, 'tx, TStore: StateStore + 'a + 'tx> WriteableSubstateStore for PendingS
     }
 
     fn put_diff(&mut self, transaction_id: TransactionId, diff: &SubstateDiff) -> Result<(), Self::Error> {
-        for (id, version) in diff.down_iter() {
-            let id = VersionedSubstateId::new(id.clone(), *version);
-            let shard = id.to_substate_address().to_shard(self.num_preshards);
-            self.put(SubstateChange::Down {
-                id,
-                shard,
-                transaction_id,
-            })?;
-        }
-
-        for (id, substate) in diff.up_iter() {
-            let id = VersionedSubstateId::new(id.clone(), substate.version());
-            let shard = id.to_substate_address().to_shard(self.num_preshards);
-            self.put(SubstateChange::Up {
-                id,
-                shard,
-                substate: substate.clone(),
-                transaction_id,
-            })?;
+        for change in build_substate_diff(transaction_id, diff, self.num_preshards) {
+            self.put(change)?;
         }
 
         Ok(())
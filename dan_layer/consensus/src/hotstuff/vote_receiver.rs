@@ -59,7 +59,7 @@ where TConsensusSpec: ConsensusSpec
         match self.handle_vote(from, message, check_leadership).await {
             Ok(true) => {
                 // If we reached quorum, trigger a check to see if we should propose
-                self.pacemaker.beat();
+                self.pacemaker.beat_default();
             },
             Ok(false) => {},
             Err(err) => {
@@ -271,6 +271,8 @@ where TConsensusSpec: ConsensusSpec
 
         if !self.vote_signature_service.verify(
             &message.signature,
+            self.network,
+            message.epoch,
             sender_leaf_hash,
             &message.block_id,
             &message.decision,
@@ -6,7 +6,7 @@ use std::fmt::{Debug, Formatter};
 use log::*;
 use tari_dan_common_types::{Epoch, NodeHeight, ShardGroup};
 use tari_dan_storage::{
-    consensus_models::{Block, BlockDiff, HighQc, LeafBlock, TransactionPool},
+    consensus_models::{Block, BlockDiff, HighQc, LastSentVote, LeafBlock, TransactionPool},
     StateStore,
 };
 use tari_epoch_manager::{EpochManagerEvent, EpochManagerReader};
@@ -24,6 +24,7 @@ use crate::{
     hotstuff::{
         error::HotStuffError,
         event::HotstuffEvent,
+        on_beat::BeatReason,
         on_catch_up_sync::OnCatchUpSync,
         on_inbound_message::OnInboundMessage,
         on_message_validate::{MessageValidationResult, OnMessageValidate},
@@ -294,7 +295,8 @@ impl<TConsensusSpec: ConsensusSpec> HotstuffWorker<TConsensusSpec> {
                     }
                 },
 
-                _ = on_beat.wait() => {
+                reason = on_beat.wait() => {
+                    debug!(target: LOG_TARGET, "🫀 Beat triggered by {reason}");
                     if let Err(e) = self.on_beat(current_epoch).await {
                         self.on_failure("on_beat", &e).await;
                         return Err(e);
@@ -440,7 +442,7 @@ impl<TConsensusSpec: ConsensusSpec> HotstuffWorker<TConsensusSpec> {
         // There are num_pending_txs transactions in the queue. If we have no pending transactions, we'll propose now if
         // able.
         if num_pending_txs == 0 {
-            self.pacemaker.beat();
+            self.pacemaker.beat(BeatReason::NewTransaction);
         }
 
         Ok(())
@@ -489,8 +491,16 @@ impl<TConsensusSpec: ConsensusSpec> HotstuffWorker<TConsensusSpec> {
                 //     self.pacemaker.set_epoch(epoch).await?;
                 // }
 
+                // A vote sent in a previous epoch must not be treated as a double vote in this epoch
+                self.state_store.with_write_tx(|tx| {
+                    if LastSentVote::get(&**tx)?.is_some_and(|vote| vote.epoch < epoch) {
+                        LastSentVote::clear(tx)?;
+                    }
+                    Ok::<_, HotStuffError>(())
+                })?;
+
                 // If we can propose a block end, let's not wait for the block time to do it
-                self.pacemaker.beat();
+                self.pacemaker.beat_default();
             },
             EpochManagerEvent::ThisValidatorIsRegistered { .. } => {},
         }
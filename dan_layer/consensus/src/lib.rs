@@ -2,6 +2,7 @@
 //   SPDX-License-Identifier: BSD-3-Clause
 
 mod block_validations;
+mod committee_cache;
 pub mod hotstuff;
 pub mod messages;
 pub mod traits;
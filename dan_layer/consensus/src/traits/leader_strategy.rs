@@ -64,4 +64,45 @@ pub trait LeaderStrategy<TAddr> {
     fn get_leader_for_next_block<'b>(&self, committee: &'b Committee<TAddr>, height: NodeHeight) -> &'b TAddr {
         self.get_leader(committee, height + NodeHeight(1))
     }
+
+    /// Returns the leaders for the `n` heights following `current_height`, in height order.
+    fn get_leader_for_next_n<'b>(
+        &self,
+        committee: &'b Committee<TAddr>,
+        current_height: NodeHeight,
+        n: usize,
+    ) -> Vec<&'b TAddr> {
+        (1..=n as u64)
+            .map(|i| self.get_leader(committee, current_height + NodeHeight(i)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct RoundRobin;
+
+    impl LeaderStrategy<u32> for RoundRobin {
+        fn calculate_leader(&self, committee: &Committee<u32>, height: NodeHeight) -> u32 {
+            (height.0 % committee.members.len() as u64) as u32
+        }
+    }
+
+    #[test]
+    fn it_returns_the_next_n_leaders() {
+        let committee = Committee::new((0..3).map(|addr| (addr, PublicKey::default())).collect());
+        let leaders = RoundRobin.get_leader_for_next_n(&committee, NodeHeight(4), 5);
+        assert_eq!(leaders, vec![&2, &0, &1, &2, &0]);
+
+        for (i, leader) in leaders.into_iter().enumerate() {
+            let height = NodeHeight(4) + NodeHeight(i as u64 + 1);
+            assert_eq!(leader, RoundRobin.get_leader(&committee, height));
+        }
+
+        assert!(RoundRobin
+            .get_leader_for_next_n(&committee, NodeHeight(4), 0)
+            .is_empty());
+    }
 }
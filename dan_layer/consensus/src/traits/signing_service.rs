@@ -1,8 +1,9 @@
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
+use tari_common::configuration::Network;
 use tari_common_types::types::{FixedHash, PublicKey};
-use tari_dan_common_types::hashing::vote_signature_hasher;
+use tari_dan_common_types::{hashing::vote_signature_hasher, Epoch};
 use tari_dan_storage::consensus_models::{BlockId, QuorumDecision, ValidatorSchnorrSignature, ValidatorSignature};
 
 pub trait ValidatorSignatureService {
@@ -12,16 +13,34 @@ pub trait ValidatorSignatureService {
 }
 
 pub trait VoteSignatureService: ValidatorSignatureService {
-    fn create_message(&self, voter_leaf_hash: &FixedHash, block_id: &BlockId, decision: &QuorumDecision) -> FixedHash {
+    /// Creates the message that is signed for a vote. The network and epoch are included so that a vote cannot be
+    /// replayed on another network or in another epoch.
+    fn create_message(
+        &self,
+        network: Network,
+        epoch: Epoch,
+        voter_leaf_hash: &FixedHash,
+        block_id: &BlockId,
+        decision: &QuorumDecision,
+    ) -> FixedHash {
         vote_signature_hasher()
+            .chain(&network.as_byte())
+            .chain(&epoch)
             .chain(voter_leaf_hash)
             .chain(block_id)
             .chain(decision)
             .result()
     }
 
-    fn sign_vote(&self, leaf_hash: &FixedHash, block_id: &BlockId, decision: &QuorumDecision) -> ValidatorSignature {
-        let message = self.create_message(leaf_hash, block_id, decision);
+    fn sign_vote(
+        &self,
+        network: Network,
+        epoch: Epoch,
+        leaf_hash: &FixedHash,
+        block_id: &BlockId,
+        decision: &QuorumDecision,
+    ) -> ValidatorSignature {
+        let message = self.create_message(network, epoch, leaf_hash, block_id, decision);
         let signature = self.sign(message);
         ValidatorSignature::new(self.public_key().clone(), signature)
     }
@@ -29,8 +48,82 @@ pub trait VoteSignatureService: ValidatorSignatureService {
     fn verify(
         &self,
         signature: &ValidatorSignature,
+        network: Network,
+        epoch: Epoch,
         leaf_hash: &FixedHash,
         block_id: &BlockId,
         decision: &QuorumDecision,
     ) -> bool;
 }
+
+#[cfg(test)]
+mod tests {
+    use tari_common_types::types::PrivateKey;
+    use tari_crypto::keys::PublicKey as _;
+
+    use super::*;
+
+    struct TestSignatureService {
+        secret_key: PrivateKey,
+        public_key: PublicKey,
+    }
+
+    impl TestSignatureService {
+        fn new() -> Self {
+            let secret_key = PrivateKey::from(1u64);
+            let public_key = PublicKey::from_secret_key(&secret_key);
+            Self { secret_key, public_key }
+        }
+    }
+
+    impl ValidatorSignatureService for TestSignatureService {
+        fn sign<M: AsRef<[u8]>>(&self, message: M) -> ValidatorSchnorrSignature {
+            ValidatorSignature::sign(&self.secret_key, message).signature
+        }
+
+        fn public_key(&self) -> &PublicKey {
+            &self.public_key
+        }
+    }
+
+    impl VoteSignatureService for TestSignatureService {
+        fn verify(
+            &self,
+            signature: &ValidatorSignature,
+            network: Network,
+            epoch: Epoch,
+            leaf_hash: &FixedHash,
+            block_id: &BlockId,
+            decision: &QuorumDecision,
+        ) -> bool {
+            signature.verify(self.create_message(network, epoch, leaf_hash, block_id, decision))
+        }
+    }
+
+    #[test]
+    fn it_does_not_verify_a_vote_for_another_network_or_epoch() {
+        let service = TestSignatureService::new();
+        let leaf_hash = FixedHash::zero();
+        let block_id = BlockId::zero();
+        let decision = QuorumDecision::Accept;
+        let signature = service.sign_vote(Network::LocalNet, Epoch(1), &leaf_hash, &block_id, &decision);
+
+        assert!(service.verify(
+            &signature,
+            Network::LocalNet,
+            Epoch(1),
+            &leaf_hash,
+            &block_id,
+            &decision
+        ));
+        assert!(!service.verify(&signature, Network::Igor, Epoch(1), &leaf_hash, &block_id, &decision));
+        assert!(!service.verify(
+            &signature,
+            Network::LocalNet,
+            Epoch(2),
+            &leaf_hash,
+            &block_id,
+            &decision
+        ));
+    }
+}
@@ -107,6 +107,60 @@ async fn propose_blocks_with_queued_up_transactions_until_all_committed() {
     test.assert_clean_shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn propose_blocks_within_max_commands_per_block() {
+    const MAX_COMMANDS: usize = 3;
+    setup_logger();
+    let mut test = Test::builder()
+        .with_test_timeout(Duration::from_secs(60))
+        .with_max_commands_per_block(MAX_COMMANDS)
+        .add_committee(0, vec!["1", "2"])
+        .start()
+        .await;
+    // More transactions than fit in a single block
+    for _ in 0..10 {
+        test.send_transaction_to_all(Decision::Commit, 1, 1).await;
+    }
+    test.start_epoch(Epoch(1)).await;
+
+    // Replicas reject proposals with more than MAX_COMMANDS commands, so this only completes if the leader respects the
+    // limit
+    loop {
+        test.on_block_committed().await;
+
+        if test.is_transaction_pool_empty() {
+            break;
+        }
+        let leaf = test.get_validator(&TestAddress::new("1")).get_leaf_block();
+        if leaf.height > NodeHeight(50) {
+            panic!("Not all transaction committed after {} blocks", leaf.height);
+        }
+    }
+
+    test.assert_all_validators_at_same_height().await;
+    test.assert_all_validators_committed();
+
+    let validator = test.get_validator(&TestAddress::new("1"));
+    validator
+        .state_store
+        .with_read_tx(|tx| {
+            let mut block = tx.blocks_get_tip(Epoch(1), validator.shard_group)?;
+            while !block.id().is_zero() {
+                assert!(
+                    block.command_count() <= MAX_COMMANDS,
+                    "Block {} has {} commands",
+                    block,
+                    block.command_count()
+                );
+                block = block.get_parent(tx)?;
+            }
+            Ok::<_, HotStuffError>(())
+        })
+        .unwrap();
+
+    test.assert_clean_shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 async fn propose_blocks_with_new_transactions_until_all_committed() {
     setup_logger();
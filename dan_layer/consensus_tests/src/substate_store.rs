@@ -8,6 +8,7 @@ use tari_consensus::{
 use tari_dan_common_types::{shard::Shard, NodeAddressable, PeerAddress};
 use tari_dan_storage::{
     consensus_models::{
+        build_substate_diff,
         BlockId,
         QcId,
         SubstateChange,
@@ -19,7 +20,7 @@ use tari_dan_storage::{
 };
 use tari_engine_types::{
     component::{ComponentBody, ComponentHeader},
-    substate::{Substate, SubstateId, SubstateValue},
+    substate::{Substate, SubstateDiff, SubstateId, SubstateValue},
 };
 use tari_state_store_sqlite::SqliteStateStore;
 use tari_template_lib::models::{ComponentAddress, EntityId, ObjectKey};
@@ -195,6 +196,39 @@ fn it_allows_locks_within_one_transaction() {
     assert_eq!(n, 2);
 }
 
+#[test]
+fn it_builds_substate_diff_with_downs_before_ups() {
+    let mut diff = SubstateDiff::new();
+    diff.up(new_substate_id(1), new_substate(1, 1));
+    diff.up(new_substate_id(2), new_substate(2, 0));
+    diff.down(new_substate_id(1), 0);
+
+    let changes = build_substate_diff(tx_id(0), &diff, TEST_NUM_PRESHARDS);
+    assert_eq!(changes.len(), 3);
+    assert!(changes[0].is_down());
+    assert_eq!(
+        *changes[0].versioned_substate_id(),
+        VersionedSubstateId::new(new_substate_id(1), 0)
+    );
+    assert!(changes[1].is_up());
+    assert_eq!(
+        *changes[1].versioned_substate_id(),
+        VersionedSubstateId::new(new_substate_id(1), 1)
+    );
+    assert!(changes[2].is_up());
+    assert_eq!(
+        *changes[2].versioned_substate_id(),
+        VersionedSubstateId::new(new_substate_id(2), 0)
+    );
+    for change in &changes {
+        assert_eq!(
+            change.shard(),
+            change.to_substate_address().to_shard(TEST_NUM_PRESHARDS)
+        );
+        assert_eq!(change.transaction_id(), tx_id(0));
+    }
+}
+
 fn add_substate(store: &TestStore, seed: u8, version: u32) -> VersionedSubstateId {
     let id = new_substate_id(seed);
     let value = new_substate_value(seed);
@@ -397,6 +397,7 @@ pub struct TestBuilder {
     timeout: Option<Duration>,
     debug_sql_file: Option<String>,
     message_filter: Option<MessageFilter>,
+    max_commands_per_block: usize,
 }
 
 impl TestBuilder {
@@ -407,6 +408,7 @@ impl TestBuilder {
             timeout: Some(Duration::from_secs(10)),
             debug_sql_file: None,
             message_filter: None,
+            max_commands_per_block: 1000,
         }
     }
 
@@ -452,10 +454,16 @@ impl TestBuilder {
         self
     }
 
+    pub fn with_max_commands_per_block(mut self, max_commands_per_block: usize) -> Self {
+        self.max_commands_per_block = max_commands_per_block;
+        self
+    }
+
     async fn build_validators(
         leader_strategy: &RoundRobinLeaderStrategy,
         epoch_manager: &TestEpochManager,
         sql_address: String,
+        max_commands_per_block: usize,
         shutdown_signal: ShutdownSignal,
     ) -> (Vec<ValidatorChannels>, HashMap<TestAddress, Validator>) {
         let num_committees = epoch_manager.get_num_committees(Epoch(0)).await.unwrap();
@@ -475,6 +483,7 @@ impl TestBuilder {
                     .with_epoch_manager(epoch_manager.clone_for(address.clone(), pk, shard_addr))
                     .with_leader_strategy(*leader_strategy)
                     .with_num_committees(num_committees)
+                    .with_max_commands_per_block(max_commands_per_block)
                     .spawn(shutdown_signal.clone());
                 (channels, (address, validator))
             })
@@ -502,8 +511,14 @@ impl TestBuilder {
         let epoch_manager = TestEpochManager::new(tx_epoch_events);
         epoch_manager.add_committees(committees).await;
         let shutdown = Shutdown::new();
-        let (channels, validators) =
-            Self::build_validators(&leader_strategy, &epoch_manager, self.sql_address, shutdown.to_signal()).await;
+        let (channels, validators) = Self::build_validators(
+            &leader_strategy,
+            &epoch_manager,
+            self.sql_address,
+            self.max_commands_per_block,
+            shutdown.to_signal(),
+        )
+        .await;
         let network = spawn_network(channels, shutdown.to_signal(), self.message_filter);
 
         Test {
@@ -19,16 +19,26 @@ use tari_transaction::VersionedSubstateId;
 use crate::support::TestAddress;
 
 pub(crate) fn random_substate_in_shard_group(shard_group: ShardGroup, num_shards: NumPreshards) -> VersionedSubstateId {
+    random_substate_in_shard_group_seeded(shard_group, num_shards, &mut OsRng)
+}
+
+/// Same as [random_substate_in_shard_group] but draws from the given RNG, so that a seeded RNG (e.g.
+/// `StdRng::seed_from_u64`) can be used to reproduce a failing test.
+pub(crate) fn random_substate_in_shard_group_seeded(
+    shard_group: ShardGroup,
+    num_shards: NumPreshards,
+    rng: &mut impl RngCore,
+) -> VersionedSubstateId {
     let range = shard_group.to_substate_address_range(num_shards);
-    let middlish = random_substate_address_range(range);
+    let middlish = random_substate_address_range(range, rng);
     let entity_id = EntityId::new(copy_fixed(&middlish.to_u256().to_be_bytes()[0..EntityId::LENGTH]));
-    let rand_bytes = OsRng.gen::<[u8; ComponentKey::LENGTH]>();
+    let rand_bytes = rng.gen::<[u8; ComponentKey::LENGTH]>();
     let component_key = ComponentKey::new(copy_fixed(&rand_bytes));
     let substate_id = SubstateId::Component(ComponentAddress::new(ObjectKey::new(entity_id, component_key)));
     VersionedSubstateId::new(substate_id, 0)
 }
 
-fn random_substate_address_range<R: RangeBounds<SubstateAddress>>(range: R) -> SubstateAddress {
+fn random_substate_address_range<R: RangeBounds<SubstateAddress>>(range: R, rng: &mut impl RngCore) -> SubstateAddress {
     let start = match range.start_bound() {
         std::ops::Bound::Included(addr) => addr.to_u256(),
         std::ops::Bound::Excluded(addr) => addr.to_u256() + 1,
@@ -40,7 +50,7 @@ fn random_substate_address_range<R: RangeBounds<SubstateAddress>>(range: R) -> S
         std::ops::Bound::Unbounded => U256::MAX,
     };
     let mut bytes = [0u8; 32];
-    OsRng.fill_bytes(&mut bytes);
+    rng.fill_bytes(&mut bytes);
     let rand = U256::from_le_bytes(bytes);
     SubstateAddress::from_u256(start + (rand % (end - start)))
 }
@@ -58,3 +68,21 @@ pub fn derive_keypair_from_address(addr: &TestAddress) -> (PrivateKey, PublicKey
     let public_key = PublicKey::from_secret_key(&secret_key);
     (secret_key, public_key)
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn it_generates_the_same_substate_for_the_same_seed() {
+        let shard_group = ShardGroup::new(0, 31);
+        let a = random_substate_in_shard_group_seeded(shard_group, NumPreshards::P64, &mut StdRng::seed_from_u64(1));
+        let b = random_substate_in_shard_group_seeded(shard_group, NumPreshards::P64, &mut StdRng::seed_from_u64(1));
+        assert_eq!(a, b);
+
+        let c = random_substate_in_shard_group_seeded(shard_group, NumPreshards::P64, &mut StdRng::seed_from_u64(2));
+        assert_ne!(a, c);
+    }
+}
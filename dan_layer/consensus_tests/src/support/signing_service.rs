@@ -2,8 +2,10 @@
 //   SPDX-License-Identifier: BSD-3-Clause
 
 use rand::rngs::OsRng;
+use tari_common::configuration::Network;
 use tari_common_types::types::{FixedHash, PrivateKey, PublicKey};
 use tari_consensus::traits::{ValidatorSignatureService, VoteSignatureService};
+use tari_dan_common_types::Epoch;
 use tari_dan_storage::consensus_models::{BlockId, QuorumDecision, ValidatorSchnorrSignature, ValidatorSignature};
 
 use super::{helpers, TestAddress};
@@ -40,6 +42,8 @@ impl VoteSignatureService for TestVoteSignatureService {
     fn verify(
         &self,
         _signature: &ValidatorSignature,
+        _network: Network,
+        _epoch: Epoch,
         _leaf_hash: &FixedHash,
         _block_id: &BlockId,
         _decision: &QuorumDecision,
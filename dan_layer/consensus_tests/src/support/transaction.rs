@@ -6,6 +6,7 @@ use std::{iter, time::Duration};
 use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
 use tari_common_types::types::PrivateKey;
 use tari_dan_storage::consensus_models::{
+    AbortReason,
     BlockId,
     Decision,
     ExecutedTransaction,
@@ -32,7 +33,7 @@ pub fn build_transaction_from(
 ) -> TransactionRecord {
     let mut tx = TransactionRecord::new(tx);
     if decision.is_abort() {
-        tx.set_current_decision_to_abort("Test aborted");
+        tx.set_current_decision_to_abort(AbortReason::ExecutionFailure, "Test aborted");
     }
 
     let execution = create_execution_result_for_transaction(
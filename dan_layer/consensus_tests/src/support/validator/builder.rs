@@ -42,6 +42,7 @@ pub struct ValidatorBuilder {
     pub num_committees: u32,
     pub epoch_manager: Option<TestEpochManager>,
     pub transaction_executions: TestTransactionExecutionsStore,
+    pub max_commands_per_block: usize,
 }
 
 impl ValidatorBuilder {
@@ -57,6 +58,7 @@ impl ValidatorBuilder {
             leader_strategy: RoundRobinLeaderStrategy::new(),
             epoch_manager: None,
             transaction_executions: TestTransactionExecutionsStore::new(),
+            max_commands_per_block: 1000,
         }
     }
 
@@ -97,6 +99,11 @@ impl ValidatorBuilder {
         self
     }
 
+    pub fn with_max_commands_per_block(&mut self, max_commands_per_block: usize) -> &mut Self {
+        self.max_commands_per_block = max_commands_per_block;
+        self
+    }
+
     pub fn spawn(&self, shutdown_signal: ShutdownSignal) -> (ValidatorChannels, Validator) {
         log::info!(
             "Spawning validator with address {} and public key {}",
@@ -132,6 +139,7 @@ impl ValidatorBuilder {
                 max_base_layer_blocks_behind: 5,
                 network: Network::LocalNet,
                 pacemaker_max_base_time: Duration::from_secs(10),
+                max_commands_per_block: self.max_commands_per_block,
             },
             self.address.clone(),
             inbound_messaging,
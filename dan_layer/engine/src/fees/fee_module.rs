@@ -4,9 +4,8 @@
 use std::io;
 
 use tari_bor::encode_into;
-use tari_engine_types::fees::FeeSource;
+use tari_engine_types::fees::{FeeSource, FeeTable, STORAGE_COST_REDUCTION_DIVISOR};
 
-use super::FeeTable;
 use crate::runtime::{RuntimeModule, RuntimeModuleError, StateTracker};
 
 pub struct FeeModule {
@@ -43,8 +42,6 @@ impl RuntimeModule for FeeModule {
             Ok::<_, RuntimeModuleError>(counter.get())
         })?;
 
-        // TODO: Cost per byte of storage is reduced by a pretty arbitrarily chosen factor (floor(cost/0.333...))
-        const STORAGE_COST_REDUCTION_DIVISOR: u64 = 3;
         track.add_fee_charge(
             FeeSource::Storage,
             // Divide a storage cost reduction factor
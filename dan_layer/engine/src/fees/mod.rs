@@ -1,8 +1,6 @@
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
-mod fee_table;
-pub use fee_table::FeeTable;
-
 mod fee_module;
 pub use fee_module::FeeModule;
+pub use tari_engine_types::fees::{FeeTable, STORAGE_COST_REDUCTION_DIVISOR};
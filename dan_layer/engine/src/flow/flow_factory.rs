@@ -32,6 +32,7 @@ impl FlowFactory {
         let template_def = TemplateDef::V1(TemplateDefV1 {
             template_name: flow_definition.name.clone(),
             tari_version: TARI_VERSION.to_owned(),
+            version: 0,
             functions: vec![FunctionDef {
                 name: "main".to_string(),
                 arguments: flow_definition
@@ -44,6 +45,7 @@ impl FlowFactory {
                     .collect(),
                 output: Type::Unit,
                 is_mut: false,
+                is_constructor: true,
             }],
         });
 
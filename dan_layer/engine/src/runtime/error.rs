@@ -106,6 +106,13 @@ pub enum RuntimeError {
     },
     #[error("Invalid argument {argument}: {reason}")]
     InvalidArgument { argument: &'static str, reason: String },
+    #[error("Invalid argument {arg_index} for function '{function}': expected type `{expected_type}`: {details}")]
+    InvalidCallArgument {
+        function: String,
+        arg_index: usize,
+        expected_type: String,
+        details: String,
+    },
     #[error("Invalid amount '{amount}': {reason}")]
     InvalidAmount { amount: Amount, reason: String },
     #[error("Call frame error: {details}")]
@@ -4,6 +4,7 @@
 use std::{
     collections::{hash_map::Entry, HashMap},
     fmt::Display,
+    sync::Arc,
 };
 
 use tari_dan_common_types::optional::IsNotFoundError;
@@ -12,6 +13,21 @@ use tari_engine_types::{
     substate::SubstateId,
 };
 
+/// Callback that is invoked for every substate lock and unlock made by the working state store
+pub type LockObserver = Arc<dyn Fn(LockEvent) + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockEvent {
+    Acquired {
+        address: SubstateId,
+        flag: LockFlag,
+        lock_id: LockId,
+    },
+    Released {
+        lock_id: LockId,
+    },
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct LockedSubstates {
     lock_ids: HashMap<LockId, SubstateId>,
@@ -89,6 +105,32 @@ impl LockedSubstates {
         Ok(())
     }
 
+    /// Upgrades the read lock with the given id to a write lock. This only succeeds if the lock is the only read lock
+    /// held on the substate. Upgrading a write lock is a no-op.
+    pub fn try_upgrade_lock(&mut self, lock_id: LockId) -> Result<(), LockError> {
+        let addr = self
+            .lock_ids
+            .get(&lock_id)
+            .ok_or(LockError::LockIdNotFound { lock_id })?;
+
+        let lock_state = self.locks.get_mut(addr).ok_or_else(|| LockError::InvariantError {
+            function: "LockedSubstates::try_upgrade_lock",
+            details: format!("Lock id {lock_id} was found but the address {addr} did not exist in the locks map"),
+        })?;
+
+        match lock_state {
+            LockState::Read(1) | LockState::Write => {
+                *lock_state = LockState::Write;
+                Ok(())
+            },
+            LockState::Read(_) => Err(LockError::InvalidLockRequest {
+                address: addr.clone(),
+                requested_lock: LockFlag::Write,
+                lock_state: *lock_state,
+            }),
+        }
+    }
+
     pub fn get(&self, lock_id: LockId, lock_flag: LockFlag) -> Result<LockedSubstate, LockError> {
         let addr = self
             .lock_ids
@@ -241,3 +283,52 @@ impl IsNotFoundError for LockError {
         matches!(self, Self::LockIdNotFound { .. })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn substate_id() -> SubstateId {
+        "component_0000000000000000000000000000000000000000000000000000000000000000"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn it_upgrades_a_sole_read_lock() {
+        let mut locks = LockedSubstates::default();
+        let lock_id = locks.try_lock(&substate_id(), LockFlag::Read).unwrap();
+        locks.get(lock_id, LockFlag::Write).unwrap_err();
+
+        locks.try_upgrade_lock(lock_id).unwrap();
+        locks.get(lock_id, LockFlag::Write).unwrap();
+        let err = locks.try_lock(&substate_id(), LockFlag::Read).unwrap_err();
+        assert!(matches!(err, LockError::InvalidLockRequest { .. }));
+
+        // Upgrading a write lock is a no-op
+        locks.try_upgrade_lock(lock_id).unwrap();
+        locks.try_unlock(lock_id).unwrap();
+        locks.try_lock(&substate_id(), LockFlag::Write).unwrap();
+    }
+
+    #[test]
+    fn it_does_not_upgrade_a_shared_read_lock() {
+        let mut locks = LockedSubstates::default();
+        let lock_id = locks.try_lock(&substate_id(), LockFlag::Read).unwrap();
+        let other_lock_id = locks.try_lock(&substate_id(), LockFlag::Read).unwrap();
+
+        let err = locks.try_upgrade_lock(lock_id).unwrap_err();
+        assert!(matches!(err, LockError::InvalidLockRequest {
+            requested_lock: LockFlag::Write,
+            lock_state: LockState::Read(2),
+            ..
+        }));
+        locks.get(lock_id, LockFlag::Read).unwrap();
+
+        locks.try_unlock(other_lock_id).unwrap();
+        locks.try_upgrade_lock(lock_id).unwrap();
+
+        let err = locks.try_upgrade_lock(LockId::MAX).unwrap_err();
+        assert!(err.is_not_found_error());
+    }
+}
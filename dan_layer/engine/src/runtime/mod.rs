@@ -43,7 +43,7 @@ mod tracker;
 
 mod locking;
 pub mod scope;
-pub use locking::{LockError, LockState};
+pub use locking::{LockError, LockEvent, LockObserver, LockState};
 mod address_allocation;
 mod state_store;
 mod tracker_auth;
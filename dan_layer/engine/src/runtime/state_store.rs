@@ -1,21 +1,25 @@
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
-use std::{collections::HashMap, mem};
+use std::{collections::HashMap, fmt, mem};
 
 use indexmap::IndexMap;
 use tari_dan_common_types::optional::Optional;
 use tari_engine_types::{
     component::ComponentHeader,
+    hashing::substate_value_hasher32,
     lock::{LockFlag, LockId},
     substate::{Substate, SubstateId, SubstateValue},
     vault::Vault,
 };
-use tari_template_lib::models::{ComponentAddress, VaultId};
+use tari_template_lib::{
+    models::{ComponentAddress, VaultId},
+    Hash,
+};
 
 use crate::{
     runtime::{
-        locking::{LockError, LockedSubstates},
+        locking::{LockError, LockEvent, LockObserver, LockedSubstates},
         RuntimeError,
     },
     state_store::{memory::MemoryStateStore, AtomicDb, StateReader},
@@ -28,6 +32,7 @@ pub struct WorkingStateStore {
 
     loaded_substates: HashMap<SubstateId, SubstateValue>,
     locked_substates: LockedSubstates,
+    lock_observer: Option<LockObserverHandle>,
 
     state_store: MemoryStateStore,
 }
@@ -38,10 +43,17 @@ impl WorkingStateStore {
             new_substates: IndexMap::new(),
             loaded_substates: HashMap::new(),
             locked_substates: Default::default(),
+            lock_observer: None,
             state_store,
         }
     }
 
+    /// Sets a callback that is invoked every time a substate lock is acquired or released, or removes it if None.
+    /// This is intended for tracing lock ordering when debugging.
+    pub fn set_lock_observer(&mut self, observer: Option<LockObserver>) {
+        self.lock_observer = observer.map(LockObserverHandle);
+    }
+
     pub fn try_lock(&mut self, address: &SubstateId, lock_flag: LockFlag) -> Result<LockId, RuntimeError> {
         if !self.exists(address)? {
             return Err(RuntimeError::SubstateNotFound {
@@ -50,11 +62,21 @@ impl WorkingStateStore {
         }
         let lock_id = self.locked_substates.try_lock(address, lock_flag)?;
         self.load(address)?;
+        if let Some(observer) = &self.lock_observer {
+            (observer.0)(LockEvent::Acquired {
+                address: address.clone(),
+                flag: lock_flag,
+                lock_id,
+            });
+        }
         Ok(lock_id)
     }
 
     pub fn try_unlock(&mut self, lock_id: LockId) -> Result<(), LockError> {
         self.locked_substates.try_unlock(lock_id)?;
+        if let Some(observer) = &self.lock_observer {
+            (observer.0)(LockEvent::Released { lock_id });
+        }
         Ok(())
     }
 
@@ -174,6 +196,38 @@ impl WorkingStateStore {
         &self.new_substates
     }
 
+    /// Returns the mutated substates in canonical order (by encoded substate address), regardless of the order in
+    /// which they were mutated. Nodes that mutate the same substates always produce the same sequence.
+    pub fn mutated_substates_sorted(&self) -> Vec<(SubstateId, &SubstateValue)> {
+        let mut substates = self
+            .new_substates
+            .iter()
+            .map(|(id, value)| (id.clone(), value))
+            .collect::<Vec<_>>();
+        substates.sort_by_cached_key(|(id, _)| id.to_bytes());
+        substates
+    }
+
+    /// Returns a hash of the mutated substates that does not depend on the order in which they were mutated. Each
+    /// substate contributes its id and the hash of its value, in the order given by
+    /// [WorkingStateStore::mutated_substates_sorted].
+    pub fn diff_hash(&self) -> Hash {
+        self.mutated_substates_sorted()
+            .into_iter()
+            .fold(substate_value_hasher32(), |hasher, (id, value)| {
+                let value_hash = substate_value_hasher32().chain(value).result();
+                hasher.chain(&id).chain(&value_hash)
+            })
+            .result()
+    }
+
+    /// Runs `f` against a throwaway copy of this store and returns its result. All mutations made by `f` are discarded,
+    /// so this store is unchanged. This is useful to simulate execution without affecting the working state.
+    pub fn with_scratch<R, F: FnOnce(&mut WorkingStateStore) -> R>(&self, f: F) -> R {
+        let mut scratch = self.clone();
+        f(&mut scratch)
+    }
+
     pub fn new_vaults(&self) -> impl Iterator<Item = (VaultId, &Vault)> + '_ {
         self.new_substates
             .iter()
@@ -205,3 +259,122 @@ impl WorkingStateStore {
             })
     }
 }
+
+#[derive(Clone)]
+struct LockObserverHandle(LockObserver);
+
+impl fmt::Debug for LockObserverHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LockObserver")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tari_engine_types::non_fungible_index::NonFungibleIndex;
+    use tari_template_lib::{
+        constants::XTR,
+        models::{NonFungibleAddress, NonFungibleId, NonFungibleIndexAddress},
+    };
+
+    use super::*;
+
+    fn create_store_with_indexes<I: IntoIterator<Item = u64>>(indexes: I) -> WorkingStateStore {
+        let mut store = WorkingStateStore::new(MemoryStateStore::default());
+        for index in indexes {
+            let id = SubstateId::NonFungibleIndex(NonFungibleIndexAddress::new(XTR, index));
+            let value = SubstateValue::NonFungibleIndex(NonFungibleIndex::new(NonFungibleAddress::new(
+                XTR,
+                NonFungibleId::from_u64(index),
+            )));
+            store.insert(id, value).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn mutated_substates_sorted_is_independent_of_insertion_order() {
+        let store_a = create_store_with_indexes([3, 1, 2, 300]);
+        let store_b = create_store_with_indexes([2, 300, 3, 1]);
+
+        let ids_a = store_a
+            .mutated_substates_sorted()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        let ids_b = store_b
+            .mutated_substates_sorted()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids_a, ids_b);
+        assert!(ids_a.windows(2).all(|w| w[0].to_bytes() < w[1].to_bytes()));
+
+        // The insertion order is preserved for existing callers
+        assert_ne!(
+            store_a.mutated_substates().keys().collect::<Vec<_>>(),
+            store_b.mutated_substates().keys().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn diff_hash_is_independent_of_insertion_order() {
+        let store_a = create_store_with_indexes([3, 1, 2, 300]);
+        let store_b = create_store_with_indexes([2, 300, 3, 1]);
+        assert_eq!(store_a.diff_hash(), store_b.diff_hash());
+
+        let store_c = create_store_with_indexes([3, 1, 2]);
+        assert_ne!(store_a.diff_hash(), store_c.diff_hash());
+    }
+
+    #[test]
+    fn with_scratch_discards_mutations() {
+        let store = create_store_with_indexes([1, 2]);
+        let id = SubstateId::NonFungibleIndex(NonFungibleIndexAddress::new(XTR, 3));
+        let value = SubstateValue::NonFungibleIndex(NonFungibleIndex::new(NonFungibleAddress::new(
+            XTR,
+            NonFungibleId::from_u64(3),
+        )));
+
+        let num_mutated = store.with_scratch(|scratch| {
+            scratch.insert(id.clone(), value).unwrap();
+            scratch.mutated_substates().len()
+        });
+        assert_eq!(num_mutated, 3);
+        assert_eq!(store.mutated_substates().len(), 2);
+        assert!(!store.exists(&id).unwrap());
+    }
+
+    #[test]
+    fn it_notifies_the_lock_observer() {
+        let id = SubstateId::NonFungibleIndex(NonFungibleIndexAddress::new(XTR, 1));
+        let mut store = create_store_with_indexes([1]);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        store.set_lock_observer(Some(Arc::new(move |event: LockEvent| {
+            events_clone.lock().unwrap().push(event)
+        })));
+
+        let read_lock = store.try_lock(&id, LockFlag::Read).unwrap();
+        store.try_unlock(read_lock).unwrap();
+        let write_lock = store.try_lock(&id, LockFlag::Write).unwrap();
+        // Failed lock requests are not observed
+        store.try_lock(&id, LockFlag::Read).unwrap_err();
+
+        assert_eq!(*events.lock().unwrap(), vec![
+            LockEvent::Acquired {
+                address: id.clone(),
+                flag: LockFlag::Read,
+                lock_id: read_lock,
+            },
+            LockEvent::Released { lock_id: read_lock },
+            LockEvent::Acquired {
+                address: id,
+                flag: LockFlag::Write,
+                lock_id: write_lock,
+            },
+        ]);
+    }
+}
@@ -50,7 +50,7 @@ use tari_template_lib::{
 
 use crate::{
     runtime::{
-        locking::LockedSubstate,
+        locking::{LockObserver, LockedSubstate},
         scope::{CallScope, PushCallFrame},
         working_state::WorkingState,
         workspace::Workspace,
@@ -85,6 +85,11 @@ impl StateTracker {
         }
     }
 
+    /// Sets a callback that is invoked every time a substate lock is acquired or released
+    pub fn set_lock_observer(&self, observer: Option<LockObserver>) {
+        self.write_with(|state| state.set_lock_observer(observer));
+    }
+
     pub fn get_current_epoch(&self) -> Result<Epoch, RuntimeError> {
         self.read_with(|state| state.get_current_epoch())
     }
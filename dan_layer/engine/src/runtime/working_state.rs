@@ -56,7 +56,7 @@ use crate::{
     runtime::{
         address_allocation::AllocatedAddress,
         fee_state::FeeState,
-        locking::LockedSubstate,
+        locking::{LockObserver, LockedSubstate},
         scope::{CallFrame, CallScope},
         state_store::WorkingStateStore,
         tracker_auth::Authorization,
@@ -123,6 +123,10 @@ impl WorkingState {
         }
     }
 
+    pub fn set_lock_observer(&mut self, observer: Option<LockObserver>) {
+        self.store.set_lock_observer(observer);
+    }
+
     pub fn transaction_hash(&self) -> Hash {
         self.transaction_hash
     }
@@ -838,6 +842,14 @@ impl WorkingState {
         self.store.mutated_substates()
     }
 
+    pub fn mutated_substates_sorted(&self) -> Vec<(SubstateId, &SubstateValue)> {
+        self.store.mutated_substates_sorted()
+    }
+
+    pub fn diff_hash(&self) -> Hash {
+        self.store.diff_hash()
+    }
+
     pub fn fee_state(&self) -> &FeeState {
         &self.fee_state
     }
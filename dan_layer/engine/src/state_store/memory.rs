@@ -21,6 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use std::{
     collections::HashMap,
+    ops::Deref,
     sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
 
@@ -52,6 +53,16 @@ impl MemoryStateStore {
         }
         state.commit()
     }
+
+    /// Passes a borrow of the raw value stored at `key` to `f`, without cloning it. This avoids copying large values
+    /// when only a read is required; use [StateReader::get_state_raw] if ownership of the value is needed.
+    ///
+    /// The store is read-locked while `f` runs, so `f` MUST NOT call back into the store. Requesting write access from
+    /// within `f` will deadlock.
+    pub fn with_state_raw<R, F: FnOnce(&[u8]) -> R>(&self, key: &[u8], f: F) -> Result<R, StateStoreError> {
+        let access = self.read_access()?;
+        access.with_state_raw(key, f)
+    }
 }
 
 impl Default for MemoryStateStore {
@@ -62,6 +73,15 @@ impl Default for MemoryStateStore {
     }
 }
 
+/// Populates a new store directly from raw key/value pairs, without going through a write transaction.
+impl FromIterator<(Vec<u8>, Vec<u8>)> for MemoryStateStore {
+    fn from_iter<T: IntoIterator<Item = (Vec<u8>, Vec<u8>)>>(iter: T) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(iter.into_iter().collect())),
+        }
+    }
+}
+
 pub type MemoryReadTransaction<'a> = MemoryTransaction<RwLockReadGuard<'a, InnerKvMap>>;
 pub type MemoryWriteTransaction<'a> = MemoryTransaction<RwLockWriteGuard<'a, InnerKvMap>>;
 
@@ -70,6 +90,30 @@ pub struct MemoryTransaction<T> {
     guard: T,
 }
 
+impl<T: Deref<Target = InnerKvMap>> MemoryTransaction<T> {
+    /// Passes a borrow of the raw value stored at `key` (including pending changes) to `f`, without cloning it.
+    pub fn with_state_raw<R, F: FnOnce(&[u8]) -> R>(&self, key: &[u8], f: F) -> Result<R, StateStoreError> {
+        let value = self
+            .pending
+            .get(key)
+            .or_else(|| self.guard.get(key))
+            .ok_or_else(|| StateStoreError::NotFound {
+                kind: "state",
+                key: to_hex(key),
+            })?;
+        Ok(f(value))
+    }
+
+    fn get_many_raw_inner<'k, I: IntoIterator<Item = &'k [u8]>>(&self, keys: I) -> Vec<(Vec<u8>, Option<Vec<u8>>)> {
+        keys.into_iter()
+            .map(|key| {
+                let value = self.pending.get(key).or_else(|| self.guard.get(key)).cloned();
+                (key.to_vec(), value)
+            })
+            .collect()
+    }
+}
+
 impl MemoryTransaction<RwLockReadGuard<'_, InnerKvMap>> {
     pub fn iter_raw(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
         self.pending.iter().map(|(k, v)| (k.as_slice(), v.as_slice())).chain(
@@ -117,6 +161,13 @@ impl<'a> StateReader for MemoryTransaction<RwLockReadGuard<'a, InnerKvMap>> {
             })
     }
 
+    fn get_many_raw<'k, I: IntoIterator<Item = &'k [u8]>>(
+        &self,
+        keys: I,
+    ) -> Result<Vec<(Vec<u8>, Option<Vec<u8>>)>, StateStoreError> {
+        Ok(self.get_many_raw_inner(keys))
+    }
+
     fn exists_raw(&self, key: &[u8]) -> Result<bool, StateStoreError> {
         Ok(self.pending.contains_key(key) || self.guard.contains_key(key))
     }
@@ -134,6 +185,13 @@ impl<'a> StateReader for MemoryTransaction<RwLockWriteGuard<'a, InnerKvMap>> {
             })
     }
 
+    fn get_many_raw<'k, I: IntoIterator<Item = &'k [u8]>>(
+        &self,
+        keys: I,
+    ) -> Result<Vec<(Vec<u8>, Option<Vec<u8>>)>, StateStoreError> {
+        Ok(self.get_many_raw_inner(keys))
+    }
+
     fn exists_raw(&self, key: &[u8]) -> Result<bool, StateStoreError> {
         Ok(self.pending.contains_key(key) || self.guard.contains_key(key))
     }
@@ -181,6 +239,35 @@ mod tests {
         assert_eq!(res, None);
     }
 
+    #[test]
+    fn with_state_raw() {
+        let store = MemoryStateStore::from_iter([(b"abc".to_vec(), vec![1, 2, 3])]);
+        let len = store.with_state_raw(b"abc", |value| value.len()).unwrap();
+        assert_eq!(len, 3);
+        let res = store.with_state_raw(b"def", |value| value.len()).optional().unwrap();
+        assert_eq!(res, None);
+
+        // Pending changes are visible within a transaction
+        let mut access = store.write_access().unwrap();
+        access.set_state_raw(b"abc", vec![4]).unwrap();
+        assert_eq!(access.with_state_raw(b"abc", |value| value.to_vec()).unwrap(), vec![4]);
+    }
+
+    #[test]
+    fn from_iter() {
+        let mut source = HashMap::new();
+        source.insert(b"abc".to_vec(), vec![1, 2, 3]);
+        source.insert(b"def".to_vec(), vec![4, 5, 6]);
+
+        let store = MemoryStateStore::from_iter(source.clone());
+        source.insert(b"abc".to_vec(), vec![7, 8, 9]);
+
+        let access = store.read_access().unwrap();
+        assert_eq!(access.get_state_raw(b"abc").unwrap(), vec![1, 2, 3]);
+        assert_eq!(access.get_state_raw(b"def").unwrap(), vec![4, 5, 6]);
+        assert_eq!(access.iter_raw().count(), 2);
+    }
+
     #[test]
     fn read_write_rollback_commit() {
         #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
@@ -221,4 +308,25 @@ mod tests {
         let res: UserData = access.get_state(b"abc").unwrap();
         assert_eq!(res, user_data);
     }
+
+    #[test]
+    fn get_many_raw() {
+        let store = MemoryStateStore::default();
+        {
+            let mut access = store.write_access().unwrap();
+            access.set_state_raw(b"abc", vec![1]).unwrap();
+            access.commit().unwrap();
+        }
+
+        let mut access = store.write_access().unwrap();
+        access.set_state_raw(b"def", vec![2]).unwrap();
+        let res = access
+            .get_many_raw([b"def".as_slice(), b"ghi".as_slice(), b"abc".as_slice()])
+            .unwrap();
+        assert_eq!(res, vec![
+            (b"def".to_vec(), Some(vec![2])),
+            (b"ghi".to_vec(), None),
+            (b"abc".to_vec(), Some(vec![1])),
+        ]);
+    }
 }
@@ -28,7 +28,7 @@ use std::{error::Error, fmt::Debug};
 
 use serde::{de::DeserializeOwned, Serialize};
 use tari_bor::{decode_exact, encode, BorError};
-use tari_dan_common_types::optional::IsNotFoundError;
+use tari_dan_common_types::optional::{IsNotFoundError, Optional};
 
 // pub trait StateStorage<'a>: AtomicDb<'a, Error = StateStoreError> + Send + Sync {}
 //
@@ -66,6 +66,16 @@ pub trait StateReader {
         Ok(value)
     }
 
+    /// Returns the values for the given keys in the same order as the keys. Keys that do not exist map to None.
+    fn get_many_raw<'k, I: IntoIterator<Item = &'k [u8]>>(
+        &self,
+        keys: I,
+    ) -> Result<Vec<(Vec<u8>, Option<Vec<u8>>)>, StateStoreError> {
+        keys.into_iter()
+            .map(|key| Ok((key.to_vec(), self.get_state_raw(key).optional()?)))
+            .collect()
+    }
+
     fn exists_raw(&self, key: &[u8]) -> Result<bool, StateStoreError>;
     fn exists<K: Serialize + Debug>(&self, key: &K) -> Result<bool, StateStoreError> {
         self.exists_raw(&encode(key)?)
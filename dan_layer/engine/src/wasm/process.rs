@@ -35,6 +35,7 @@ use tari_template_lib::{
         EmitEventArg,
         EmitLogArg,
         GenerateRandomInvokeArg,
+        InvalidCallArgumentArg,
         LogLevel,
         NonFungibleInvokeArg,
         ProofInvokeArg,
@@ -48,7 +49,7 @@ use wasmer::{Function, Instance, Module, Val, WasmerEnv};
 
 use super::version::are_versions_compatible;
 use crate::{
-    runtime::Runtime,
+    runtime::{Runtime, RuntimeError},
     traits::Invokable,
     wasm::{
         environment::{AllocPtr, WasmEnv},
@@ -172,6 +173,14 @@ impl WasmProcess {
             EngineOp::BuiltinTemplateInvoke => Self::handle(env, arg, |env, arg: BuiltinTemplateInvokeArg| {
                 env.state().interface().builtin_template_invoke(arg.action)
             }),
+            EngineOp::InvalidCallArgument => Self::handle(env, arg, |_env, arg: InvalidCallArgumentArg| {
+                Err::<(), _>(RuntimeError::InvalidCallArgument {
+                    function: arg.function,
+                    arg_index: arg.arg_index,
+                    expected_type: arg.expected_type,
+                    details: arg.details,
+                })
+            }),
         };
 
         result.unwrap_or_else(|err| {
@@ -0,0 +1,71 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_bor::{decode_exact, encode};
+use tari_engine_types::instruction::Instruction;
+use tari_template_lib::{args::Arg, models::ObjectKey, prelude::*};
+
+#[template(gen_client)]
+mod state_template {
+    use super::*;
+
+    pub struct State {
+        value: u32,
+    }
+
+    impl State {
+        pub fn new() -> Component<Self> {
+            Component::new(Self { value: 0 })
+                .with_access_rules(AccessRules::new().default(AccessRule::AllowAll))
+                .create()
+        }
+
+        pub fn set(&mut self, value: u32) {
+            self.value = value;
+        }
+
+        pub fn get(&self) -> u32 {
+            self.value
+        }
+    }
+}
+
+#[test]
+fn it_builds_a_call_method_instruction_with_encoded_args() {
+    let component_address = ComponentAddress::from_array([1; ObjectKey::LENGTH]);
+
+    let instruction = State_client::StateClient::set(component_address, 5);
+
+    let (called_address, method, args) = match instruction {
+        Instruction::CallMethod {
+            component_address,
+            method,
+            args,
+        } => (component_address, method, args),
+        instruction => panic!("Expected a CallMethod instruction but got {instruction:?}"),
+    };
+    assert_eq!(called_address, component_address);
+    assert_eq!(method, "set");
+    assert_eq!(args, vec![Arg::Literal(encode(&5u32).unwrap())]);
+    let bytes = args[0].as_literal_bytes().expect("Expected a literal argument");
+    assert_eq!(decode_exact::<u32>(bytes).unwrap(), 5);
+}
+
+#[test]
+fn it_builds_a_call_function_instruction_for_functions_without_a_receiver() {
+    let template_address = TemplateAddress::from_array([2; 32]);
+
+    let instruction = State_client::StateClient::new(template_address);
+
+    let (called_address, function, args) = match instruction {
+        Instruction::CallFunction {
+            template_address,
+            function,
+            args,
+        } => (template_address, function, args),
+        instruction => panic!("Expected a CallFunction instruction but got {instruction:?}"),
+    };
+    assert_eq!(called_address, template_address);
+    assert_eq!(function, "new");
+    assert!(args.is_empty());
+}
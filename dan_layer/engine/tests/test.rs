@@ -383,8 +383,9 @@ mod errors {
         match result.finalize.result.full_reject().unwrap() {
             RejectReason::ExecutionFailure(message) => {
                 assert!(message.starts_with(
-                    "Panic! failed to decode argument at position 0 for function 'please_pass_invalid_args':"
-                ),);
+                    "Runtime error: Invalid argument 0 for function 'please_pass_invalid_args': expected type \
+                     `Amount`:"
+                ));
             },
             reason => panic!("Unexpected failure reason: {}", reason),
         }
@@ -10,6 +10,49 @@ use ts_rs::TS;
 
 use crate::resource_container::ResourceContainer;
 
+/// The value that fees are divided by to determine the amount of fees to burn. 0 means no fees are burned.
+/// This is a placeholder for the fee exhaust consensus constant so that we know where it's used later.
+pub const EXHAUST_DIVISOR: u64 = 20; // 5%
+
+/// The cost per byte of storage is divided by this value.
+// TODO: Cost per byte of storage is reduced by a pretty arbitrarily chosen factor (floor(cost/0.333...))
+pub const STORAGE_COST_REDUCTION_DIVISOR: u64 = 3;
+
+#[derive(Debug, Clone)]
+pub struct FeeTable {
+    pub per_module_call_cost: u64,
+    pub per_byte_storage_cost: u64,
+    pub per_event_cost: u64,
+    pub per_log_cost: u64,
+}
+
+impl FeeTable {
+    pub fn zero_rated() -> Self {
+        Self {
+            per_module_call_cost: 0,
+            per_byte_storage_cost: 0,
+            per_event_cost: 0,
+            per_log_cost: 0,
+        }
+    }
+
+    pub fn per_module_call_cost(&self) -> u64 {
+        self.per_module_call_cost
+    }
+
+    pub fn per_byte_storage_cost(&self) -> u64 {
+        self.per_byte_storage_cost
+    }
+
+    pub fn per_event_cost(&self) -> u64 {
+        self.per_event_cost
+    }
+
+    pub fn per_log_cost(&self) -> u64 {
+        self.per_log_cost
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[cfg_attr(feature = "ts", derive(TS), ts(export, export_to = "../../bindings/src/types/"))]
 pub struct FeeReceipt {
@@ -399,6 +399,46 @@ where
         transaction_hash.ok_or_else(|| IndexerError::NotFoundTransaction(substate_id.clone(), version))
     }
 
+    /// Returns the addresses of the committee members responsible for the given substate version, in random order
+    pub async fn get_shuffled_committee_for_substate(
+        &self,
+        substate_id: &SubstateId,
+        version: u32,
+    ) -> Result<Vec<TAddr>, IndexerError> {
+        let substate_address = SubstateAddress::from_substate_id(substate_id, version);
+        let epoch = self.committee_provider.current_epoch().await?;
+        let mut committee = self
+            .committee_provider
+            .get_committee_for_substate(epoch, substate_address)
+            .await?;
+        committee.shuffle();
+        Ok(committee.into_addresses().collect())
+    }
+
+    /// Queries a single validator node for the transaction that created the substate version and returns the events
+    /// that the transaction emitted for the substate. Returns [IndexerError::NotFoundTransaction] if the validator
+    /// node does not have the substate version.
+    pub async fn get_events_for_substate_and_version_from_vn(
+        &self,
+        vn_addr: &TAddr,
+        substate_id: &SubstateId,
+        version: u32,
+    ) -> Result<Vec<Event>, IndexerError> {
+        let substate_address = SubstateAddress::from_substate_id(substate_id, version);
+        let transaction_id = match self.get_substate_from_vn(vn_addr, substate_address).await? {
+            SubstateResult::Up { created_by_tx, .. } | SubstateResult::Down { created_by_tx, .. } => created_by_tx,
+            SubstateResult::DoesNotExist => {
+                return Err(IndexerError::NotFoundTransaction(substate_id.clone(), version))
+            },
+        };
+
+        let tx_events = self.get_events_for_transaction(transaction_id).await?;
+        Ok(tx_events
+            .into_iter()
+            .filter(|e| e.substate_id().as_ref() == Some(substate_id))
+            .collect())
+    }
+
     /// Queries the network to obtain all the events associated with a substate and
     /// a specific version.
     pub async fn get_events_for_substate_and_version(
@@ -273,6 +273,7 @@ impl From<&tari_dan_storage::consensus_models::Block> for proto::consensus::Bloc
             base_layer_block_height: value.base_layer_block_height(),
             base_layer_block_hash: value.base_layer_block_hash().as_bytes().to_vec(),
             is_dummy: value.is_dummy(),
+            command_merkle_root: value.command_merkle_root().as_slice().to_vec(),
         }
     }
 }
@@ -310,7 +311,7 @@ impl TryFrom<proto::consensus::Block> for tari_dan_storage::consensus_models::Bl
                 value.base_layer_block_hash.try_into()?,
             ))
         } else {
-            Ok(Self::new(
+            let mut block = Self::new(
                 network,
                 value.parent_id.try_into()?,
                 justify,
@@ -330,7 +331,13 @@ impl TryFrom<proto::consensus::Block> for tari_dan_storage::consensus_models::Bl
                 value.timestamp,
                 value.base_layer_block_height,
                 value.base_layer_block_hash.try_into()?,
-            ))
+            );
+            // Peers that do not send the command merkle root yet are tolerated during rollout. In that case the root
+            // derived from the commands is used.
+            if !value.command_merkle_root.is_empty() {
+                block.set_command_merkle_root(value.command_merkle_root.try_into()?);
+            }
+            Ok(block)
         }
     }
 }
@@ -0,0 +1,21 @@
+-- Normalized view of transactions.resulting_outputs, allowing efficient lookup of the transaction(s) that produced a
+-- substate. The JSON column is retained for backwards compatibility.
+create table transaction_outputs
+(
+    id             integer   not null primary key AUTOINCREMENT,
+    transaction_id text      not null,
+    substate_id    text      not null,
+    version        integer   not null,
+    created_at     timestamp not null default current_timestamp,
+    FOREIGN KEY (transaction_id) REFERENCES transactions (transaction_id)
+);
+
+create unique index transaction_outputs_uniq_idx_transaction_id_substate_id_version on transaction_outputs (transaction_id, substate_id, version);
+create index transaction_outputs_idx_substate_id_version on transaction_outputs (substate_id, version);
+
+-- Backfill from existing transactions
+insert into transaction_outputs (transaction_id, substate_id, version)
+select t.transaction_id, json_extract(o.value, '$.substate_id'), json_extract(o.value, '$.version')
+from transactions t,
+     json_each(t.resulting_outputs) o
+where t.resulting_outputs is not null;
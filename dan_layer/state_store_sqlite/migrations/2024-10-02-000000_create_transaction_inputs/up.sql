@@ -0,0 +1,25 @@
+-- Normalized view of transactions.resolved_inputs, allowing conflict and dependency queries by substate without
+-- decoding the JSON column for every transaction. The JSON column is retained for backwards compatibility.
+create table transaction_inputs
+(
+    id             integer   not null primary key AUTOINCREMENT,
+    transaction_id text      not null,
+    substate_id    text      not null,
+    version        integer   not null,
+    lock_flag      text      not null,
+    created_at     timestamp not null default current_timestamp,
+    FOREIGN KEY (transaction_id) REFERENCES transactions (transaction_id)
+);
+
+create unique index transaction_inputs_uniq_idx_transaction_id_substate_id_version on transaction_inputs (transaction_id, substate_id, version);
+create index transaction_inputs_idx_substate_id on transaction_inputs (substate_id);
+
+-- Backfill from existing transactions
+insert into transaction_inputs (transaction_id, substate_id, version, lock_flag)
+select t.transaction_id,
+       json_extract(i.value, '$.versioned_substate_id.substate_id'),
+       json_extract(i.value, '$.versioned_substate_id.version'),
+       json_extract(i.value, '$.lock_flag')
+from transactions t,
+     json_each(t.resolved_inputs) i
+where t.resolved_inputs is not null;
@@ -0,0 +1,2 @@
+alter table quorum_certificates
+    drop column signature_count;
@@ -0,0 +1,7 @@
+-- Allows the number of signatures in a QC to be queried without decoding the JSON column
+alter table quorum_certificates
+    add column signature_count integer not null default 0;
+
+-- Backfill from existing QCs
+update quorum_certificates
+set signature_count = json_array_length(json, '$.signatures');
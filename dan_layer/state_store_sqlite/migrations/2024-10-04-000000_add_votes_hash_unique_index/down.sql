@@ -0,0 +1 @@
+drop index votes_uniq_idx_hash;
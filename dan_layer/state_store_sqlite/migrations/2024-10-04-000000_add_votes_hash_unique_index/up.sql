@@ -0,0 +1,7 @@
+-- Remove any duplicate votes, keeping the first one inserted, so that the unique index can be created
+delete
+from votes
+where id not in (select min(id) from votes group by hash);
+
+-- Allows duplicate votes to be skipped when inserting in bulk
+create unique index votes_uniq_idx_hash on votes (hash);
@@ -0,0 +1 @@
+drop table rejected_blocks;
@@ -0,0 +1,11 @@
+-- Blocks that failed proposal validation, kept for post-mortem analysis
+create table rejected_blocks
+(
+    id         integer   not null primary key AUTOINCREMENT,
+    block_id   text      not null,
+    height     bigint    not null,
+    reason     text      not null,
+    created_at timestamp not null default current_timestamp
+);
+
+create index rejected_blocks_idx_created_at on rejected_blocks (created_at);
@@ -0,0 +1,3 @@
+-- The block that finalized the transaction, used to attribute finalized transactions (e.g. aborts) to an epoch
+alter table transactions
+    add column finalized_in_block text null;
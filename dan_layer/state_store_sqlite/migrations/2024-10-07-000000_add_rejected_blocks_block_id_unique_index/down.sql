@@ -0,0 +1 @@
+drop index rejected_blocks_uniq_idx_block_id;
@@ -0,0 +1,7 @@
+-- Remove any duplicate rejected blocks, keeping the first one recorded, so that the unique index can be created
+delete
+from rejected_blocks
+where id not in (select min(id) from rejected_blocks group by block_id);
+
+-- Allows a block that has already been recorded to be skipped
+create unique index rejected_blocks_uniq_idx_block_id on rejected_blocks (block_id);
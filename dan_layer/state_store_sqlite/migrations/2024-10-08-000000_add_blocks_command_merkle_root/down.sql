@@ -0,0 +1,4 @@
+alter table blocks
+    drop column command_merkle_root;
+alter table parked_blocks
+    drop column command_merkle_root;
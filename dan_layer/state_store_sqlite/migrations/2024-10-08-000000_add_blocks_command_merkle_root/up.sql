@@ -0,0 +1,5 @@
+-- Null for blocks stored before the command merkle root was persisted
+alter table blocks
+    add column command_merkle_root text null;
+alter table parked_blocks
+    add column command_merkle_root text null;
@@ -0,0 +1,4 @@
+drop table pending_transaction_aborts;
+
+alter table transactions
+    drop column abort_reason;
@@ -0,0 +1,18 @@
+-- The structured reason that a transaction was aborted. The free-form details remain in abort_details.
+alter table transactions
+    add column abort_reason text null;
+
+-- Aborts decided while voting on a block. These are applied to the transaction once the block is committed.
+create table pending_transaction_aborts
+(
+    id             integer   not null primary key AUTOINCREMENT,
+    block_id       text      not null,
+    block_height   bigint    not null,
+    transaction_id text      not null,
+    abort_reason   text      not null,
+    abort_details  text      not null,
+    created_at     timestamp not null default current_timestamp
+);
+
+create unique index pending_transaction_aborts_uniq_idx_block_id_transaction_id on pending_transaction_aborts (block_id, transaction_id);
+create index pending_transaction_aborts_idx_block_height on pending_transaction_aborts (block_height);
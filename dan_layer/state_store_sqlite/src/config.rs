@@ -0,0 +1,65 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use std::{fmt, time::Duration};
+
+/// The maximum number of host parameters in a single SQL statement (SQLITE_MAX_VARIABLE_NUMBER). This is the default
+/// for the SQLite version bundled with libsqlite3-sys (>= 3.32.0).
+pub const SQLITE_MAX_VARIABLE_NUMBER: usize = 32766;
+
+/// Connection settings applied as PRAGMAs when the store connects.
+#[derive(Debug, Clone)]
+pub struct SqliteStoreConfig {
+    /// How long to wait for a lock held by another connection before failing with `database is locked`
+    pub busy_timeout: Duration,
+    pub journal_mode: JournalMode,
+    pub synchronous: SynchronousMode,
+    /// The maximum number of bound variables in a single statement, used to chunk large inserts. This must not exceed
+    /// the SQLITE_MAX_VARIABLE_NUMBER that the linked SQLite library was compiled with.
+    pub max_sql_variables: usize,
+}
+
+impl Default for SqliteStoreConfig {
+    fn default() -> Self {
+        Self {
+            busy_timeout: Duration::from_secs(5),
+            journal_mode: JournalMode::Wal,
+            synchronous: SynchronousMode::Full,
+            max_sql_variables: SQLITE_MAX_VARIABLE_NUMBER,
+        }
+    }
+}
+
+/// The SQLite journal mode. WAL allows reads to proceed while a write is in progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalMode {
+    Wal,
+    Delete,
+}
+
+impl fmt::Display for JournalMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wal => write!(f, "WAL"),
+            Self::Delete => write!(f, "DELETE"),
+        }
+    }
+}
+
+/// How often SQLite syncs to disk. See <https://www.sqlite.org/pragma.html#pragma_synchronous>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SynchronousMode {
+    Off,
+    Normal,
+    Full,
+}
+
+impl fmt::Display for SynchronousMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Off => write!(f, "OFF"),
+            Self::Normal => write!(f, "NORMAL"),
+            Self::Full => write!(f, "FULL"),
+        }
+    }
+}
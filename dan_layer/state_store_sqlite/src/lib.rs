@@ -1,6 +1,7 @@
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
+mod config;
 mod error;
 mod reader;
 mod schema;
@@ -11,4 +12,6 @@ mod store;
 // mod tree_store;
 mod writer;
 
+pub use config::{JournalMode, SqliteStoreConfig, SynchronousMode, SQLITE_MAX_VARIABLE_NUMBER};
 pub use store::SqliteStateStore;
+pub use writer::MAX_REJECTED_BLOCKS;
@@ -14,7 +14,7 @@ use diesel::{
     dsl,
     query_builder::SqlQuery,
     sql_query,
-    sql_types::{BigInt, Text},
+    sql_types::{BigInt, Bool, Nullable, Text},
     BoolExpressionMethods,
     ExpressionMethods,
     JoinOnDsl,
@@ -33,15 +33,19 @@ use tari_common_types::types::{FixedHash, PublicKey};
 use tari_dan_common_types::{shard::Shard, Epoch, NodeAddressable, NodeHeight, ShardGroup, SubstateAddress};
 use tari_dan_storage::{
     consensus_models::{
+        AbortReason,
         Block,
+        BlockContext,
         BlockDiff,
         BlockId,
         Command,
+        Decision,
         EpochCheckpoint,
         ForeignProposal,
         ForeignProposalState,
         ForeignReceiveCounters,
         ForeignSendCounters,
+        FullTransactionView,
         HighQc,
         LastExecuted,
         LastProposed,
@@ -53,14 +57,19 @@ use tari_dan_storage::{
         PendingShardStateTreeDiff,
         QcId,
         QuorumCertificate,
+        RejectedBlock,
         StateTransition,
         StateTransitionId,
         SubstateChange,
+        SubstateLockFlag,
         SubstateRecord,
         TransactionExecution,
+        TransactionPoolHistoryEntry,
         TransactionPoolRecord,
+        TransactionPoolRecordWithUpdate,
         TransactionPoolStage,
         TransactionRecord,
+        VersionedSubstateIdLockIntent,
         Vote,
     },
     Ordering,
@@ -74,7 +83,14 @@ use tari_utilities::ByteArray;
 
 use crate::{
     error::SqliteStorageError,
-    serialization::{deserialize_hex_try_from, deserialize_json, serialize_hex, serialize_json},
+    serialization::{
+        deserialize_foreign_indexes,
+        deserialize_hex_try_from,
+        deserialize_json,
+        parse_from_string,
+        serialize_hex,
+        serialize_json,
+    },
     sql_models,
     sqlite_transaction::SqliteTransaction,
 };
@@ -83,17 +99,25 @@ const LOG_TARGET: &str = "tari::dan::storage::state_store_sqlite::reader";
 
 pub struct SqliteStateStoreReadTransaction<'a, TAddr> {
     transaction: SqliteTransaction<'a>,
+    max_sql_variables: usize,
     _addr: PhantomData<TAddr>,
 }
 
 impl<'a, TAddr> SqliteStateStoreReadTransaction<'a, TAddr> {
-    pub(crate) fn new(transaction: SqliteTransaction<'a>) -> Self {
+    pub(crate) fn new(transaction: SqliteTransaction<'a>, max_sql_variables: usize) -> Self {
         Self {
             transaction,
+            max_sql_variables,
             _addr: PhantomData,
         }
     }
 
+    /// Returns the maximum number of rows that can be bound in a single statement without exceeding the configured
+    /// variable limit, given the number of bound columns per row.
+    pub(crate) fn sql_variable_chunk_size(&self, columns_per_row: usize) -> usize {
+        (self.max_sql_variables / columns_per_row.max(1)).max(1)
+    }
+
     pub(crate) fn connection(&self) -> &mut SqliteConnection {
         self.transaction.connection()
     }
@@ -364,18 +388,19 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
 {
     type Addr = TAddr;
 
-    fn last_sent_vote_get(&self) -> Result<LastSentVote, StorageError> {
+    fn last_sent_vote_get(&self) -> Result<Option<LastSentVote>, StorageError> {
         use crate::schema::last_sent_vote;
 
-        let last_voted = last_sent_vote::table
+        let last_sent_vote = last_sent_vote::table
             .order_by(last_sent_vote::id.desc())
             .first::<sql_models::LastSentVote>(self.connection())
+            .optional()
             .map_err(|e| SqliteStorageError::DieselError {
                 operation: "last_sent_vote_get",
                 source: e,
             })?;
 
-        last_voted.try_into()
+        last_sent_vote.map(TryInto::try_into).transpose()
     }
 
     fn last_voted_get(&self) -> Result<LastVoted, StorageError> {
@@ -597,6 +622,46 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         Ok(exists > 0)
     }
 
+    fn transactions_get_full(&self, tx_id: &TransactionId) -> Result<FullTransactionView, StorageError> {
+        use crate::schema::{transaction_executions, transaction_pool};
+
+        // All reads below happen within this read transaction, so they observe a consistent snapshot
+        let record = self.transactions_get(tx_id)?;
+        let tx_id = serialize_hex(tx_id);
+
+        let latest_execution = transaction_executions::table
+            .filter(transaction_executions::transaction_id.eq(&tx_id))
+            .order_by(transaction_executions::id.desc())
+            .first::<sql_models::TransactionExecution>(self.connection())
+            .optional()
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_get_full",
+                source: e,
+            })?
+            .map(TryInto::try_into)
+            .transpose()?;
+
+        // A finalized transaction is removed from the pool, so this may not exist
+        let pool_record = transaction_pool::table
+            .filter(transaction_pool::transaction_id.eq(&tx_id))
+            .first::<sql_models::TransactionPoolRecord>(self.connection())
+            .optional()
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_get_full",
+                source: e,
+            })?
+            .map(|rec| rec.try_convert(None))
+            .transpose()?;
+
+        Ok(FullTransactionView {
+            final_decision: record.final_decision(),
+            record,
+            latest_execution,
+            pool_stage: pool_record.as_ref().map(|rec| rec.current_stage()),
+            evidence: pool_record.map(|rec| rec.evidence().clone()),
+        })
+    }
+
     fn transactions_get_any<'a, I: IntoIterator<Item = &'a TransactionId>>(
         &self,
         tx_ids: I,
@@ -619,6 +684,96 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
             .collect()
     }
 
+    fn transactions_get_by_output(&self, output: &VersionedSubstateId) -> Result<Vec<TransactionRecord>, StorageError> {
+        use crate::schema::{transaction_outputs, transactions};
+
+        let transactions = transactions::table
+            .inner_join(
+                transaction_outputs::table.on(transactions::transaction_id.eq(transaction_outputs::transaction_id)),
+            )
+            .select(transactions::all_columns)
+            .filter(transaction_outputs::substate_id.eq(output.substate_id.to_string()))
+            .filter(transaction_outputs::version.eq(output.version as i32))
+            .order_by(transactions::id.asc())
+            .get_results::<sql_models::Transaction>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_get_by_output",
+                source: e,
+            })?;
+
+        transactions
+            .into_iter()
+            .map(|transaction| transaction.try_into())
+            .collect()
+    }
+
+    fn transactions_get_by_output_substate(
+        &self,
+        substate_id: &SubstateId,
+    ) -> Result<Vec<TransactionRecord>, StorageError> {
+        use crate::schema::{transaction_outputs, transactions};
+
+        // transaction_outputs is kept in sync with transactions.resulting_outputs and is indexed by (substate_id,
+        // version), so this avoids a full scan of the JSON column
+        let transactions = transactions::table
+            .select(transactions::all_columns)
+            .filter(
+                transactions::transaction_id.eq_any(
+                    transaction_outputs::table
+                        .select(transaction_outputs::transaction_id)
+                        .filter(transaction_outputs::substate_id.eq(substate_id.to_string())),
+                ),
+            )
+            .order_by(transactions::id.asc())
+            .get_results::<sql_models::Transaction>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_get_by_output_substate",
+                source: e,
+            })?;
+
+        transactions
+            .into_iter()
+            .map(|transaction| transaction.try_into())
+            .collect()
+    }
+
+    fn transactions_get_ids_by_input(&self, substate_id: &SubstateId) -> Result<Vec<TransactionId>, StorageError> {
+        use crate::schema::transaction_inputs;
+
+        let transaction_ids = transaction_inputs::table
+            .select(transaction_inputs::transaction_id)
+            .filter(transaction_inputs::substate_id.eq(substate_id.to_string()))
+            .distinct()
+            .get_results::<String>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_get_ids_by_input",
+                source: e,
+            })?;
+
+        transaction_ids
+            .into_iter()
+            .map(|s| deserialize_hex_try_from(&s))
+            .collect()
+    }
+
+    fn transaction_inputs_get_all(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Vec<VersionedSubstateIdLockIntent>, StorageError> {
+        use crate::schema::transaction_inputs;
+
+        let inputs = transaction_inputs::table
+            .filter(transaction_inputs::transaction_id.eq(serialize_hex(transaction_id)))
+            .order_by(transaction_inputs::id.asc())
+            .get_results::<sql_models::TransactionInput>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_inputs_get_all",
+                source: e,
+            })?;
+
+        inputs.into_iter().map(TryInto::try_into).collect()
+    }
+
     fn transactions_get_paginated(
         &self,
         limit: u64,
@@ -651,6 +806,133 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
             .collect()
     }
 
+    fn transactions_get_after(
+        &self,
+        after_id: Option<&TransactionId>,
+        limit: u64,
+        ordering: Ordering,
+    ) -> Result<Vec<TransactionRecord>, StorageError> {
+        use crate::schema::transactions;
+
+        let mut query = transactions::table.into_boxed();
+
+        if let Some(after_id) = after_id {
+            let after_row_id = transactions::table
+                .select(transactions::id)
+                .filter(transactions::transaction_id.eq(serialize_hex(after_id)))
+                .first::<i32>(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "transactions_get_after",
+                    source: e,
+                })?;
+
+            match ordering {
+                Ordering::Ascending => query = query.filter(transactions::id.gt(after_row_id)),
+                Ordering::Descending => query = query.filter(transactions::id.lt(after_row_id)),
+            }
+        }
+
+        match ordering {
+            Ordering::Ascending => query = query.order_by(transactions::id.asc()),
+            Ordering::Descending => query = query.order_by(transactions::id.desc()),
+        }
+
+        let transactions = query
+            .limit(limit as i64)
+            .get_results::<sql_models::Transaction>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_get_after",
+                source: e,
+            })?;
+
+        transactions
+            .into_iter()
+            .map(|transaction| transaction.try_into())
+            .collect()
+    }
+
+    fn transactions_get_by_decision(
+        &self,
+        decision: Decision,
+        epoch: Option<Epoch>,
+        limit: u64,
+        offset: u64,
+    ) -> Result<Vec<TransactionRecord>, StorageError> {
+        use crate::schema::transactions;
+
+        // Transactions that have not been finalized have a NULL final_decision and are therefore excluded
+        let mut query = transactions::table
+            .filter(transactions::final_decision.eq(decision.to_string()))
+            .into_boxed();
+
+        if let Some(epoch) = epoch {
+            // The transaction is valid in the epoch if it falls within its (optional) min/max epoch bounds
+            let epoch = epoch.as_u64() as i64;
+            query = query
+                .filter(transactions::min_epoch.is_null().or(transactions::min_epoch.le(epoch)))
+                .filter(transactions::max_epoch.is_null().or(transactions::max_epoch.ge(epoch)));
+        }
+
+        let transactions = query
+            .order_by(transactions::id.asc())
+            .limit(limit as i64)
+            .offset(offset as i64)
+            .get_results::<sql_models::Transaction>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_get_by_decision",
+                source: e,
+            })?;
+
+        transactions
+            .into_iter()
+            .map(|transaction| transaction.try_into())
+            .collect()
+    }
+
+    fn transactions_count_by_abort_reason(
+        &self,
+        epoch: Option<Epoch>,
+    ) -> Result<HashMap<AbortReason, u64>, StorageError> {
+        use crate::schema::{blocks, transactions};
+
+        let mut query = transactions::table
+            .select(transactions::abort_reason)
+            .filter(transactions::final_decision.eq(Decision::Abort.to_string()))
+            .into_boxed();
+
+        if let Some(epoch) = epoch {
+            // Attribute each abort to the epoch of the block that finalized it
+            query = query.filter(
+                transactions::finalized_in_block.eq_any(
+                    blocks::table
+                        .select(blocks::block_id.nullable())
+                        .filter(blocks::epoch.eq(epoch.as_u64() as i64)),
+                ),
+            );
+        }
+
+        let abort_reasons =
+            query
+                .get_results::<Option<String>>(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "transactions_count_by_abort_reason",
+                    source: e,
+                })?;
+
+        let mut counts = HashMap::new();
+        for reason in abort_reasons {
+            // Aborts recorded without a reason (legacy rows) are counted as Unknown
+            let reason = reason
+                .as_deref()
+                .map(parse_from_string)
+                .transpose()?
+                .unwrap_or(AbortReason::Unknown);
+            *counts.entry(reason).or_insert(0u64) += 1;
+        }
+
+        Ok(counts)
+    }
+
     fn transaction_executions_get(
         &self,
         tx_id: &TransactionId,
@@ -693,6 +975,45 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         execution.try_into()
     }
 
+    fn transaction_executions_get_for_block(
+        &self,
+        block_id: &BlockId,
+    ) -> Result<Vec<TransactionExecution>, StorageError> {
+        use crate::schema::transaction_executions;
+
+        let executions = transaction_executions::table
+            .filter(transaction_executions::block_id.eq(serialize_hex(block_id)))
+            .order_by(transaction_executions::transaction_id.asc())
+            .get_results::<sql_models::TransactionExecution>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_executions_get_for_block",
+                source: e,
+            })?;
+
+        executions.into_iter().map(TryInto::try_into).collect()
+    }
+
+    fn transaction_executions_get_latest(
+        &self,
+        tx_id: &TransactionId,
+    ) -> Result<Option<TransactionExecution>, StorageError> {
+        use crate::schema::{blocks, transaction_executions};
+
+        let execution = transaction_executions::table
+            .inner_join(blocks::table.on(transaction_executions::block_id.eq(blocks::block_id)))
+            .select(transaction_executions::all_columns)
+            .filter(transaction_executions::transaction_id.eq(serialize_hex(tx_id)))
+            .order_by((blocks::height.desc(), transaction_executions::id.desc()))
+            .first::<sql_models::TransactionExecution>(self.connection())
+            .optional()
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_executions_get_latest",
+                source: e,
+            })?;
+
+        execution.map(TryInto::try_into).transpose()
+    }
+
     fn blocks_get(&self, block_id: &BlockId) -> Result<Block, StorageError> {
         use crate::schema::{blocks, quorum_certificates};
 
@@ -717,6 +1038,19 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         block.try_convert(qc)
     }
 
+    fn blocks_get_full_context(&self, block_id: &BlockId) -> Result<BlockContext, StorageError> {
+        let block = self.blocks_get(block_id)?;
+        let mut transactions = self.transactions_get_any(block.all_transaction_ids())?;
+        let positions = block
+            .all_transaction_ids()
+            .enumerate()
+            .map(|(i, id)| (*id, i))
+            .collect::<HashMap<_, _>>();
+        transactions.sort_by_key(|t| positions.get(t.id()).copied());
+
+        Ok(BlockContext { block, transactions })
+    }
+
     fn blocks_get_last_n_in_epoch(&self, n: usize, epoch: Epoch) -> Result<Vec<Block>, StorageError> {
         use crate::schema::{blocks, quorum_certificates};
 
@@ -816,6 +1150,21 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         Ok(count > 0)
     }
 
+    fn blocks_get_foreign_indexes(&self, block_id: &BlockId) -> Result<IndexMap<Shard, u64>, StorageError> {
+        use crate::schema::blocks;
+
+        let foreign_indexes = blocks::table
+            .select(blocks::foreign_indexes)
+            .filter(blocks::block_id.eq(serialize_hex(block_id)))
+            .first::<String>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "blocks_get_foreign_indexes",
+                source: e,
+            })?;
+
+        deserialize_foreign_indexes(&foreign_indexes)
+    }
+
     fn blocks_is_ancestor(&self, descendant: &BlockId, ancestor: &BlockId) -> Result<bool, StorageError> {
         if !self.blocks_exists(descendant)? {
             return Err(StorageError::QueryError {
@@ -965,6 +1314,33 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         Ok(total_fee.to_u64().expect("total fee overflows u64"))
     }
 
+    fn blocks_sum_leader_fees_by_proposer(&self, epoch: Epoch) -> Result<HashMap<String, u64>, StorageError> {
+        use crate::schema::blocks;
+
+        let fees = blocks::table
+            .filter(blocks::epoch.eq(epoch.as_u64() as i64))
+            .filter(blocks::is_committed.eq(true))
+            .filter(blocks::is_dummy.eq(false))
+            .group_by(blocks::proposed_by)
+            .select((blocks::proposed_by, dsl::sum(blocks::total_leader_fee)))
+            .get_results::<(String, Option<BigDecimal>)>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "blocks_sum_leader_fees_by_proposer",
+                source: e,
+            })?;
+
+        // proposed_by is stored as the hex-encoded public key
+        fees.into_iter()
+            .map(|(proposed_by, total_fee)| {
+                let total_fee = total_fee
+                    .unwrap_or_default()
+                    .to_u64()
+                    .ok_or(StorageError::InvalidIntegerCast)?;
+                Ok((proposed_by, total_fee))
+            })
+            .collect()
+    }
+
     fn blocks_get_any_with_epoch_range(
         &self,
         epoch_range: RangeInclusive<Epoch>,
@@ -1111,54 +1487,260 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
             .collect()
     }
 
-    fn blocks_get_count(&self) -> Result<i64, StorageError> {
+    fn blocks_get_paginated_by_epoch(&self, epoch: Epoch, limit: u64, offset: u64) -> Result<Vec<Block>, StorageError> {
         use crate::schema::{blocks, quorum_certificates};
-        let count = blocks::table
+
+        let blocks = blocks::table
             .left_join(quorum_certificates::table.on(blocks::qc_id.eq(quorum_certificates::qc_id)))
-            .select(diesel::dsl::count(blocks::id))
-            .first::<i64>(self.connection())
+            .select((blocks::all_columns, quorum_certificates::all_columns.nullable()))
+            .filter(blocks::epoch.eq(epoch.as_u64() as i64))
+            .order_by(blocks::height.asc())
+            .then_order_by(blocks::id.asc())
+            .limit(limit as i64)
+            .offset(offset as i64)
+            .get_results::<(sql_models::Block, Option<sql_models::QuorumCertificate>)>(self.connection())
             .map_err(|e| SqliteStorageError::DieselError {
-                operation: "blocks_get_count",
+                operation: "blocks_get_paginated_by_epoch",
                 source: e,
             })?;
-        Ok(count)
+
+        blocks
+            .into_iter()
+            .map(|(block, qc)| {
+                let qc = qc.ok_or_else(|| SqliteStorageError::DbInconsistency {
+                    operation: "blocks_get_paginated_by_epoch",
+                    details: format!(
+                        "block {} references non-existent quorum certificate {}",
+                        block.id, block.qc_id
+                    ),
+                })?;
+
+                block.try_convert(qc)
+            })
+            .collect()
     }
 
-    fn filtered_blocks_get_count(
+    fn blocks_get_paginated_by_shard_group(
         &self,
-        filter_index: Option<usize>,
-        filter: Option<String>,
-    ) -> Result<i64, StorageError> {
+        shard_group: ShardGroup,
+        limit: u64,
+        offset: u64,
+        ordering: Ordering,
+    ) -> Result<Vec<Block>, StorageError> {
         use crate::schema::{blocks, quorum_certificates};
 
-        let mut query = blocks::table
+        let query = blocks::table
             .left_join(quorum_certificates::table.on(blocks::qc_id.eq(quorum_certificates::qc_id)))
             .select((blocks::all_columns, quorum_certificates::all_columns.nullable()))
+            .filter(blocks::shard_group.eq(shard_group.encode_as_u32() as i32))
             .into_boxed();
 
-        if let Some(filter) = filter {
-            if !filter.is_empty() {
-                if let Some(filter_index) = filter_index {
-                    match filter_index {
-                        0 => query = query.filter(blocks::block_id.like(format!("%{filter}%"))),
-                        1 => {
-                            query = query.filter(
-                                blocks::epoch
-                                    .eq(filter.parse::<i64>().map_err(|_| StorageError::InvalidIntegerCast)?),
-                            )
-                        },
-                        2 => {
-                            query = query.filter(
-                                blocks::height
-                                    .eq(filter.parse::<i64>().map_err(|_| StorageError::InvalidIntegerCast)?),
-                            )
-                        },
-                        4 => {
-                            query = query.filter(
-                                blocks::command_count
-                                    .ge(filter.parse::<i64>().map_err(|_| StorageError::InvalidIntegerCast)?),
-                            )
-                        },
+        let query = match ordering {
+            Ordering::Ascending => query.order_by(blocks::epoch.asc()).then_order_by(blocks::height.asc()),
+            Ordering::Descending => query
+                .order_by(blocks::epoch.desc())
+                .then_order_by(blocks::height.desc()),
+        };
+
+        let blocks = query
+            .limit(limit as i64)
+            .offset(offset as i64)
+            .get_results::<(sql_models::Block, Option<sql_models::QuorumCertificate>)>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "blocks_get_paginated_by_shard_group",
+                source: e,
+            })?;
+
+        blocks
+            .into_iter()
+            .map(|(block, qc)| {
+                let qc = qc.ok_or_else(|| SqliteStorageError::DbInconsistency {
+                    operation: "blocks_get_paginated_by_shard_group",
+                    details: format!(
+                        "block {} references non-existent quorum certificate {}",
+                        block.id, block.qc_id
+                    ),
+                })?;
+
+                block.try_convert(qc)
+            })
+            .collect()
+    }
+
+    fn blocks_get_tip_n(&self, n: u64, include_dummy: bool) -> Result<Vec<Block>, StorageError> {
+        use crate::schema::{blocks, quorum_certificates};
+
+        let mut query = blocks::table
+            .left_join(quorum_certificates::table.on(blocks::qc_id.eq(quorum_certificates::qc_id)))
+            .select((blocks::all_columns, quorum_certificates::all_columns.nullable()))
+            .into_boxed();
+
+        if !include_dummy {
+            query = query.filter(blocks::is_dummy.eq(false));
+        }
+
+        let blocks = query
+            .order_by(blocks::height.desc())
+            .then_order_by(blocks::timestamp.desc())
+            .limit(n as i64)
+            .get_results::<(sql_models::Block, Option<sql_models::QuorumCertificate>)>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "blocks_get_tip_n",
+                source: e,
+            })?;
+
+        blocks
+            .into_iter()
+            .map(|(block, qc)| {
+                let qc = qc.ok_or_else(|| SqliteStorageError::DbInconsistency {
+                    operation: "blocks_get_tip_n",
+                    details: format!(
+                        "block {} references non-existent quorum certificate {}",
+                        block.id, block.qc_id
+                    ),
+                })?;
+
+                block.try_convert(qc)
+            })
+            .collect()
+    }
+
+    fn blocks_get_commit_tip(&self) -> Result<Option<Block>, StorageError> {
+        use crate::schema::{blocks, quorum_certificates};
+
+        let maybe_block = blocks::table
+            .left_join(quorum_certificates::table.on(blocks::qc_id.eq(quorum_certificates::qc_id)))
+            .select((blocks::all_columns, quorum_certificates::all_columns.nullable()))
+            .filter(blocks::is_committed.eq(true))
+            .order_by(blocks::height.desc())
+            .first::<(sql_models::Block, Option<sql_models::QuorumCertificate>)>(self.connection())
+            .optional()
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "blocks_get_commit_tip",
+                source: e,
+            })?;
+
+        let Some((block, qc)) = maybe_block else {
+            return Ok(None);
+        };
+
+        let qc = qc.ok_or_else(|| SqliteStorageError::DbInconsistency {
+            operation: "blocks_get_commit_tip",
+            details: format!(
+                "block {} references non-existent quorum certificate {}",
+                block.block_id, block.qc_id
+            ),
+        })?;
+
+        block.try_convert(qc).map(Some)
+    }
+
+    fn blocks_get_nearest_to_timestamp(&self, timestamp: u64) -> Result<Option<Block>, StorageError> {
+        use crate::schema::{blocks, quorum_certificates};
+
+        let timestamp = i64::try_from(timestamp).unwrap_or(i64::MAX);
+
+        let at_or_before = blocks::table
+            .left_join(quorum_certificates::table.on(blocks::qc_id.eq(quorum_certificates::qc_id)))
+            .select((blocks::all_columns, quorum_certificates::all_columns.nullable()))
+            .filter(blocks::is_dummy.eq(false))
+            .filter(blocks::timestamp.le(timestamp))
+            .order_by(blocks::timestamp.desc())
+            .then_order_by(blocks::height.asc())
+            .first::<(sql_models::Block, Option<sql_models::QuorumCertificate>)>(self.connection())
+            .optional()
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "blocks_get_nearest_to_timestamp",
+                source: e,
+            })?;
+
+        let after = blocks::table
+            .left_join(quorum_certificates::table.on(blocks::qc_id.eq(quorum_certificates::qc_id)))
+            .select((blocks::all_columns, quorum_certificates::all_columns.nullable()))
+            .filter(blocks::is_dummy.eq(false))
+            .filter(blocks::timestamp.gt(timestamp))
+            .order_by(blocks::timestamp.asc())
+            .then_order_by(blocks::height.asc())
+            .first::<(sql_models::Block, Option<sql_models::QuorumCertificate>)>(self.connection())
+            .optional()
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "blocks_get_nearest_to_timestamp",
+                source: e,
+            })?;
+
+        let nearest = match (at_or_before, after) {
+            (Some(before), Some(after)) => {
+                // On a tie, prefer the earlier block
+                if after.0.timestamp - timestamp < timestamp - before.0.timestamp {
+                    after
+                } else {
+                    before
+                }
+            },
+            (Some(block), None) | (None, Some(block)) => block,
+            (None, None) => return Ok(None),
+        };
+
+        let (block, qc) = nearest;
+        let qc = qc.ok_or_else(|| SqliteStorageError::DbInconsistency {
+            operation: "blocks_get_nearest_to_timestamp",
+            details: format!(
+                "block {} references non-existent quorum certificate {}",
+                block.block_id, block.qc_id
+            ),
+        })?;
+
+        block.try_convert(qc).map(Some)
+    }
+
+    fn blocks_get_count(&self) -> Result<i64, StorageError> {
+        use crate::schema::{blocks, quorum_certificates};
+        let count = blocks::table
+            .left_join(quorum_certificates::table.on(blocks::qc_id.eq(quorum_certificates::qc_id)))
+            .select(diesel::dsl::count(blocks::id))
+            .first::<i64>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "blocks_get_count",
+                source: e,
+            })?;
+        Ok(count)
+    }
+
+    fn filtered_blocks_get_count(
+        &self,
+        filter_index: Option<usize>,
+        filter: Option<String>,
+    ) -> Result<i64, StorageError> {
+        use crate::schema::{blocks, quorum_certificates};
+
+        let mut query = blocks::table
+            .left_join(quorum_certificates::table.on(blocks::qc_id.eq(quorum_certificates::qc_id)))
+            .select((blocks::all_columns, quorum_certificates::all_columns.nullable()))
+            .into_boxed();
+
+        if let Some(filter) = filter {
+            if !filter.is_empty() {
+                if let Some(filter_index) = filter_index {
+                    match filter_index {
+                        0 => query = query.filter(blocks::block_id.like(format!("%{filter}%"))),
+                        1 => {
+                            query = query.filter(
+                                blocks::epoch
+                                    .eq(filter.parse::<i64>().map_err(|_| StorageError::InvalidIntegerCast)?),
+                            )
+                        },
+                        2 => {
+                            query = query.filter(
+                                blocks::height
+                                    .eq(filter.parse::<i64>().map_err(|_| StorageError::InvalidIntegerCast)?),
+                            )
+                        },
+                        4 => {
+                            query = query.filter(
+                                blocks::command_count
+                                    .ge(filter.parse::<i64>().map_err(|_| StorageError::InvalidIntegerCast)?),
+                            )
+                        },
                         5 => {
                             query = query.filter(
                                 blocks::total_leader_fee
@@ -1251,6 +1833,23 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         Ok(count > 0)
     }
 
+    fn parked_blocks_waiting_on(&self, transaction_id: &TransactionId) -> Result<Vec<BlockId>, StorageError> {
+        use crate::schema::{missing_transactions, parked_blocks};
+
+        let block_ids = missing_transactions::table
+            .inner_join(parked_blocks::table.on(missing_transactions::block_id.eq(parked_blocks::block_id)))
+            .select(parked_blocks::block_id)
+            .filter(missing_transactions::transaction_id.eq(serialize_hex(transaction_id)))
+            .distinct()
+            .get_results::<String>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "parked_blocks_waiting_on",
+                source: e,
+            })?;
+
+        block_ids.into_iter().map(|s| deserialize_hex_try_from(&s)).collect()
+    }
+
     fn quorum_certificates_get(&self, qc_id: &QcId) -> Result<QuorumCertificate, StorageError> {
         use crate::schema::quorum_certificates;
 
@@ -1266,6 +1865,21 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         deserialize_json(&qc_json)
     }
 
+    fn quorum_certificates_get_signature_count(&self, qc_id: &QcId) -> Result<usize, StorageError> {
+        use crate::schema::quorum_certificates;
+
+        let count = quorum_certificates::table
+            .select(quorum_certificates::signature_count)
+            .filter(quorum_certificates::qc_id.eq(serialize_hex(qc_id)))
+            .first::<i32>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "quorum_certificates_get_signature_count",
+                source: e,
+            })?;
+
+        Ok(count as usize)
+    }
+
     fn quorum_certificates_get_all<'a, I: IntoIterator<Item = &'a QcId>>(
         &self,
         qc_ids: I,
@@ -1314,6 +1928,46 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         deserialize_json(&qc_json)
     }
 
+    fn quorum_certificates_get_chain(
+        &self,
+        from_block: &BlockId,
+        max_depth: usize,
+    ) -> Result<Vec<QuorumCertificate>, StorageError> {
+        use crate::schema::{blocks, quorum_certificates};
+
+        let mut chain = Vec::new();
+        let mut block_id = *from_block;
+        while chain.len() < max_depth {
+            let qc_id = blocks::table
+                .select(blocks::qc_id)
+                .filter(blocks::block_id.eq(serialize_hex(block_id)))
+                .first::<String>(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "quorum_certificates_get_chain",
+                    source: e,
+                })?;
+
+            let qc_json = quorum_certificates::table
+                .select(quorum_certificates::json)
+                .filter(quorum_certificates::qc_id.eq(&qc_id))
+                .first::<String>(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "quorum_certificates_get_chain",
+                    source: e,
+                })?;
+
+            let qc = deserialize_json::<QuorumCertificate>(&qc_json)?;
+            let is_zero = qc.is_zero();
+            block_id = *qc.block_id();
+            chain.push(qc);
+            if is_zero {
+                break;
+            }
+        }
+
+        Ok(chain)
+    }
+
     fn transaction_pool_get_for_blocks(
         &self,
         from_block_id: &BlockId,
@@ -1376,6 +2030,38 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         txs.into_iter().map(|tx| tx.try_convert(None)).collect()
     }
 
+    fn transaction_pool_dump(&self) -> Result<Vec<TransactionPoolRecordWithUpdate>, StorageError> {
+        use crate::schema::{transaction_pool, transaction_pool_state_updates};
+
+        let rows = transaction_pool::table
+            .left_join(
+                transaction_pool_state_updates::table.on(transaction_pool_state_updates::transaction_id
+                    .eq(transaction_pool::transaction_id)
+                    .and(dsl::sql::<Bool>(
+                        "transaction_pool_state_updates.id = (SELECT MAX(u.id) FROM transaction_pool_state_updates AS \
+                         u WHERE u.transaction_id = transaction_pool.transaction_id)",
+                    ))),
+            )
+            .order_by(transaction_pool::id.asc())
+            .get_results::<(
+                sql_models::TransactionPoolRecord,
+                Option<sql_models::TransactionPoolStateUpdate>,
+            )>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_pool_dump",
+                source: e,
+            })?;
+
+        rows.into_iter()
+            .map(|(record, update)| {
+                Ok(TransactionPoolRecordWithUpdate {
+                    record: record.try_convert(None)?,
+                    update: update.map(TryInto::try_into).transpose()?,
+                })
+            })
+            .collect()
+    }
+
     fn transaction_pool_get_many_ready(&self, max_txs: usize) -> Result<Vec<TransactionPoolRecord>, StorageError> {
         use crate::schema::transaction_pool;
 
@@ -1546,6 +2232,48 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         Ok(count as usize)
     }
 
+    fn transaction_pool_history_get_for_transactions<'a, I: IntoIterator<Item = &'a TransactionId>>(
+        &self,
+        tx_ids: I,
+    ) -> Result<Vec<TransactionPoolHistoryEntry>, StorageError> {
+        use crate::schema::transaction_pool_history;
+
+        let tx_ids = tx_ids.into_iter().map(serialize_hex).collect::<Vec<_>>();
+
+        let mut history = Vec::new();
+        for chunk in tx_ids.chunks(self.sql_variable_chunk_size(1)) {
+            let rows = transaction_pool_history::table
+                .filter(transaction_pool_history::transaction_id.eq_any(chunk))
+                .get_results::<sql_models::TransactionPoolHistory>(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "transaction_pool_history_get_for_transactions",
+                    source: e,
+                })?;
+            history.extend(rows);
+        }
+        // Each chunk is queried separately, so restore the overall order
+        history.sort_by_key(|entry| entry.history_id);
+
+        history.into_iter().map(TryInto::try_into).collect()
+    }
+
+    fn check_pool_transaction_consistency(&self) -> Result<Vec<TransactionId>, StorageError> {
+        use crate::schema::{transaction_pool, transactions};
+
+        let orphaned = transaction_pool::table
+            .left_join(transactions::table.on(transaction_pool::transaction_id.eq(transactions::transaction_id)))
+            .select(transaction_pool::transaction_id)
+            .filter(transactions::id.is_null())
+            .order_by(transaction_pool::id.asc())
+            .get_results::<String>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "check_pool_transaction_consistency",
+                source: e,
+            })?;
+
+        orphaned.into_iter().map(|s| deserialize_hex_try_from(&s)).collect()
+    }
+
     fn transactions_fetch_involved_shards(
         &self,
         transaction_ids: HashSet<TransactionId>,
@@ -1625,11 +2353,18 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         Ok(count as u64)
     }
 
-    fn votes_get_for_block(&self, block_id: &BlockId) -> Result<Vec<Vote>, StorageError> {
+    fn votes_get_for_block(&self, epoch: Epoch, block_id: &BlockId) -> Result<Vec<Vote>, StorageError> {
         use crate::schema::votes;
 
         let votes = votes::table
+            .filter(votes::epoch.eq(epoch.as_u64() as i64))
             .filter(votes::block_id.eq(serialize_hex(block_id)))
+            // Only the latest vote from each sender
+            .filter(dsl::sql::<Bool>(
+                "votes.id = (SELECT MAX(v.id) FROM votes v WHERE v.epoch = votes.epoch AND v.block_id = votes.block_id \
+                 AND v.sender_leaf_hash = votes.sender_leaf_hash)",
+            ))
+            .order_by(votes::id.asc())
             .get_results::<sql_models::Vote>(self.connection())
             .map_err(|e| SqliteStorageError::DieselError {
                 operation: "votes_get_for_block",
@@ -1639,6 +2374,21 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         votes.into_iter().map(Vote::try_from).collect()
     }
 
+    fn rejected_blocks_get_recent(&self, limit: u64) -> Result<Vec<RejectedBlock>, StorageError> {
+        use crate::schema::rejected_blocks;
+
+        let rejected = rejected_blocks::table
+            .order_by((rejected_blocks::created_at.desc(), rejected_blocks::id.desc()))
+            .limit(limit as i64)
+            .get_results::<sql_models::RejectedBlock>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "rejected_blocks_get_recent",
+                source: e,
+            })?;
+
+        rejected.into_iter().map(TryInto::try_into).collect()
+    }
+
     fn substates_get(&self, address: &SubstateAddress) -> Result<SubstateRecord, StorageError> {
         use crate::schema::substates;
 
@@ -1653,6 +2403,26 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         substate.try_into()
     }
 
+    fn substates_get_at_version(
+        &self,
+        substate_id: &SubstateId,
+        version: u32,
+    ) -> Result<Option<SubstateRecord>, StorageError> {
+        use crate::schema::substates;
+
+        let substate = substates::table
+            .filter(substates::substate_id.eq(substate_id.to_string()))
+            .filter(substates::version.eq(version as i32))
+            .first::<sql_models::SubstateRecord>(self.connection())
+            .optional()
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "substates_get_at_version",
+                source: e,
+            })?;
+
+        substate.map(TryInto::try_into).transpose()
+    }
+
     fn substates_get_any(
         &self,
         substate_ids: &HashSet<SubstateRequirement>,
@@ -1810,6 +2580,39 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         substates.into_iter().map(TryInto::try_into).collect()
     }
 
+    fn substates_stream_after(
+        &mut self,
+        after: &SubstateAddress,
+    ) -> Result<impl Iterator<Item = Result<SubstateRecord, StorageError>> + '_, StorageError> {
+        use crate::schema::substates;
+
+        let start_id = substates::table
+            .select(substates::id)
+            .filter(substates::address.eq(after.to_string()))
+            .get_result::<i32>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "substates_stream_after",
+                source: e,
+            })?;
+
+        let substates = substates::table
+            .filter(substates::id.gt(start_id))
+            .order_by(substates::id.asc())
+            .load_iter::<sql_models::SubstateRecord, _>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "substates_stream_after",
+                source: e,
+            })?;
+
+        Ok(substates.map(|substate| {
+            let substate = substate.map_err(|e| SqliteStorageError::DieselError {
+                operation: "substates_stream_after",
+                source: e,
+            })?;
+            SubstateRecord::try_from(substate)
+        }))
+    }
+
     fn substates_get_many_within_range(
         &self,
         start: &SubstateAddress,
@@ -1838,6 +2641,7 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
 
         let substates = substates::table
             .filter(substates::created_by_transaction.eq(serialize_hex(tx_id)))
+            .order_by((substates::substate_id.asc(), substates::version.asc()))
             .get_results::<sql_models::SubstateRecord>(self.connection())
             .map_err(|e| SqliteStorageError::DieselError {
                 operation: "substates_get_many_by_created_transaction",
@@ -1855,6 +2659,7 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
 
         let substates = substates::table
             .filter(substates::destroyed_by_transaction.eq(serialize_hex(tx_id)))
+            .order_by((substates::substate_id.asc(), substates::version.asc()))
             .get_results::<sql_models::SubstateRecord>(self.connection())
             .map_err(|e| SqliteStorageError::DieselError {
                 operation: "substates_get_many_by_destroyed_transaction",
@@ -1893,6 +2698,59 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         Ok(substates)
     }
 
+    fn substates_total_data_bytes(&self, shard: Option<Shard>, include_destroyed: bool) -> Result<u64, StorageError> {
+        use crate::schema::substates;
+
+        let mut query = substates::table
+            // LENGTH counts characters for TEXT, so cast to a BLOB to count bytes
+            .select(dsl::sql::<Nullable<BigInt>>("SUM(LENGTH(CAST(data AS BLOB)))"))
+            .into_boxed();
+
+        if let Some(shard) = shard {
+            query = query.filter(substates::created_by_shard.eq(shard.as_u32() as i32));
+        }
+
+        if !include_destroyed {
+            query = query.filter(substates::destroyed_by_transaction.is_null());
+        }
+
+        let total = query
+            .first::<Option<i64>>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "substates_total_data_bytes",
+                source: e,
+            })?;
+
+        Ok(total.unwrap_or(0) as u64)
+    }
+
+    fn substates_verify_addresses(&self) -> Result<Vec<SubstateId>, StorageError> {
+        use crate::schema::substates;
+
+        let rows = substates::table
+            .select((substates::substate_id, substates::version, substates::address))
+            .order_by(substates::id.asc())
+            .get_results::<(String, i32, String)>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "substates_verify_addresses",
+                source: e,
+            })?;
+
+        let mut mismatched = Vec::new();
+        for (substate_id, version, address) in rows {
+            let id = SubstateId::from_str(&substate_id).map_err(|e| SqliteStorageError::MalformedDbData {
+                operation: "substates_verify_addresses",
+                details: format!("'{}' is not a valid SubstateId: {}", substate_id, e),
+            })?;
+            let expected = SubstateAddress::from_substate_id(&id, version as u32);
+            if serialize_hex(expected) != address {
+                mismatched.push(id);
+            }
+        }
+
+        Ok(mismatched)
+    }
+
     fn substate_locks_get_all_for_block(
         &self,
         block_id: BlockId,
@@ -1947,6 +2805,35 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         lock.try_into_substate_lock()
     }
 
+    fn substate_locks_get_conflicting(
+        &self,
+        substate_id: &SubstateId,
+        transaction_id: &TransactionId,
+        requested: SubstateLockFlag,
+    ) -> Result<Vec<LockedSubstate>, StorageError> {
+        use crate::schema::substate_locks;
+
+        let mut query = substate_locks::table
+            .filter(substate_locks::substate_id.eq(substate_id.to_string()))
+            .filter(substate_locks::transaction_id.ne(serialize_hex(transaction_id)))
+            .into_boxed();
+
+        // Read locks only conflict with WRITE and OUTPUT locks
+        if requested.is_read() {
+            query = query.filter(substate_locks::lock.ne(SubstateLockFlag::Read.to_string()));
+        }
+
+        let locks = query
+            .order_by(substate_locks::id.asc())
+            .get_results::<sql_models::SubstateLock>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "substate_locks_get_conflicting",
+                source: e,
+            })?;
+
+        locks.into_iter().map(|lock| lock.try_into_substate_lock()).collect()
+    }
+
     fn pending_state_tree_diffs_get_all_up_to_commit_block(
         &self,
         block_id: &BlockId,
@@ -2023,6 +2910,42 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
             .collect()
     }
 
+    fn state_transitions_stream_after(
+        &mut self,
+        id: StateTransitionId,
+        end_epoch: Epoch,
+    ) -> Result<impl Iterator<Item = Result<StateTransition, StorageError>> + '_, StorageError> {
+        use crate::schema::{state_transitions, substates};
+
+        // Never return epoch 0 state transitions
+        let min_epoch = Some(id.epoch().as_u64()).filter(|e| *e > 0).unwrap_or(1) as i64;
+        let transitions = state_transitions::table
+            .left_join(substates::table.on(state_transitions::substate_address.eq(substates::address)))
+            .select((state_transitions::all_columns, substates::all_columns.nullable()))
+            .filter(state_transitions::seq.ge(id.seq() as i64))
+            .filter(state_transitions::epoch.ge(min_epoch))
+            .filter(state_transitions::epoch.lt(end_epoch.as_u64() as i64))
+            .filter(state_transitions::shard.eq(id.shard().as_u32() as i32))
+            .order_by(state_transitions::seq.asc())
+            .load_iter::<(sql_models::StateTransition, Option<sql_models::SubstateRecord>), _>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "state_transitions_stream_after",
+                source: e,
+            })?;
+
+        Ok(transitions.map(|transition| {
+            let (t, s) = transition.map_err(|e| SqliteStorageError::DieselError {
+                operation: "state_transitions_stream_after",
+                source: e,
+            })?;
+            let s = s.ok_or_else(|| StorageError::DataInconsistency {
+                details: format!("substate entry does not exist for transition {}", t.id),
+            })?;
+
+            t.try_convert(s)
+        }))
+    }
+
     fn state_transitions_get_last_id(&self, shard: Shard) -> Result<StateTransitionId, StorageError> {
         use crate::schema::state_transitions;
 
@@ -2043,6 +2966,31 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         Ok(StateTransitionId::new(epoch, shard, seq))
     }
 
+    fn state_transitions_find_gaps(&self, shard: Shard) -> Result<Vec<(i64, i64)>, StorageError> {
+        use crate::schema::state_transitions;
+
+        let seqs = state_transitions::table
+            .select(state_transitions::seq)
+            .filter(state_transitions::shard.eq(shard.as_u32() as i32))
+            .order_by(state_transitions::seq.asc())
+            .get_results::<i64>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "state_transitions_find_gaps",
+                source: e,
+            })?;
+
+        let mut gaps = Vec::new();
+        let mut expected = 0i64;
+        for seq in seqs {
+            if seq > expected {
+                gaps.push((expected, seq - 1));
+            }
+            expected = expected.max(seq + 1);
+        }
+
+        Ok(gaps)
+    }
+
     fn state_tree_nodes_get(&self, shard: Shard, key: &NodeKey) -> Result<Node<Version>, StorageError> {
         use crate::schema::state_tree;
 
@@ -2081,6 +3029,50 @@ impl<'tx, TAddr: NodeAddressable + Serialize + DeserializeOwned + 'tx> StateStor
         Ok(version.map(|v| v as Version))
     }
 
+    fn state_tree_versions_get_latest_many(&self, shards: &[Shard]) -> Result<HashMap<Shard, Version>, StorageError> {
+        use crate::schema::state_tree_shard_versions;
+
+        let versions = state_tree_shard_versions::table
+            .filter(state_tree_shard_versions::shard.eq_any(shards.iter().map(|s| s.as_u32() as i32)))
+            .group_by(state_tree_shard_versions::shard)
+            .select((
+                state_tree_shard_versions::shard,
+                dsl::max(state_tree_shard_versions::version),
+            ))
+            .get_results::<(i32, Option<i64>)>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "state_tree_versions_get_latest_many",
+                source: e,
+            })?;
+
+        Ok(versions
+            .into_iter()
+            .filter_map(|(shard, version)| Some((Shard::from(shard as u32), version? as Version)))
+            .collect())
+    }
+
+    fn state_tree_verify_shard_version(&self, shard: Shard) -> Result<bool, StorageError> {
+        use crate::schema::state_tree;
+
+        let Some(version) = self.state_tree_versions_get_latest(shard)? else {
+            return Ok(true);
+        };
+
+        let root_key = NodeKey::new_empty_path(version);
+        let count = state_tree::table
+            .count()
+            .filter(state_tree::shard.eq(shard.as_u32() as i32))
+            .filter(state_tree::key.eq(root_key.to_string()))
+            .filter(state_tree::is_stale.eq(false))
+            .first::<i64>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "state_tree_verify_shard_version",
+                source: e,
+            })?;
+
+        Ok(count > 0)
+    }
+
     fn epoch_checkpoint_get(&self, epoch: Epoch) -> Result<EpochCheckpoint, StorageError> {
         use crate::schema::epoch_checkpoints;
 
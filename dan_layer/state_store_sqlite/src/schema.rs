@@ -39,6 +39,7 @@ diesel::table! {
         base_layer_block_height -> BigInt,
         base_layer_block_hash -> Text,
         created_at -> Timestamp,
+        command_merkle_root -> Nullable<Text>,
     }
 }
 
@@ -189,6 +190,7 @@ diesel::table! {
         base_layer_block_height -> BigInt,
         base_layer_block_hash -> Text,
         created_at -> Timestamp,
+        command_merkle_root -> Nullable<Text>,
     }
 }
 
@@ -204,6 +206,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    pending_transaction_aborts (id) {
+        id -> Integer,
+        block_id -> Text,
+        block_height -> BigInt,
+        transaction_id -> Text,
+        abort_reason -> Text,
+        abort_details -> Text,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     quorum_certificates (id) {
         id -> Integer,
@@ -211,6 +225,17 @@ diesel::table! {
         block_id -> Text,
         json -> Text,
         created_at -> Timestamp,
+        signature_count -> Integer,
+    }
+}
+
+diesel::table! {
+    rejected_blocks (id) {
+        id -> Integer,
+        block_id -> Text,
+        height -> BigInt,
+        reason -> Text,
+        created_at -> Timestamp,
     }
 }
 
@@ -299,6 +324,27 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    transaction_inputs (id) {
+        id -> Integer,
+        transaction_id -> Text,
+        substate_id -> Text,
+        version -> Integer,
+        lock_flag -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    transaction_outputs (id) {
+        id -> Integer,
+        transaction_id -> Text,
+        substate_id -> Text,
+        version -> Integer,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     transaction_pool (id) {
         id -> Integer,
@@ -374,6 +420,8 @@ diesel::table! {
         min_epoch -> Nullable<BigInt>,
         max_epoch -> Nullable<BigInt>,
         created_at -> Timestamp,
+        finalized_in_block -> Nullable<Text>,
+        abort_reason -> Nullable<Text>,
     }
 }
 
@@ -407,13 +455,17 @@ diesel::allow_tables_to_appear_in_same_query!(
     missing_transactions,
     parked_blocks,
     pending_state_tree_diffs,
+    pending_transaction_aborts,
     quorum_certificates,
+    rejected_blocks,
     state_transitions,
     state_tree,
     state_tree_shard_versions,
     substate_locks,
     substates,
     transaction_executions,
+    transaction_inputs,
+    transaction_outputs,
     transaction_pool,
     transaction_pool_history,
     transaction_pool_state_updates,
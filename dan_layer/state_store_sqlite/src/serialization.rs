@@ -1,8 +1,15 @@
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
-use std::{any::type_name, str::FromStr};
+use std::{any::type_name, fmt, str::FromStr};
 
-use serde::Serialize;
+use indexmap::IndexMap;
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize,
+    Deserializer,
+    Serialize,
+};
+use tari_dan_common_types::shard::Shard;
 use tari_dan_storage::StorageError;
 
 pub fn serialize_json<T: Serialize + ?Sized>(t: &T) -> Result<String, StorageError> {
@@ -54,3 +61,39 @@ where T: FromStr {
         details: format!("Cannot parse string '{s}'"),
     })
 }
+
+/// Decodes the JSON-encoded foreign indexes of a block. Unlike a plain `deserialize_json`, this rejects duplicate
+/// shard entries rather than silently keeping the last one.
+pub fn deserialize_foreign_indexes(s: &str) -> Result<IndexMap<Shard, u64>, StorageError> {
+    deserialize_json::<ForeignIndexes>(s).map(|indexes| indexes.0)
+}
+
+struct ForeignIndexes(IndexMap<Shard, u64>);
+
+impl<'de> Deserialize<'de> for ForeignIndexes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        struct ForeignIndexesVisitor;
+
+        impl<'de> Visitor<'de> for ForeignIndexesVisitor {
+            type Value = ForeignIndexes;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of shard to foreign index")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where A: MapAccess<'de> {
+                let mut indexes = IndexMap::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some((shard, index)) = map.next_entry::<Shard, u64>()? {
+                    if indexes.insert(shard, index).is_some() {
+                        return Err(serde::de::Error::custom(format!("duplicate foreign index for {shard}")));
+                    }
+                }
+                Ok(ForeignIndexes(indexes))
+            }
+        }
+
+        deserializer.deserialize_map(ForeignIndexesVisitor)
+    }
+}
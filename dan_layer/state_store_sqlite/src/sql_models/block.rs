@@ -10,7 +10,7 @@ use time::PrimitiveDateTime;
 
 use crate::{
     schema::blocks,
-    serialization::{deserialize_hex, deserialize_hex_try_from, deserialize_json},
+    serialization::{deserialize_foreign_indexes, deserialize_hex, deserialize_hex_try_from, deserialize_json},
     sql_models,
 };
 
@@ -39,6 +39,7 @@ pub struct Block {
     pub base_layer_block_height: i64,
     pub base_layer_block_hash: String,
     pub created_at: PrimitiveDateTime,
+    pub command_merkle_root: Option<String>,
 }
 
 impl Block {
@@ -70,11 +71,15 @@ impl Block {
             })?,
             deserialize_json(&self.commands)?,
             deserialize_hex_try_from(&self.merkle_root)?,
+            self.command_merkle_root
+                .as_deref()
+                .map(deserialize_hex_try_from)
+                .transpose()?,
             self.total_leader_fee as u64,
             self.is_dummy,
             self.is_processed,
             self.is_committed,
-            deserialize_json(&self.foreign_indexes)?,
+            deserialize_foreign_indexes(&self.foreign_indexes)?,
             self.signature.map(|val| deserialize_json(&val)).transpose()?,
             self.created_at,
             self.block_time.map(|v| v as u64),
@@ -107,6 +112,7 @@ pub struct ParkedBlock {
     pub base_layer_block_height: i64,
     pub base_layer_block_hash: String,
     pub created_at: PrimitiveDateTime,
+    pub command_merkle_root: Option<String>,
 }
 
 impl TryFrom<ParkedBlock> for consensus_models::Block {
@@ -140,11 +146,16 @@ impl TryFrom<ParkedBlock> for consensus_models::Block {
             })?,
             deserialize_json(&value.commands)?,
             deserialize_hex_try_from(&value.merkle_root)?,
+            value
+                .command_merkle_root
+                .as_deref()
+                .map(deserialize_hex_try_from)
+                .transpose()?,
             value.total_leader_fee as u64,
             false,
             false,
             false,
-            deserialize_json(&value.foreign_indexes)?,
+            deserialize_foreign_indexes(&value.foreign_indexes)?,
             value.signature.map(|val| deserialize_json(&val)).transpose()?,
             value.created_at,
             value.block_time.map(|v| v as u64),
@@ -2,13 +2,9 @@
 //   SPDX-License-Identifier: BSD-3-Clause
 
 use diesel::Queryable;
-use tari_dan_common_types::shard::Shard;
 use tari_dan_storage::{consensus_models, consensus_models::BlockId, StorageError};
-use tari_transaction::VersionedSubstateId;
 use time::PrimitiveDateTime;
 
-use crate::serialization::{deserialize_hex_try_from, deserialize_json};
-
 #[derive(Debug, Clone, Queryable)]
 pub struct BlockDiff {
     pub id: i32,
@@ -32,32 +28,6 @@ impl BlockDiff {
     }
 
     pub fn try_convert_change(d: Self) -> Result<consensus_models::SubstateChange, StorageError> {
-        let substate_id = d.substate_id.parse().map_err(|err| StorageError::DataInconsistency {
-            details: format!("Invalid substate id {}: {}", d.substate_id, err),
-        })?;
-        let id = VersionedSubstateId::new(substate_id, d.version as u32);
-        let transaction_id = deserialize_hex_try_from(&d.transaction_id)?;
-        let shard = Shard::from(d.shard as u32);
-        match d.change.as_str() {
-            "Up" => {
-                let state = d.state.ok_or(StorageError::DataInconsistency {
-                    details: "Block diff change type is Up but state is missing".to_string(),
-                })?;
-                Ok(consensus_models::SubstateChange::Up {
-                    id,
-                    shard,
-                    transaction_id,
-                    substate: deserialize_json(&state)?,
-                })
-            },
-            "Down" => Ok(consensus_models::SubstateChange::Down {
-                id,
-                transaction_id,
-                shard,
-            }),
-            _ => Err(StorageError::DataInconsistency {
-                details: format!("Invalid block diff change type: {}", d.change),
-            }),
-        }
+        consensus_models::SubstateChange::from_change_string(&d.change)
     }
 }
@@ -8,11 +8,13 @@ mod epoch_checkpoint;
 mod leaf_block;
 mod pending_state_tree_diff;
 mod quorum_certificate;
+mod rejected_block;
 mod state_transition;
 mod substate;
 mod substate_lock;
 mod transaction;
 mod transaction_execution;
+mod transaction_input;
 mod transaction_pool;
 mod vote;
 
@@ -23,10 +25,12 @@ pub use epoch_checkpoint::*;
 pub use leaf_block::*;
 pub use pending_state_tree_diff::*;
 pub use quorum_certificate::*;
+pub use rejected_block::*;
 pub use state_transition::*;
 pub use substate::*;
 pub use substate_lock::*;
 pub use transaction::*;
 pub use transaction_execution::*;
+pub use transaction_input::*;
 pub use transaction_pool::*;
 pub use vote::*;
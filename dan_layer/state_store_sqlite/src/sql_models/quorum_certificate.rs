@@ -14,6 +14,7 @@ pub struct QuorumCertificate {
     pub block_id: String,
     pub json: String,
     pub created_at: PrimitiveDateTime,
+    pub signature_count: i32,
 }
 
 impl TryFrom<QuorumCertificate> for consensus_models::QuorumCertificate {
@@ -0,0 +1,31 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use diesel::Queryable;
+use tari_dan_common_types::NodeHeight;
+use tari_dan_storage::{consensus_models, StorageError};
+use time::PrimitiveDateTime;
+
+use crate::serialization::deserialize_hex_try_from;
+
+#[derive(Debug, Clone, Queryable)]
+pub struct RejectedBlock {
+    pub id: i32,
+    pub block_id: String,
+    pub height: i64,
+    pub reason: String,
+    pub created_at: PrimitiveDateTime,
+}
+
+impl TryFrom<RejectedBlock> for consensus_models::RejectedBlock {
+    type Error = StorageError;
+
+    fn try_from(value: RejectedBlock) -> Result<Self, Self::Error> {
+        Ok(Self {
+            block_id: deserialize_hex_try_from(&value.block_id)?,
+            height: NodeHeight(value.height as u64),
+            reason: value.reason,
+            created_at: value.created_at,
+        })
+    }
+}
@@ -9,7 +9,7 @@ use tari_dan_storage::{consensus_models, consensus_models::Decision, StorageErro
 use tari_transaction::UnsignedTransaction;
 use time::PrimitiveDateTime;
 
-use crate::serialization::deserialize_json;
+use crate::serialization::{deserialize_json, parse_from_string};
 
 #[derive(Debug, Clone, Queryable)]
 pub struct Transaction {
@@ -30,6 +30,8 @@ pub struct Transaction {
     pub min_epoch: Option<i64>,
     pub max_epoch: Option<i64>,
     pub created_at: PrimitiveDateTime,
+    pub finalized_in_block: Option<String>,
+    pub abort_reason: Option<String>,
 }
 
 impl TryFrom<Transaction> for tari_transaction::Transaction {
@@ -87,6 +89,7 @@ impl TryFrom<Transaction> for consensus_models::TransactionRecord {
             .unwrap_or_default();
         let resolved_inputs = value.resolved_inputs.as_deref().map(deserialize_json).transpose()?;
         let abort_details = value.abort_details.clone();
+        let abort_reason = value.abort_reason.as_deref().map(parse_from_string).transpose()?;
 
         let finalized_time = value
             .finalized_at
@@ -102,6 +105,7 @@ impl TryFrom<Transaction> for consensus_models::TransactionRecord {
             finalized_time,
             resulting_outputs,
             abort_details,
+            abort_reason,
         ))
     }
 }
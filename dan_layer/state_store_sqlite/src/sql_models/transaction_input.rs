@@ -0,0 +1,35 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use diesel::Queryable;
+use tari_dan_storage::{consensus_models::VersionedSubstateIdLockIntent, StorageError};
+use tari_transaction::VersionedSubstateId;
+use time::PrimitiveDateTime;
+
+use crate::serialization::parse_from_string;
+
+#[derive(Debug, Clone, Queryable)]
+pub struct TransactionInput {
+    pub id: i32,
+    pub transaction_id: String,
+    pub substate_id: String,
+    pub version: i32,
+    pub lock_flag: String,
+    pub created_at: PrimitiveDateTime,
+}
+
+impl TryFrom<TransactionInput> for VersionedSubstateIdLockIntent {
+    type Error = StorageError;
+
+    fn try_from(value: TransactionInput) -> Result<Self, Self::Error> {
+        let substate_id = parse_from_string(&value.substate_id)?;
+        let lock_flag = value.lock_flag.parse().map_err(|_| StorageError::DataInconsistency {
+            details: format!("Failed to parse SubstateLockFlag: {}", value.lock_flag),
+        })?;
+
+        Ok(VersionedSubstateIdLockIntent::new(
+            VersionedSubstateId::new(substate_id, value.version as u32),
+            lock_flag,
+        ))
+    }
+}
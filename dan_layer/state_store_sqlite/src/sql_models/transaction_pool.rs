@@ -2,6 +2,7 @@
 //    SPDX-License-Identifier: BSD-3-Clause
 
 use diesel::{Queryable, QueryableByName};
+use tari_dan_common_types::NodeHeight;
 use tari_dan_storage::{
     consensus_models,
     consensus_models::{Evidence, LeaderFee},
@@ -114,3 +115,66 @@ pub struct TransactionPoolStateUpdate {
     #[diesel(sql_type = diesel::sql_types::Timestamp)]
     pub created_at: PrimitiveDateTime,
 }
+
+impl TryFrom<TransactionPoolStateUpdate> for consensus_models::TransactionPoolStatusUpdate {
+    type Error = StorageError;
+
+    fn try_from(value: TransactionPoolStateUpdate) -> Result<Self, Self::Error> {
+        Ok(Self {
+            block_id: deserialize_hex_try_from(&value.block_id)?,
+            block_height: NodeHeight(value.block_height as u64),
+            transaction_id: deserialize_hex_try_from(&value.transaction_id)?,
+            stage: parse_from_string(&value.stage)?,
+            evidence: deserialize_json(&value.evidence)?,
+            is_ready: value.is_ready,
+            local_decision: value
+                .local_decision
+                .as_deref()
+                .map(parse_from_string)
+                .transpose()?
+                .ok_or_else(|| StorageError::DataInconsistency {
+                    details: format!("TransactionPoolStateUpdate {} has no local_decision", value.id),
+                })?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Queryable)]
+pub struct TransactionPoolHistory {
+    pub history_id: Option<i32>,
+    pub id: i32,
+    pub transaction_id: String,
+    pub original_decision: String,
+    pub local_decision: Option<String>,
+    pub remote_decision: Option<String>,
+    pub evidence: Option<String>,
+    pub transaction_fee: Option<i64>,
+    pub leader_fee: Option<i64>,
+    pub global_exhaust_burn: Option<i64>,
+    pub stage: String,
+    pub new_stage: String,
+    pub is_ready: bool,
+    pub new_is_ready: bool,
+    pub updated_at: PrimitiveDateTime,
+    pub created_at: PrimitiveDateTime,
+    pub change_time: Option<PrimitiveDateTime>,
+}
+
+impl TryFrom<TransactionPoolHistory> for consensus_models::TransactionPoolHistoryEntry {
+    type Error = StorageError;
+
+    fn try_from(value: TransactionPoolHistory) -> Result<Self, Self::Error> {
+        Ok(Self {
+            transaction_id: deserialize_hex_try_from(&value.transaction_id)?,
+            original_decision: parse_from_string(&value.original_decision)?,
+            local_decision: value.local_decision.as_deref().map(parse_from_string).transpose()?,
+            remote_decision: value.remote_decision.as_deref().map(parse_from_string).transpose()?,
+            transaction_fee: value.transaction_fee.map(|f| f as u64),
+            stage: parse_from_string(&value.stage)?,
+            new_stage: parse_from_string(&value.new_stage)?,
+            is_ready: value.is_ready,
+            new_is_ready: value.new_is_ready,
+            change_time: value.change_time,
+        })
+    }
+}
@@ -16,6 +16,7 @@ use tari_dan_common_types::NodeAddressable;
 use tari_dan_storage::{StateStore, StorageError};
 
 use crate::{
+    config::SqliteStoreConfig,
     error::SqliteStorageError,
     reader::SqliteStateStoreReadTransaction,
     sqlite_transaction::SqliteTransaction,
@@ -26,13 +27,32 @@ const LOG_TARGET: &str = "tari::dan::storage::sqlite::state_store";
 
 pub struct SqliteStateStore<TAddr> {
     connection: Arc<Mutex<SqliteConnection>>,
+    max_sql_variables: usize,
     _addr: PhantomData<TAddr>,
 }
 
 impl<TAddr> SqliteStateStore<TAddr> {
     pub fn connect(url: &str) -> Result<Self, StorageError> {
+        Self::connect_with_config(url, &SqliteStoreConfig::default())
+    }
+
+    pub fn connect_with_config(url: &str, config: &SqliteStoreConfig) -> Result<Self, StorageError> {
         let mut connection = SqliteConnection::establish(url).map_err(SqliteStorageError::from)?;
 
+        // The busy timeout must be set first so that the remaining statements wait for other connections
+        for pragma in [
+            format!("PRAGMA busy_timeout = {};", config.busy_timeout.as_millis()),
+            format!("PRAGMA journal_mode = {};", config.journal_mode),
+            format!("PRAGMA synchronous = {};", config.synchronous),
+        ] {
+            sql_query(pragma)
+                .execute(&mut connection)
+                .map_err(|source| SqliteStorageError::DieselError {
+                    source,
+                    operation: "set pragma",
+                })?;
+        }
+
         const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
         connection
             .run_pending_migrations(MIGRATIONS)
@@ -47,6 +67,7 @@ impl<TAddr> SqliteStateStore<TAddr> {
 
         Ok(Self {
             connection: Arc::new(Mutex::new(connection)),
+            max_sql_variables: config.max_sql_variables,
             _addr: PhantomData,
         })
     }
@@ -76,13 +97,13 @@ impl<TAddr: NodeAddressable + Serialize + DeserializeOwned> StateStore for Sqlit
 
     fn create_read_tx(&self) -> Result<Self::ReadTransaction<'_>, StorageError> {
         let tx = SqliteTransaction::begin(self.connection.lock().unwrap())?;
-        Ok(SqliteStateStoreReadTransaction::new(tx))
+        Ok(SqliteStateStoreReadTransaction::new(tx, self.max_sql_variables))
     }
 
     fn create_write_tx(&self) -> Result<Self::WriteTransaction<'_>, StorageError> {
         let timer = Instant::now();
         let tx = SqliteTransaction::begin(self.connection.lock().unwrap())?;
-        let tx = SqliteStateStoreWriteTransaction::new(tx);
+        let tx = SqliteStateStoreWriteTransaction::new(tx, self.max_sql_variables);
         let elapsed = timer.elapsed();
         let level = if elapsed > Duration::from_secs(1) {
             log::Level::Warn
@@ -102,6 +123,7 @@ impl<TAddr> Clone for SqliteStateStore<TAddr> {
     fn clone(&self) -> Self {
         Self {
             connection: self.connection.clone(),
+            max_sql_variables: self.max_sql_variables,
             _addr: PhantomData,
         }
     }
@@ -1,7 +1,11 @@
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
-use std::ops::Deref;
+use std::{
+    collections::HashSet,
+    ops::{Deref, DerefMut},
+    time::Duration,
+};
 
 use diesel::{
     dsl,
@@ -11,6 +15,7 @@ use diesel::{
     NullableExpressionMethods,
     OptionalExtension,
     QueryDsl,
+    QueryableByName,
     RunQueryDsl,
     SqliteConnection,
 };
@@ -19,6 +24,7 @@ use log::*;
 use tari_dan_common_types::{shard::Shard, Epoch, NodeAddressable, NodeHeight};
 use tari_dan_storage::{
     consensus_models::{
+        AbortReason,
         Block,
         BlockDiff,
         BlockId,
@@ -46,6 +52,7 @@ use tari_dan_storage::{
         TransactionPoolStatusUpdate,
         TransactionRecord,
         VersionedStateHashTreeDiff,
+        VersionedSubstateIdLockIntent,
         Vote,
     },
     StateStoreReadTransaction,
@@ -68,15 +75,18 @@ use crate::{
 
 const LOG_TARGET: &str = "tari::dan::storage";
 
+/// The maximum number of rejected blocks that are retained. Older rows are removed when a new block is recorded.
+pub const MAX_REJECTED_BLOCKS: i64 = 1000;
+
 pub struct SqliteStateStoreWriteTransaction<'a, TAddr> {
     /// None indicates if the transaction has been explicitly committed/rolled back
     transaction: Option<SqliteStateStoreReadTransaction<'a, TAddr>>,
 }
 
 impl<'a, TAddr: NodeAddressable> SqliteStateStoreWriteTransaction<'a, TAddr> {
-    pub fn new(transaction: SqliteTransaction<'a>) -> Self {
+    pub fn new(transaction: SqliteTransaction<'a>, max_sql_variables: usize) -> Self {
         Self {
-            transaction: Some(SqliteStateStoreReadTransaction::new(transaction)),
+            transaction: Some(SqliteStateStoreReadTransaction::new(transaction, max_sql_variables)),
         }
     }
 
@@ -153,6 +163,7 @@ impl<'a, TAddr: NodeAddressable> SqliteStateStoreWriteTransaction<'a, TAddr> {
             parked_blocks::parent_block_id.eq(serialize_hex(block.parent())),
             parked_blocks::network.eq(block.network().to_string()),
             parked_blocks::merkle_root.eq(block.merkle_root().to_string()),
+            parked_blocks::command_merkle_root.eq(serialize_hex(block.command_merkle_root())),
             parked_blocks::height.eq(block.height().as_u64() as i64),
             parked_blocks::epoch.eq(block.epoch().as_u64() as i64),
             parked_blocks::shard_group.eq(block.shard_group().encode_as_u32() as i32),
@@ -179,6 +190,180 @@ impl<'a, TAddr: NodeAddressable> SqliteStateStoreWriteTransaction<'a, TAddr> {
 
         Ok(())
     }
+
+    fn execute_savepoint_sql(&mut self, sql: &str) -> Result<(), StorageError> {
+        diesel::sql_query(sql)
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "savepoint",
+                source: e,
+            })?;
+        Ok(())
+    }
+
+    fn transactions_purge_inner(&mut self, transaction_id: &str) -> Result<(), StorageError> {
+        use crate::schema::{
+            pending_transaction_aborts,
+            transaction_executions,
+            transaction_inputs,
+            transaction_outputs,
+            transaction_pool,
+            transaction_pool_state_updates,
+            transactions,
+        };
+
+        diesel::delete(pending_transaction_aborts::table)
+            .filter(pending_transaction_aborts::transaction_id.eq(transaction_id))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_purge",
+                source: e,
+            })?;
+
+        diesel::delete(transaction_pool_state_updates::table)
+            .filter(transaction_pool_state_updates::transaction_id.eq(transaction_id))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_purge",
+                source: e,
+            })?;
+
+        diesel::delete(transaction_pool::table)
+            .filter(transaction_pool::transaction_id.eq(transaction_id))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_purge",
+                source: e,
+            })?;
+
+        diesel::delete(transaction_executions::table)
+            .filter(transaction_executions::transaction_id.eq(transaction_id))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_purge",
+                source: e,
+            })?;
+
+        diesel::delete(transaction_inputs::table)
+            .filter(transaction_inputs::transaction_id.eq(transaction_id))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_purge",
+                source: e,
+            })?;
+
+        diesel::delete(transaction_outputs::table)
+            .filter(transaction_outputs::transaction_id.eq(transaction_id))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_purge",
+                source: e,
+            })?;
+
+        let num_deleted = diesel::delete(transactions::table)
+            .filter(transactions::transaction_id.eq(transaction_id))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_purge",
+                source: e,
+            })?;
+
+        if num_deleted == 0 {
+            return Err(StorageError::NotFound {
+                item: "transaction".to_string(),
+                key: transaction_id.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn transaction_inputs_set(
+        &mut self,
+        transaction_id: &TransactionId,
+        inputs: &[VersionedSubstateIdLockIntent],
+    ) -> Result<(), StorageError> {
+        use crate::schema::transaction_inputs;
+
+        let transaction_id = serialize_hex(transaction_id);
+
+        diesel::delete(transaction_inputs::table)
+            .filter(transaction_inputs::transaction_id.eq(&transaction_id))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_inputs_set",
+                source: e,
+            })?;
+
+        if inputs.is_empty() {
+            return Ok(());
+        }
+
+        let values = inputs
+            .iter()
+            .map(|input| {
+                (
+                    transaction_inputs::transaction_id.eq(&transaction_id),
+                    transaction_inputs::substate_id.eq(input.versioned_substate_id().substate_id().to_string()),
+                    transaction_inputs::version.eq(input.versioned_substate_id().version() as i32),
+                    transaction_inputs::lock_flag.eq(input.lock_flag().to_string()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(transaction_inputs::table)
+            .values(values)
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_inputs_set",
+                source: e,
+            })?;
+
+        Ok(())
+    }
+
+    fn transaction_outputs_set(
+        &mut self,
+        transaction_id: &TransactionId,
+        outputs: &[VersionedSubstateId],
+    ) -> Result<(), StorageError> {
+        use crate::schema::transaction_outputs;
+
+        let transaction_id = serialize_hex(transaction_id);
+
+        diesel::delete(transaction_outputs::table)
+            .filter(transaction_outputs::transaction_id.eq(&transaction_id))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_outputs_set",
+                source: e,
+            })?;
+
+        if outputs.is_empty() {
+            return Ok(());
+        }
+
+        let values = outputs
+            .iter()
+            .map(|output| {
+                (
+                    transaction_outputs::transaction_id.eq(&transaction_id),
+                    transaction_outputs::substate_id.eq(output.substate_id.to_string()),
+                    transaction_outputs::version.eq(output.version as i32),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(transaction_outputs::table)
+            .values(values)
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_outputs_set",
+                source: e,
+            })?;
+
+        Ok(())
+    }
 }
 
 impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteStateStoreWriteTransaction<'tx, TAddr> {
@@ -203,6 +388,7 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
             blocks::block_id.eq(serialize_hex(block.id())),
             blocks::parent_block_id.eq(serialize_hex(block.parent())),
             blocks::merkle_root.eq(block.merkle_root().to_string()),
+            blocks::command_merkle_root.eq(serialize_hex(block.command_merkle_root())),
             blocks::network.eq(block.network().to_string()),
             blocks::height.eq(block.height().as_u64() as i64),
             blocks::epoch.eq(block.epoch().as_u64() as i64),
@@ -283,9 +469,12 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
     fn block_diffs_insert(&mut self, block_diff: &BlockDiff) -> Result<(), StorageError> {
         use crate::schema::block_diffs;
 
+        // The number of columns bound for each inserted change below
+        const COLUMNS_PER_ROW: usize = 7;
+
         let block_id = serialize_hex(block_diff.block_id);
         // We commit in chunks because we can hit the SQL variable limit
-        for chunk in block_diff.changes.chunks(1000) {
+        for chunk in block_diff.changes.chunks(self.sql_variable_chunk_size(COLUMNS_PER_ROW)) {
             let values = chunk
                 .iter()
                 .map(|ch| {
@@ -295,7 +484,7 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
                         block_diffs::substate_id.eq(ch.versioned_substate_id().substate_id().to_string()),
                         block_diffs::version.eq(ch.versioned_substate_id().version() as i32),
                         block_diffs::shard.eq(ch.shard().as_u32() as i32),
-                        block_diffs::change.eq(ch.as_change_string()),
+                        block_diffs::change.eq(ch.as_change_string()?),
                         block_diffs::state.eq(ch.substate().map(serialize_json).transpose()?),
                     ))
                 })
@@ -335,6 +524,7 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
             quorum_certificates::qc_id.eq(serialize_hex(qc.id())),
             quorum_certificates::block_id.eq(serialize_hex(qc.block_id())),
             quorum_certificates::json.eq(serialize_json(qc)?),
+            quorum_certificates::signature_count.eq(qc.signatures().len() as i32),
         );
 
         diesel::insert_into(quorum_certificates::table)
@@ -370,6 +560,19 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
         Ok(())
     }
 
+    fn last_sent_vote_clear(&mut self) -> Result<(), StorageError> {
+        use crate::schema::last_sent_vote;
+
+        diesel::delete(last_sent_vote::table)
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "last_sent_vote_clear",
+                source: e,
+            })?;
+
+        Ok(())
+    }
+
     fn last_voted_set(&mut self, last_voted: &LastVoted) -> Result<(), StorageError> {
         use crate::schema::last_voted;
 
@@ -561,6 +764,20 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
         Ok(())
     }
 
+    fn foreign_proposals_delete_below_base_layer_height(&mut self, height: u64) -> Result<usize, StorageError> {
+        use crate::schema::foreign_proposals;
+
+        let num_deleted = diesel::delete(foreign_proposals::table)
+            .filter(foreign_proposals::base_layer_block_height.lt(height as i64))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "foreign_proposals_delete_below_base_layer_height",
+                source: e,
+            })?;
+
+        Ok(num_deleted)
+    }
+
     fn foreign_send_counters_set(
         &mut self,
         foreign_send_counter: &ForeignSendCounters,
@@ -632,6 +849,7 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
                     reason: format!("Cannot convert finalize time into PrimitiveDateTime: {e}"),
                 })?),
             transactions::abort_details.eq(tx_rec.abort_details()),
+            transactions::abort_reason.eq(tx_rec.abort_reason.map(|r| r.to_string())),
             transactions::min_epoch.eq(transaction.min_epoch().map(|e| e.as_u64() as i64)),
             transactions::max_epoch.eq(transaction.max_epoch().map(|e| e.as_u64() as i64)),
         );
@@ -644,6 +862,9 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
                 source: e,
             })?;
 
+        self.transaction_inputs_set(transaction.id(), tx_rec.resolved_inputs().unwrap_or_default())?;
+        self.transaction_outputs_set(transaction.id(), tx_rec.resulting_outputs())?;
+
         Ok(())
     }
 
@@ -663,6 +884,7 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
             final_decision: Option<String>,
             finalized_at: Option<PrimitiveDateTime>,
             abort_details: Option<String>,
+            abort_reason: Option<String>,
         }
 
         let change_set = Changes {
@@ -680,6 +902,7 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
                 PrimitiveDateTime::new(now.date(), now.time())
             }),
             abort_details: transaction_rec.abort_details.clone(),
+            abort_reason: transaction_rec.abort_reason.map(|r| r.to_string()),
         };
 
         let num_affected = diesel::update(transactions::table)
@@ -698,6 +921,9 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
             });
         }
 
+        self.transaction_inputs_set(transaction.id(), transaction_rec.resolved_inputs().unwrap_or_default())?;
+        self.transaction_outputs_set(transaction.id(), transaction_rec.resulting_outputs())?;
+
         Ok(())
     }
 
@@ -705,12 +931,30 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
         &mut self,
         txs: I,
     ) -> Result<(), StorageError> {
-        use crate::schema::transactions;
+        use crate::schema::{transaction_inputs, transaction_outputs, transactions};
 
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
         let insert = txs
             .into_iter()
             .map(|rec| {
                 let transaction = rec.transaction();
+                let transaction_id = serialize_hex(transaction.id());
+                inputs.extend(rec.resolved_inputs().unwrap_or_default().iter().map(|input| {
+                    (
+                        transaction_inputs::transaction_id.eq(transaction_id.clone()),
+                        transaction_inputs::substate_id.eq(input.versioned_substate_id().substate_id().to_string()),
+                        transaction_inputs::version.eq(input.versioned_substate_id().version() as i32),
+                        transaction_inputs::lock_flag.eq(input.lock_flag().to_string()),
+                    )
+                }));
+                outputs.extend(rec.resulting_outputs().iter().map(|output| {
+                    (
+                        transaction_outputs::transaction_id.eq(transaction_id.clone()),
+                        transaction_outputs::substate_id.eq(output.substate_id.to_string()),
+                        transaction_outputs::version.eq(output.version as i32),
+                    )
+                }));
                 Ok((
                     transactions::transaction_id.eq(serialize_hex(transaction.id())),
                     transactions::fee_instructions.eq(serialize_json(transaction.fee_instructions())?),
@@ -733,6 +977,26 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
                 source: e,
             })?;
 
+        if !inputs.is_empty() {
+            diesel::insert_or_ignore_into(transaction_inputs::table)
+                .values(inputs)
+                .execute(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "transactions_insert",
+                    source: e,
+                })?;
+        }
+
+        if !outputs.is_empty() {
+            diesel::insert_or_ignore_into(transaction_outputs::table)
+                .values(outputs)
+                .execute(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "transactions_insert",
+                    source: e,
+                })?;
+        }
+
         Ok(())
     }
 
@@ -743,6 +1007,8 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
     ) -> Result<(), StorageError> {
         use crate::schema::transactions;
 
+        let finalized_in_block = serialize_hex(block_id);
+
         let changes = transactions
             .into_iter()
             .map(|atom| {
@@ -762,6 +1028,15 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
                 //     },
                 // };
 
+                // An abort that the local executor rejected is recorded as an execution failure. Any other abort keeps
+                // the reason (e.g. a lock conflict) that was recorded when the abort was committed.
+                let execution_failure = atom
+                    .decision
+                    .is_abort()
+                    .then(|| exec.result().finalize.reject())
+                    .flatten()
+                    .map(|reason| reason.to_string());
+
                 Ok((
                     transactions::transaction_id.eq(serialize_hex(atom.id())),
                     (
@@ -771,12 +1046,14 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
                         transactions::execution_time_ms.eq(exec.execution_time().as_millis() as i64),
                         transactions::final_decision.eq(atom.decision.to_string()),
                         transactions::finalized_at.eq(now()),
+                        transactions::finalized_in_block.eq(&finalized_in_block),
                     ),
+                    (atom.id, exec.resolved_inputs, exec.resulting_outputs, execution_failure),
                 ))
             })
             .collect::<Result<Vec<_>, StorageError>>()?;
 
-        for (predicate, change) in changes {
+        for (predicate, change, (transaction_id, inputs, outputs, execution_failure)) in changes {
             diesel::update(transactions::table)
                 .filter(predicate)
                 .set(change)
@@ -785,11 +1062,136 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
                     operation: "transactions_finalize_all",
                     source: e,
                 })?;
+
+            if let Some(abort_details) = execution_failure {
+                self.transactions_set_abort_details(
+                    &transaction_id,
+                    Some(AbortReason::ExecutionFailure),
+                    Some(&abort_details),
+                )?;
+            }
+
+            self.transaction_inputs_set(&transaction_id, &inputs)?;
+            self.transaction_outputs_set(&transaction_id, &outputs)?;
+        }
+
+        Ok(())
+    }
+
+    fn transactions_set_abort_details(
+        &mut self,
+        tx_id: &TransactionId,
+        abort_reason: Option<AbortReason>,
+        abort_details: Option<&str>,
+    ) -> Result<(), StorageError> {
+        use crate::schema::transactions;
+
+        let num_affected = diesel::update(transactions::table)
+            .filter(transactions::transaction_id.eq(serialize_hex(tx_id)))
+            .set((
+                transactions::abort_reason.eq(abort_reason.map(|r| r.to_string())),
+                transactions::abort_details.eq(abort_details),
+            ))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_set_abort_details",
+                source: e,
+            })?;
+
+        if num_affected == 0 {
+            return Err(StorageError::NotFound {
+                item: "transaction".to_string(),
+                key: tx_id.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn transactions_add_pending_abort(
+        &mut self,
+        block_id: &BlockId,
+        block_height: NodeHeight,
+        tx_id: &TransactionId,
+        abort_reason: AbortReason,
+        abort_details: &str,
+    ) -> Result<(), StorageError> {
+        use crate::schema::pending_transaction_aborts;
+
+        diesel::replace_into(pending_transaction_aborts::table)
+            .values((
+                pending_transaction_aborts::block_id.eq(serialize_hex(block_id)),
+                pending_transaction_aborts::block_height.eq(block_height.as_u64() as i64),
+                pending_transaction_aborts::transaction_id.eq(serialize_hex(tx_id)),
+                pending_transaction_aborts::abort_reason.eq(abort_reason.to_string()),
+                pending_transaction_aborts::abort_details.eq(abort_details),
+            ))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_add_pending_abort",
+                source: e,
+            })?;
+
+        Ok(())
+    }
+
+    fn transactions_commit_pending_aborts(
+        &mut self,
+        block_id: &BlockId,
+        block_height: NodeHeight,
+    ) -> Result<(), StorageError> {
+        use crate::schema::{pending_transaction_aborts, transactions};
+
+        let aborts = pending_transaction_aborts::table
+            .select((
+                pending_transaction_aborts::transaction_id,
+                pending_transaction_aborts::abort_reason,
+                pending_transaction_aborts::abort_details,
+            ))
+            .filter(pending_transaction_aborts::block_id.eq(serialize_hex(block_id)))
+            .get_results::<(String, String, String)>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_commit_pending_aborts",
+                source: e,
+            })?;
+
+        for (transaction_id, abort_reason, abort_details) in aborts {
+            diesel::update(transactions::table)
+                .filter(transactions::transaction_id.eq(&transaction_id))
+                .set((
+                    transactions::abort_reason.eq(abort_reason),
+                    transactions::abort_details.eq(abort_details),
+                ))
+                .execute(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "transactions_commit_pending_aborts",
+                    source: e,
+                })?;
         }
 
+        diesel::delete(pending_transaction_aborts::table)
+            .filter(pending_transaction_aborts::block_height.le(block_height.as_u64() as i64))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transactions_commit_pending_aborts",
+                source: e,
+            })?;
+
         Ok(())
     }
 
+    fn transactions_purge(&mut self, tx_id: &TransactionId) -> Result<(), StorageError> {
+        // The savepoint ensures that a partial purge is undone even if the outer transaction is committed
+        self.execute_savepoint_sql("SAVEPOINT transactions_purge")?;
+        let result = self.transactions_purge_inner(&serialize_hex(tx_id));
+        if result.is_err() {
+            self.execute_savepoint_sql("ROLLBACK TO SAVEPOINT transactions_purge")?;
+        }
+        self.execute_savepoint_sql("RELEASE SAVEPOINT transactions_purge")?;
+
+        result
+    }
+
     fn transaction_executions_insert_or_ignore(
         &mut self,
         transaction_execution: &TransactionExecution,
@@ -843,6 +1245,41 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
         Ok(())
     }
 
+    fn transaction_pool_insert_new_batch<I: IntoIterator<Item = (TransactionId, Decision)>>(
+        &mut self,
+        items: I,
+    ) -> Result<(), StorageError> {
+        use crate::schema::transaction_pool;
+
+        // The number of columns bound for each inserted transaction below
+        const COLUMNS_PER_ROW: usize = 4;
+
+        let inserts = items
+            .into_iter()
+            .map(|(transaction_id, decision)| {
+                (
+                    transaction_pool::transaction_id.eq(serialize_hex(transaction_id)),
+                    transaction_pool::original_decision.eq(decision.to_string()),
+                    transaction_pool::stage.eq(TransactionPoolStage::New.to_string()),
+                    transaction_pool::is_ready.eq(true),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // We have to break up into multiple queries because we can hit max SQL variable limit
+        for chunk in inserts.chunks(self.sql_variable_chunk_size(COLUMNS_PER_ROW)) {
+            diesel::insert_or_ignore_into(transaction_pool::table)
+                .values(chunk)
+                .execute(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "transaction_pool_insert_new_batch",
+                    source: e,
+                })?;
+        }
+
+        Ok(())
+    }
+
     fn transaction_pool_set_atom(&mut self, transaction: TransactionAtom) -> Result<(), StorageError> {
         use crate::schema::transaction_pool;
 
@@ -1128,6 +1565,149 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
         Ok(())
     }
 
+    fn transaction_pool_history_prune(&mut self, older_than: Duration) -> Result<usize, StorageError> {
+        use crate::schema::transaction_pool_history;
+
+        let cutoff =
+            OffsetDateTime::now_utc()
+                .checked_sub(older_than.try_into().map_err(|e: time::error::ConversionRange| {
+                    StorageError::QueryError {
+                        reason: format!("Cannot convert {older_than:?} into a time Duration: {e}"),
+                    }
+                })?)
+                .ok_or_else(|| StorageError::QueryError {
+                    reason: format!("Prune cutoff {older_than:?} is out of range"),
+                })?;
+        let cutoff = PrimitiveDateTime::new(cutoff.date(), cutoff.time());
+
+        let num_deleted = diesel::delete(transaction_pool_history::table)
+            .filter(transaction_pool_history::change_time.lt(cutoff))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_pool_history_prune",
+                source: e,
+            })?;
+
+        Ok(num_deleted)
+    }
+
+    fn transaction_pool_prune_orphaned_updates(
+        &mut self,
+        valid_block_ids: &HashSet<BlockId>,
+    ) -> Result<usize, StorageError> {
+        use crate::schema::{transaction_pool, transaction_pool_state_updates};
+
+        #[derive(QueryableByName)]
+        struct TransactionIdSqlValue {
+            #[diesel(sql_type = Text)]
+            transaction_id: String,
+        }
+
+        // The valid set may be larger than the SQL variable limit, so rather than binding it in the DELETE, we load it
+        // into a temporary table in chunks and filter against that.
+        diesel::sql_query("CREATE TEMP TABLE IF NOT EXISTS valid_block_ids (block_id TEXT NOT NULL PRIMARY KEY)")
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_pool_prune_orphaned_updates",
+                source: e,
+            })?;
+        diesel::sql_query("DELETE FROM valid_block_ids")
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_pool_prune_orphaned_updates",
+                source: e,
+            })?;
+
+        let valid_block_ids = valid_block_ids.iter().map(serialize_hex).collect::<Vec<_>>();
+        for chunk in valid_block_ids.chunks(self.sql_variable_chunk_size(1)) {
+            let values = vec!["(?)"; chunk.len()].join(", ");
+            let mut query = diesel::sql_query(format!(
+                "INSERT OR IGNORE INTO valid_block_ids (block_id) VALUES {values}"
+            ))
+            .into_boxed();
+            for block_id in chunk {
+                query = query.bind::<Text, _>(block_id);
+            }
+            query
+                .execute(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "transaction_pool_prune_orphaned_updates",
+                    source: e,
+                })?;
+        }
+
+        let affected_transactions = diesel::sql_query(
+            r#"
+            DELETE FROM transaction_pool_state_updates
+            WHERE block_id NOT IN (SELECT block_id FROM valid_block_ids)
+            RETURNING transaction_id"#,
+        )
+        .get_results::<TransactionIdSqlValue>(self.connection())
+        .map_err(|e| SqliteStorageError::DieselError {
+            operation: "transaction_pool_prune_orphaned_updates",
+            source: e,
+        })?;
+        let num_removed = affected_transactions.len();
+
+        diesel::sql_query("DROP TABLE valid_block_ids")
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "transaction_pool_prune_orphaned_updates",
+                source: e,
+            })?;
+
+        let affected_transactions = affected_transactions
+            .into_iter()
+            .map(|row| row.transaction_id)
+            .collect::<HashSet<_>>();
+        for transaction_id in affected_transactions {
+            let latest_update = transaction_pool_state_updates::table
+                .select((
+                    transaction_pool_state_updates::stage,
+                    transaction_pool_state_updates::is_ready,
+                ))
+                .filter(transaction_pool_state_updates::transaction_id.eq(&transaction_id))
+                .order_by(transaction_pool_state_updates::block_height.desc())
+                .then_order_by(transaction_pool_state_updates::id.desc())
+                .first::<(String, bool)>(self.connection())
+                .optional()
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "transaction_pool_prune_orphaned_updates",
+                    source: e,
+                })?;
+
+            match latest_update {
+                Some((stage, is_ready)) => {
+                    diesel::update(transaction_pool::table)
+                        .filter(transaction_pool::transaction_id.eq(&transaction_id))
+                        .set((
+                            transaction_pool::pending_stage.eq(stage),
+                            transaction_pool::is_ready.eq(is_ready),
+                        ))
+                        .execute(self.connection())
+                        .map_err(|e| SqliteStorageError::DieselError {
+                            operation: "transaction_pool_prune_orphaned_updates",
+                            source: e,
+                        })?;
+                },
+                // Without any remaining updates, the transaction is back at its committed stage. The readiness that
+                // was committed with that stage is not kept separately, so the record keeps its current readiness.
+                None => {
+                    diesel::update(transaction_pool::table)
+                        .filter(transaction_pool::transaction_id.eq(&transaction_id))
+                        .set(transaction_pool::pending_stage.eq(None::<String>))
+                        .execute(self.connection())
+                        .map_err(|e| SqliteStorageError::DieselError {
+                            operation: "transaction_pool_prune_orphaned_updates",
+                            source: e,
+                        })?;
+                },
+            }
+        }
+
+        Ok(num_removed)
+    }
+
     fn missing_transactions_insert<
         'a,
         IMissing: IntoIterator<Item = &'a TransactionId>,
@@ -1253,6 +1833,89 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
         Ok(())
     }
 
+    fn votes_insert_all<'a, I: IntoIterator<Item = &'a Vote>>(&mut self, votes: I) -> Result<(), StorageError> {
+        use crate::schema::votes;
+
+        // The number of columns bound for each inserted vote below
+        const COLUMNS_PER_ROW: usize = 6;
+
+        let inserts = votes
+            .into_iter()
+            .map(|vote| {
+                Ok((
+                    votes::hash.eq(serialize_hex(vote.calculate_hash())),
+                    votes::epoch.eq(vote.epoch.as_u64() as i64),
+                    votes::block_id.eq(serialize_hex(vote.block_id)),
+                    votes::sender_leaf_hash.eq(serialize_hex(vote.sender_leaf_hash)),
+                    votes::decision.eq(i32::from(vote.decision.as_u8())),
+                    votes::signature.eq(serialize_json(&vote.signature)?),
+                ))
+            })
+            .collect::<Result<Vec<_>, StorageError>>()?;
+
+        // We have to break up into multiple queries because we can hit max SQL variable limit
+        for chunk in inserts.chunks(self.sql_variable_chunk_size(COLUMNS_PER_ROW)) {
+            diesel::insert_or_ignore_into(votes::table)
+                .values(chunk)
+                .execute(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "votes_insert_all",
+                    source: e,
+                })?;
+        }
+
+        Ok(())
+    }
+
+    fn rejected_blocks_insert(
+        &mut self,
+        block_id: &BlockId,
+        height: NodeHeight,
+        reason: &str,
+    ) -> Result<(), StorageError> {
+        use crate::schema::rejected_blocks;
+
+        let num_inserted = diesel::insert_or_ignore_into(rejected_blocks::table)
+            .values((
+                rejected_blocks::block_id.eq(serialize_hex(block_id)),
+                rejected_blocks::height.eq(height.as_u64() as i64),
+                rejected_blocks::reason.eq(reason),
+            ))
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "rejected_blocks_insert",
+                source: e,
+            })?;
+
+        if num_inserted == 0 {
+            return Ok(());
+        }
+
+        // Remove the oldest rows so that the table does not grow without bound
+        let oldest_retained_id = rejected_blocks::table
+            .select(rejected_blocks::id)
+            .order_by(rejected_blocks::id.desc())
+            .offset(MAX_REJECTED_BLOCKS - 1)
+            .first::<i32>(self.connection())
+            .optional()
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "rejected_blocks_insert",
+                source: e,
+            })?;
+
+        if let Some(oldest_retained_id) = oldest_retained_id {
+            diesel::delete(rejected_blocks::table)
+                .filter(rejected_blocks::id.lt(oldest_retained_id))
+                .execute(self.connection())
+                .map_err(|e| SqliteStorageError::DieselError {
+                    operation: "rejected_blocks_insert",
+                    source: e,
+                })?;
+        }
+
+        Ok(())
+    }
+
     fn substate_locks_insert_all<I: IntoIterator<Item = (SubstateId, Vec<LockedSubstate>)>>(
         &mut self,
         block_id: BlockId,
@@ -1260,43 +1923,34 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
     ) -> Result<(), StorageError> {
         use crate::schema::substate_locks;
 
-        let mut iter = locks.into_iter();
-        const CHUNK_SIZE: usize = 100;
-        // We have to break up into multiple queries because we can hit max SQL variable limit
-        loop {
-            let locks = iter
-                .by_ref()
-                .take(CHUNK_SIZE)
-                .flat_map(|(id, locks)| {
-                    locks.into_iter().map(move |lock| {
-                        (
-                            substate_locks::block_id.eq(serialize_hex(block_id)),
-                            substate_locks::substate_id.eq(id.to_string()),
-                            substate_locks::version.eq(lock.version() as i32),
-                            substate_locks::transaction_id.eq(serialize_hex(lock.transaction_id())),
-                            substate_locks::lock.eq(lock.substate_lock().to_string()),
-                            substate_locks::is_local_only.eq(lock.is_local_only()),
-                        )
-                    })
-                })
-                .collect::<Vec<_>>();
+        // The number of columns bound for each inserted lock below
+        const COLUMNS_PER_ROW: usize = 6;
 
-            let count = locks.len();
-            if count == 0 {
-                break;
-            }
+        let locks = locks
+            .into_iter()
+            .flat_map(|(id, locks)| {
+                locks.into_iter().map(move |lock| {
+                    (
+                        substate_locks::block_id.eq(serialize_hex(block_id)),
+                        substate_locks::substate_id.eq(id.to_string()),
+                        substate_locks::version.eq(lock.version() as i32),
+                        substate_locks::transaction_id.eq(serialize_hex(lock.transaction_id())),
+                        substate_locks::lock.eq(lock.substate_lock().to_string()),
+                        substate_locks::is_local_only.eq(lock.is_local_only()),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
 
+        // We have to break up into multiple queries because we can hit max SQL variable limit
+        for chunk in locks.chunks(self.sql_variable_chunk_size(COLUMNS_PER_ROW)) {
             diesel::insert_into(substate_locks::table)
-                .values(locks)
+                .values(chunk)
                 .execute(self.connection())
                 .map_err(|e| SqliteStorageError::DieselError {
                     operation: "substate_locks_insert_all",
                     source: e,
                 })?;
-
-            if count < CHUNK_SIZE {
-                break;
-            }
         }
 
         Ok(())
@@ -1319,8 +1973,49 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
         Ok(())
     }
 
-    fn substates_create(&mut self, substate: SubstateRecord) -> Result<(), StorageError> {
-        use crate::schema::{state_transitions, substates};
+    fn substate_locks_remove_expired(&mut self, committed_up_to: BlockId) -> Result<usize, StorageError> {
+        use crate::schema::{blocks, substate_locks, transaction_pool};
+
+        let committed_height = blocks::table
+            .select(blocks::height)
+            .filter(blocks::block_id.eq(serialize_hex(committed_up_to)))
+            .first::<i64>(self.connection())
+            .optional()
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "substate_locks_remove_expired",
+                source: e,
+            })?
+            .ok_or_else(|| StorageError::NotFound {
+                item: "block".to_string(),
+                key: committed_up_to.to_string(),
+            })?;
+
+        let num_removed = diesel::delete(substate_locks::table)
+            .filter(
+                substate_locks::block_id.eq_any(
+                    blocks::table
+                        .select(blocks::block_id)
+                        .filter(blocks::height.le(committed_height)),
+                ),
+            )
+            .filter(
+                substate_locks::transaction_id.ne_all(transaction_pool::table.select(transaction_pool::transaction_id)),
+            )
+            .execute(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "substate_locks_remove_expired",
+                source: e,
+            })?;
+
+        Ok(num_removed)
+    }
+
+    fn substates_create(
+        &mut self,
+        substate: SubstateRecord,
+        state_version: Option<Version>,
+    ) -> Result<(), StorageError> {
+        use crate::schema::{pending_state_tree_diffs, state_transitions, substates};
 
         if substate.is_destroyed() {
             return Err(StorageError::QueryError {
@@ -1331,6 +2026,27 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
             });
         }
 
+        // The state transition records the latest state tree version, so the pending state tree diff for the block
+        // must already have been committed
+        let num_pending_diffs = pending_state_tree_diffs::table
+            .count()
+            .filter(pending_state_tree_diffs::block_id.eq(serialize_hex(substate.created_block)))
+            .filter(pending_state_tree_diffs::shard.eq(substate.created_by_shard.as_u32() as i32))
+            .first::<i64>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "substates_create",
+                source: e,
+            })?;
+        if num_pending_diffs > 0 {
+            return Err(StorageError::QueryError {
+                reason: format!(
+                    "substates_create: substate {} was inserted before the pending state tree diff for block {} \
+                     (shard {}) was committed. State tree updates MUST be applied before substates are inserted.",
+                    substate.substate_id, substate.created_block, substate.created_by_shard
+                ),
+            });
+        }
+
         let values = (
             substates::address.eq(serialize_hex(substate.to_substate_address())),
             substates::substate_id.eq(substate.substate_id.to_string()),
@@ -1364,7 +2080,10 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
         let next_seq = seq.map(|s| s + 1).unwrap_or(0);
 
         // This means that we MUST do the state tree updates before inserting substates
-        let version = self.state_tree_versions_get_latest(substate.created_by_shard)?;
+        let version = match state_version {
+            Some(version) => Some(version),
+            None => self.state_tree_versions_get_latest(substate.created_by_shard)?,
+        };
         let values = (
             state_transitions::seq.eq(next_seq),
             state_transitions::epoch.eq(substate.created_at_epoch.as_u64() as i64),
@@ -1396,6 +2115,7 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
         destroyed_block_height: NodeHeight,
         destroyed_transaction_id: &TransactionId,
         destroyed_qc_id: &QcId,
+        state_version: Option<Version>,
     ) -> Result<(), StorageError> {
         use crate::schema::{state_transitions, substates};
 
@@ -1429,7 +2149,10 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
             })?;
         let next_seq = seq.map(|s| s + 1).unwrap_or(0);
 
-        let version = self.state_tree_versions_get_latest(shard)?;
+        let version = match state_version {
+            Some(version) => Some(version),
+            None => self.state_tree_versions_get_latest(shard)?,
+        };
         let values = (
             state_transitions::seq.eq(next_seq),
             state_transitions::epoch.eq(epoch.as_u64() as i64),
@@ -1491,7 +2214,28 @@ impl<'tx, TAddr: NodeAddressable + 'tx> StateStoreWriteTransaction for SqliteSta
         shard: Shard,
         diff: VersionedStateHashTreeDiff,
     ) -> Result<(), StorageError> {
-        use crate::schema::{blocks, pending_state_tree_diffs};
+        use crate::schema::{blocks, pending_state_tree_diffs, substates};
+
+        // Substates for a block are only inserted once the block's state tree diff has been committed, so there cannot
+        // be any substates created by this block yet
+        let num_substates = substates::table
+            .count()
+            .filter(substates::created_block.eq(serialize_hex(block_id)))
+            .filter(substates::created_by_shard.eq(shard.as_u32() as i32))
+            .first::<i64>(self.connection())
+            .map_err(|e| SqliteStorageError::DieselError {
+                operation: "pending_state_tree_diffs_insert",
+                source: e,
+            })?;
+        if num_substates > 0 {
+            return Err(StorageError::QueryError {
+                reason: format!(
+                    "pending_state_tree_diffs_insert: {num_substates} substate(s) were already inserted for block \
+                     {block_id} (shard {shard}) before its state tree diff. State tree updates MUST be applied before \
+                     substates are inserted."
+                ),
+            });
+        }
 
         let insert = (
             pending_state_tree_diffs::block_id.eq(serialize_hex(block_id)),
@@ -1620,6 +2364,12 @@ impl<'a, TAddr> Deref for SqliteStateStoreWriteTransaction<'a, TAddr> {
     }
 }
 
+impl<'a, TAddr> DerefMut for SqliteStateStoreWriteTransaction<'a, TAddr> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.transaction.as_mut().unwrap()
+    }
+}
+
 impl<TAddr> Drop for SqliteStateStoreWriteTransaction<'_, TAddr> {
     fn drop(&mut self) {
         if self.transaction.is_some() {
@@ -1,17 +1,29 @@
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
+use std::collections::BTreeSet;
+
+use indexmap::IndexMap;
 use rand::{rngs::OsRng, RngCore};
 use tari_common_types::types::FixedHash;
-use tari_dan_common_types::{Epoch, NodeHeight};
+use tari_dan_common_types::{shard::Shard, Epoch, NodeHeight, NumPreshards, ShardGroup};
 use tari_dan_storage::{
-    consensus_models::{Block, Command, Decision, TransactionAtom, TransactionPoolStage, TransactionPoolStatusUpdate},
+    consensus_models::{
+        Block,
+        Command,
+        Decision,
+        QuorumCertificate,
+        TransactionAtom,
+        TransactionPoolStage,
+        TransactionPoolStatusUpdate,
+        TransactionRecord,
+    },
     StateStore,
     StateStoreReadTransaction,
     StateStoreWriteTransaction,
 };
 use tari_state_store_sqlite::SqliteStateStore;
-use tari_transaction::TransactionId;
+use tari_transaction::{Transaction, TransactionId};
 use tari_utilities::epoch_time::EpochTime;
 
 fn create_db() -> SqliteStateStore<String> {
@@ -30,6 +42,60 @@ fn create_tx_atom() -> TransactionAtom {
     }
 }
 
+fn create_tx_record(min_epoch: u64) -> TransactionRecord {
+    // The min epoch is used to make the transaction id unique
+    TransactionRecord::new(Transaction::builder().with_min_epoch(Some(Epoch(min_epoch))).build())
+}
+
+/// The parameters of a block created by [create_block]. Parameters that a test does not depend on can be left as
+/// their defaults.
+struct BlockParams {
+    height: u64,
+    epoch: Epoch,
+    shard_group: ShardGroup,
+    commands: BTreeSet<Command>,
+    total_leader_fee: u64,
+    foreign_indexes: IndexMap<Shard, u64>,
+    /// The justify QC of the block, or the justify QC of the parent block if None
+    justify: Option<QuorumCertificate>,
+    timestamp: u64,
+}
+
+impl Default for BlockParams {
+    fn default() -> Self {
+        Self {
+            height: 1,
+            epoch: Epoch(0),
+            shard_group: ShardGroup::all_shards(NumPreshards::P64),
+            commands: BTreeSet::new(),
+            total_leader_fee: 0,
+            foreign_indexes: IndexMap::new(),
+            justify: None,
+            timestamp: EpochTime::now().as_u64(),
+        }
+    }
+}
+
+fn create_block(parent: &Block, params: BlockParams) -> Block {
+    Block::new(
+        parent.network(),
+        *parent.id(),
+        params.justify.unwrap_or_else(|| parent.justify().clone()),
+        NodeHeight(params.height),
+        params.epoch,
+        params.shard_group,
+        Default::default(),
+        params.commands,
+        Default::default(),
+        params.total_leader_fee,
+        params.foreign_indexes,
+        None,
+        params.timestamp,
+        0,
+        FixedHash::zero(),
+    )
+}
+
 mod confirm_all_transitions {
     use tari_dan_common_types::{NumPreshards, ShardGroup};
 
@@ -144,3 +210,3013 @@ mod confirm_all_transitions {
         tx.rollback().unwrap();
     }
 }
+
+mod foreign_indexes {
+    use diesel::RunQueryDsl;
+
+    use super::*;
+
+    #[test]
+    fn it_decodes_foreign_indexes() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+
+        let foreign_indexes = [(Shard::from(40), 1), (Shard::from(41), 3)].into_iter().collect();
+        let block = create_block(&zero_block, BlockParams {
+            shard_group: ShardGroup::new(0, 31),
+            foreign_indexes,
+            ..Default::default()
+        });
+        block.insert(&mut tx).unwrap();
+
+        let indexes = tx.blocks_get_foreign_indexes(block.id()).unwrap();
+        assert_eq!(indexes, *block.foreign_indexes());
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_rejects_foreign_indexes_with_duplicate_shards() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+
+        let block = create_block(&zero_block, BlockParams {
+            shard_group: ShardGroup::new(0, 31),
+            foreign_indexes: [(Shard::from(40), 1)].into_iter().collect(),
+            ..Default::default()
+        });
+        block.insert(&mut tx).unwrap();
+
+        // A plain JSON decode would silently keep the last index for shard 40
+        diesel::sql_query(r#"UPDATE blocks SET foreign_indexes = '{"40":1,"40":3}'"#)
+            .execute(tx.connection())
+            .unwrap();
+        tx.blocks_get_foreign_indexes(block.id()).unwrap_err();
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod transaction_outputs {
+    use std::str::FromStr;
+
+    use tari_engine_types::substate::SubstateId;
+    use tari_transaction::VersionedSubstateId;
+
+    use super::*;
+
+    fn create_output(n: u32, version: u32) -> VersionedSubstateId {
+        VersionedSubstateId::new(SubstateId::from_str(&format!("component_{n:056x}")).unwrap(), version)
+    }
+
+    #[test]
+    fn it_finds_transactions_by_output() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let mut rec1 = create_tx_record(1);
+        rec1.resulting_outputs = vec![create_output(1, 0), create_output(2, 0)];
+        rec1.insert(&mut tx).unwrap();
+        let mut rec2 = create_tx_record(2);
+        rec2.resulting_outputs = vec![create_output(1, 1)];
+        rec2.insert(&mut tx).unwrap();
+
+        let found = tx.transactions_get_by_output(&create_output(1, 0)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id(), rec1.id());
+
+        let found = tx.transactions_get_by_output(&create_output(1, 1)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id(), rec2.id());
+
+        // Updating the record replaces its outputs
+        rec1.resulting_outputs = vec![create_output(3, 0)];
+        rec1.update(&mut tx).unwrap();
+        assert!(tx.transactions_get_by_output(&create_output(2, 0)).unwrap().is_empty());
+        let found = tx.transactions_get_by_output(&create_output(3, 0)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id(), rec1.id());
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_finds_transactions_by_any_version_of_an_output_substate() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let mut rec1 = create_tx_record(1);
+        rec1.resulting_outputs = vec![create_output(1, 0), create_output(2, 0)];
+        rec1.insert(&mut tx).unwrap();
+        let mut rec2 = create_tx_record(2);
+        rec2.resulting_outputs = vec![create_output(1, 1)];
+        rec2.insert(&mut tx).unwrap();
+
+        let found = tx
+            .transactions_get_by_output_substate(create_output(1, 0).substate_id())
+            .unwrap();
+        assert_eq!(found.iter().map(|t| *t.id()).collect::<Vec<_>>(), vec![
+            *rec1.id(),
+            *rec2.id()
+        ]);
+
+        let found = tx
+            .transactions_get_by_output_substate(create_output(2, 0).substate_id())
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id(), rec1.id());
+
+        assert!(tx
+            .transactions_get_by_output_substate(create_output(3, 0).substate_id())
+            .unwrap()
+            .is_empty());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod blocks_paginated_by_shard_group {
+    use tari_dan_common_types::{NumPreshards, ShardGroup};
+    use tari_dan_storage::Ordering;
+
+    use super::*;
+
+    fn create_block(parent: &Block, height: u64, shard_group: ShardGroup) -> Block {
+        Block::new(
+            parent.network(),
+            *parent.id(),
+            parent.justify().clone(),
+            NodeHeight(height),
+            Epoch(0),
+            shard_group,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            EpochTime::now().as_u64(),
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    #[test]
+    fn it_returns_blocks_for_the_shard_group_ordered_by_height() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        // Blocks are loaded together with their justify QC
+        zero_block.justify().insert(&mut tx).unwrap();
+
+        let shard_group = ShardGroup::new(0, 31);
+        let block1 = create_block(&zero_block, 1, shard_group);
+        block1.insert(&mut tx).unwrap();
+        let block2 = create_block(&block1, 2, shard_group);
+        block2.insert(&mut tx).unwrap();
+        let other_block = create_block(&zero_block, 1, ShardGroup::new(32, 63));
+        other_block.insert(&mut tx).unwrap();
+
+        let blocks = tx
+            .blocks_get_paginated_by_shard_group(shard_group, 10, 0, Ordering::Ascending)
+            .unwrap();
+        assert_eq!(blocks.iter().map(|b| *b.id()).collect::<Vec<_>>(), vec![
+            *block1.id(),
+            *block2.id()
+        ]);
+
+        let blocks = tx
+            .blocks_get_paginated_by_shard_group(shard_group, 1, 0, Ordering::Descending)
+            .unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].id(), block2.id());
+
+        let blocks = tx
+            .blocks_get_paginated_by_shard_group(ShardGroup::new(0, 15), 10, 0, Ordering::Ascending)
+            .unwrap();
+        assert!(blocks.is_empty());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod transaction_inputs {
+    use std::str::FromStr;
+
+    use tari_dan_storage::consensus_models::VersionedSubstateIdLockIntent;
+    use tari_engine_types::substate::SubstateId;
+    use tari_transaction::VersionedSubstateId;
+
+    use super::*;
+
+    fn create_substate_id(n: u32) -> SubstateId {
+        SubstateId::from_str(&format!("component_{n:056x}")).unwrap()
+    }
+
+    #[test]
+    fn it_matches_the_resolved_inputs() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let mut rec1 = create_tx_record(1);
+        rec1.resolved_inputs = Some(vec![
+            VersionedSubstateIdLockIntent::write(VersionedSubstateId::new(create_substate_id(1), 0)),
+            VersionedSubstateIdLockIntent::read(VersionedSubstateId::new(create_substate_id(2), 3)),
+        ]);
+        rec1.insert(&mut tx).unwrap();
+        let mut rec2 = create_tx_record(2);
+        rec2.resolved_inputs = Some(vec![VersionedSubstateIdLockIntent::read(VersionedSubstateId::new(
+            create_substate_id(2),
+            3,
+        ))]);
+        rec2.insert(&mut tx).unwrap();
+        let rec3 = create_tx_record(3);
+        rec3.insert(&mut tx).unwrap();
+
+        for rec in [&rec1, &rec2, &rec3] {
+            let inputs = tx.transaction_inputs_get_all(rec.id()).unwrap();
+            let stored = tx.transactions_get(rec.id()).unwrap();
+            assert_eq!(inputs, stored.resolved_inputs().unwrap_or_default());
+        }
+
+        let mut ids = tx.transactions_get_ids_by_input(&create_substate_id(2)).unwrap();
+        ids.sort();
+        let mut expected = vec![*rec1.id(), *rec2.id()];
+        expected.sort();
+        assert_eq!(ids, expected);
+        assert_eq!(tx.transactions_get_ids_by_input(&create_substate_id(1)).unwrap(), vec![
+            *rec1.id()
+        ]);
+
+        // Updating the record replaces its inputs
+        rec1.resolved_inputs = Some(vec![VersionedSubstateIdLockIntent::write(VersionedSubstateId::new(
+            create_substate_id(1),
+            1,
+        ))]);
+        rec1.update(&mut tx).unwrap();
+        assert_eq!(
+            tx.transaction_inputs_get_all(rec1.id()).unwrap(),
+            rec1.resolved_inputs().unwrap()
+        );
+        assert_eq!(tx.transactions_get_ids_by_input(&create_substate_id(2)).unwrap(), vec![
+            *rec2.id()
+        ]);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod leader_fees {
+    use tari_common_types::types::PublicKey;
+    use tari_dan_common_types::{NumPreshards, ShardGroup};
+    use tari_utilities::ByteArray;
+
+    use super::*;
+
+    fn create_block(parent: &Block, height: u64, epoch: Epoch, total_leader_fee: u64) -> Block {
+        Block::new(
+            parent.network(),
+            *parent.id(),
+            parent.justify().clone(),
+            NodeHeight(height),
+            epoch,
+            ShardGroup::all_shards(NumPreshards::P64),
+            PublicKey::default(),
+            Default::default(),
+            Default::default(),
+            total_leader_fee,
+            Default::default(),
+            None,
+            EpochTime::now().as_u64(),
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    #[test]
+    fn it_sums_leader_fees_for_committed_blocks_in_the_epoch() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+
+        let block1 = create_block(&zero_block, 1, Epoch(1), 10);
+        block1.insert(&mut tx).unwrap();
+        tx.blocks_set_flags(block1.id(), Some(true), None).unwrap();
+        let block2 = create_block(&block1, 2, Epoch(1), 20);
+        block2.insert(&mut tx).unwrap();
+        tx.blocks_set_flags(block2.id(), Some(true), None).unwrap();
+        // Not committed
+        let block3 = create_block(&block2, 3, Epoch(1), 40);
+        block3.insert(&mut tx).unwrap();
+        // Different epoch
+        let block4 = create_block(&block3, 4, Epoch(2), 80);
+        block4.insert(&mut tx).unwrap();
+        tx.blocks_set_flags(block4.id(), Some(true), None).unwrap();
+
+        let fees = tx.blocks_sum_leader_fees_by_proposer(Epoch(1)).unwrap();
+        assert_eq!(fees.len(), 1);
+        assert_eq!(fees[&hex::encode(PublicKey::default().as_bytes())], 30);
+
+        let fees = tx.blocks_sum_leader_fees_by_proposer(Epoch(3)).unwrap();
+        assert!(fees.is_empty());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod transactions_by_decision {
+    use tari_dan_storage::consensus_models::AbortReason;
+
+    use super::*;
+
+    #[test]
+    fn it_filters_by_final_decision_and_epoch() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let mut committed1 = create_tx_record(1);
+        committed1.final_decision = Some(Decision::Commit);
+        committed1.insert(&mut tx).unwrap();
+        let mut committed2 = create_tx_record(5);
+        committed2.final_decision = Some(Decision::Commit);
+        committed2.insert(&mut tx).unwrap();
+        let mut aborted = create_tx_record(2);
+        aborted.set_abort(AbortReason::ExecutionFailure, "test");
+        aborted.insert(&mut tx).unwrap();
+        // Not finalized
+        create_tx_record(3).insert(&mut tx).unwrap();
+
+        let ids = |recs: Vec<TransactionRecord>| recs.iter().map(|r| *r.id()).collect::<Vec<_>>();
+
+        let recs = tx.transactions_get_by_decision(Decision::Commit, None, 10, 0).unwrap();
+        assert_eq!(ids(recs), vec![*committed1.id(), *committed2.id()]);
+
+        let recs = tx.transactions_get_by_decision(Decision::Abort, None, 10, 0).unwrap();
+        assert_eq!(ids(recs), vec![*aborted.id()]);
+
+        // committed2 has a min epoch of 5
+        let recs = tx
+            .transactions_get_by_decision(Decision::Commit, Some(Epoch(2)), 10, 0)
+            .unwrap();
+        assert_eq!(ids(recs), vec![*committed1.id()]);
+
+        let recs = tx.transactions_get_by_decision(Decision::Commit, None, 1, 1).unwrap();
+        assert_eq!(ids(recs), vec![*committed2.id()]);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod transactions_get_after {
+    use tari_dan_common_types::optional::IsNotFoundError;
+    use tari_dan_storage::Ordering;
+
+    use super::*;
+
+    #[test]
+    fn it_pages_by_the_last_seen_transaction() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let recs = (0..5).map(create_tx_record).collect::<Vec<_>>();
+        for rec in &recs {
+            rec.insert(&mut tx).unwrap();
+        }
+        let ids = |recs: &[TransactionRecord]| recs.iter().map(|r| *r.id()).collect::<Vec<_>>();
+
+        let page1 = tx.transactions_get_after(None, 2, Ordering::Ascending).unwrap();
+        assert_eq!(ids(&page1), ids(&recs[0..2]));
+        let page2 = tx
+            .transactions_get_after(Some(page1[1].id()), 2, Ordering::Ascending)
+            .unwrap();
+        assert_eq!(ids(&page2), ids(&recs[2..4]));
+
+        // A transaction inserted between pages does not shift the next page
+        let late = create_tx_record(5);
+        late.insert(&mut tx).unwrap();
+        let page3 = tx
+            .transactions_get_after(Some(page2[1].id()), 2, Ordering::Ascending)
+            .unwrap();
+        assert_eq!(ids(&page3), vec![*recs[4].id(), *late.id()]);
+
+        let page = tx
+            .transactions_get_after(Some(recs[2].id()), 10, Ordering::Descending)
+            .unwrap();
+        assert_eq!(ids(&page), vec![*recs[1].id(), *recs[0].id()]);
+
+        let err = tx
+            .transactions_get_after(Some(&create_tx_atom().id), 2, Ordering::Ascending)
+            .unwrap_err();
+        assert!(err.is_not_found_error());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod parked_blocks {
+    use tari_dan_common_types::{NumPreshards, ShardGroup};
+
+    use super::*;
+
+    fn create_block(parent: &Block, height: u64) -> Block {
+        Block::new(
+            parent.network(),
+            *parent.id(),
+            parent.justify().clone(),
+            NodeHeight(height),
+            Epoch(0),
+            ShardGroup::all_shards(NumPreshards::P64),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            EpochTime::now().as_u64(),
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    #[test]
+    fn it_returns_parked_blocks_waiting_on_a_transaction() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+
+        let tx_id1 = create_tx_atom().id;
+        let tx_id2 = create_tx_atom().id;
+        let tx_id3 = create_tx_atom().id;
+
+        let block1 = create_block(&zero_block, 1);
+        tx.missing_transactions_insert(&block1, [&tx_id1, &tx_id2], [&tx_id1])
+            .unwrap();
+        let block2 = create_block(&zero_block, 2);
+        tx.missing_transactions_insert(&block2, [&tx_id1], []).unwrap();
+
+        let mut block_ids = tx.parked_blocks_waiting_on(&tx_id1).unwrap();
+        block_ids.sort();
+        let mut expected = vec![*block1.id(), *block2.id()];
+        expected.sort();
+        assert_eq!(block_ids, expected);
+
+        assert_eq!(tx.parked_blocks_waiting_on(&tx_id2).unwrap(), vec![*block1.id()]);
+        assert!(tx.parked_blocks_waiting_on(&tx_id3).unwrap().is_empty());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod abort_reasons {
+    use std::time::Duration;
+
+    use tari_dan_common_types::{NumPreshards, ShardGroup};
+    use tari_dan_storage::consensus_models::{AbortReason, BlockId, TransactionExecution};
+    use tari_engine_types::{
+        commit_result::{ExecuteResult, FinalizeResult, RejectReason, TransactionResult},
+        fees::FeeReceipt,
+        substate::SubstateDiff,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_counts_aborted_transactions_by_reason() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let mut rec = create_tx_record(1);
+        rec.set_abort(AbortReason::LockConflict, "substate is write-locked");
+        rec.insert(&mut tx).unwrap();
+        let mut rec = create_tx_record(2);
+        rec.set_abort(AbortReason::LockConflict, "substate is read-locked");
+        rec.insert(&mut tx).unwrap();
+        let mut rec = create_tx_record(3);
+        rec.set_abort(AbortReason::NoInvolvedShards, "no shards");
+        rec.insert(&mut tx).unwrap();
+        // Legacy abort details recorded without a reason
+        let mut rec = create_tx_record(4);
+        rec.final_decision = Some(Decision::Abort);
+        rec.abort_details = Some("Abort decision via sync".to_string());
+        rec.insert(&mut tx).unwrap();
+        let mut rec = create_tx_record(5);
+        rec.final_decision = Some(Decision::Abort);
+        rec.insert(&mut tx).unwrap();
+        // Committed transactions are not counted
+        let mut rec = create_tx_record(6);
+        rec.final_decision = Some(Decision::Commit);
+        rec.insert(&mut tx).unwrap();
+
+        let counts = tx.transactions_count_by_abort_reason(None).unwrap();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&AbortReason::LockConflict], 2);
+        assert_eq!(counts[&AbortReason::NoInvolvedShards], 1);
+        assert_eq!(counts[&AbortReason::Unknown], 2);
+
+        // None of the transactions were finalized in a block
+        let counts = tx.transactions_count_by_abort_reason(Some(Epoch(1))).unwrap();
+        assert!(counts.is_empty());
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_counts_aborts_in_the_epoch_of_the_finalizing_block() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        let block1 = create_block(&zero_block, 1, Epoch(1));
+        block1.insert(&mut tx).unwrap();
+        let block2 = create_block(&block1, 2, Epoch(2));
+        block2.insert(&mut tx).unwrap();
+
+        // Rejected by the executor and finalized in epoch 1
+        let rec1 = create_tx_record(5);
+        rec1.insert(&mut tx).unwrap();
+        tx.transaction_executions_insert_or_ignore(&create_execution(
+            *block1.id(),
+            *rec1.id(),
+            TransactionResult::Reject(RejectReason::ExecutionFailure("out of funds".to_string())),
+        ))
+        .unwrap();
+        // Aborted due to a lock conflict in block 1 and finalized in epoch 2
+        let rec2 = create_tx_record(6);
+        rec2.insert(&mut tx).unwrap();
+        tx.transaction_executions_insert_or_ignore(&create_execution(
+            *block2.id(),
+            *rec2.id(),
+            TransactionResult::Accept(SubstateDiff::new()),
+        ))
+        .unwrap();
+        TransactionRecord::add_pending_abort(
+            &mut tx,
+            block1.id(),
+            block1.height(),
+            rec2.id(),
+            AbortReason::LockConflict,
+            "substate is write-locked",
+        )
+        .unwrap();
+        TransactionRecord::commit_pending_aborts(&mut tx, block1.id(), block1.height()).unwrap();
+
+        TransactionRecord::finalize_all(&mut tx, *block1.id(), &[create_abort_atom(*rec1.id())]).unwrap();
+        TransactionRecord::finalize_all(&mut tx, *block2.id(), &[create_abort_atom(*rec2.id())]).unwrap();
+
+        let stored = TransactionRecord::get(&*tx, rec1.id()).unwrap();
+        assert_eq!(stored.abort_reason(), Some(AbortReason::ExecutionFailure));
+        assert_eq!(stored.abort_details().unwrap(), "Execution failure: out of funds");
+
+        let counts = tx.transactions_count_by_abort_reason(Some(Epoch(1))).unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[&AbortReason::ExecutionFailure], 1);
+
+        let counts = tx.transactions_count_by_abort_reason(Some(Epoch(2))).unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[&AbortReason::LockConflict], 1);
+
+        let counts = tx.transactions_count_by_abort_reason(None).unwrap();
+        assert_eq!(counts.len(), 2);
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_applies_pending_aborts_only_when_the_block_commits() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        let block1 = create_block(&zero_block, 1, Epoch(1));
+        // A competing block at the same height that is never committed
+        let fork_block = create_block(&zero_block, 1, Epoch(2));
+
+        let rec1 = create_tx_record(1);
+        rec1.insert(&mut tx).unwrap();
+        let rec2 = create_tx_record(2);
+        rec2.insert(&mut tx).unwrap();
+
+        TransactionRecord::add_pending_abort(
+            &mut tx,
+            block1.id(),
+            block1.height(),
+            rec1.id(),
+            AbortReason::LockConflict,
+            "substate is write-locked",
+        )
+        .unwrap();
+        TransactionRecord::add_pending_abort(
+            &mut tx,
+            fork_block.id(),
+            fork_block.height(),
+            rec2.id(),
+            AbortReason::LockConflict,
+            "substate is read-locked",
+        )
+        .unwrap();
+
+        // Nothing is recorded on the transactions before the block commits
+        let stored = TransactionRecord::get(&*tx, rec1.id()).unwrap();
+        assert!(stored.abort_reason.is_none());
+        assert!(stored.abort_details().is_none());
+
+        TransactionRecord::commit_pending_aborts(&mut tx, block1.id(), block1.height()).unwrap();
+
+        let stored = TransactionRecord::get(&*tx, rec1.id()).unwrap();
+        assert_eq!(stored.abort_reason, Some(AbortReason::LockConflict));
+        assert_eq!(stored.abort_details().unwrap(), "substate is write-locked");
+
+        // The abort in the uncommitted fork is discarded and cannot be applied later
+        TransactionRecord::commit_pending_aborts(&mut tx, fork_block.id(), fork_block.height()).unwrap();
+        let stored = TransactionRecord::get(&*tx, rec2.id()).unwrap();
+        assert!(stored.abort_reason.is_none());
+        assert!(stored.abort_details().is_none());
+
+        tx.rollback().unwrap();
+    }
+
+    fn create_block(parent: &Block, height: u64, epoch: Epoch) -> Block {
+        Block::new(
+            parent.network(),
+            *parent.id(),
+            parent.justify().clone(),
+            NodeHeight(height),
+            epoch,
+            ShardGroup::all_shards(NumPreshards::P64),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            EpochTime::now().as_u64(),
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    fn create_execution(
+        block_id: BlockId,
+        transaction_id: TransactionId,
+        result: TransactionResult,
+    ) -> TransactionExecution {
+        TransactionExecution::new(
+            block_id,
+            transaction_id,
+            ExecuteResult {
+                finalize: FinalizeResult::new(
+                    transaction_id.into_array().into(),
+                    vec![],
+                    vec![],
+                    result,
+                    FeeReceipt::default(),
+                ),
+            },
+            vec![],
+            vec![],
+            Duration::from_millis(1),
+        )
+    }
+
+    fn create_abort_atom(id: TransactionId) -> TransactionAtom {
+        TransactionAtom {
+            id,
+            decision: Decision::Abort,
+            evidence: Default::default(),
+            transaction_fee: 0,
+            leader_fee: None,
+        }
+    }
+}
+
+mod reexecute {
+    use std::{str::FromStr, time::Duration};
+
+    use tari_dan_storage::{
+        consensus_models::{AbortReason, ExecutedTransaction},
+        StorageError,
+    };
+    use tari_engine_types::{
+        commit_result::{ExecuteResult, FinalizeResult, RejectReason, TransactionResult},
+        fees::FeeReceipt,
+        substate::{SubstateDiff, SubstateId},
+    };
+    use tari_transaction::VersionedSubstateId;
+
+    use super::*;
+
+    fn create_output(n: u32) -> VersionedSubstateId {
+        VersionedSubstateId::new(SubstateId::from_str(&format!("component_{n:056x}")).unwrap(), 0)
+    }
+
+    fn execute_rejected(
+        transaction: Transaction,
+        outputs: Vec<VersionedSubstateId>,
+    ) -> Result<ExecutedTransaction, StorageError> {
+        let result = ExecuteResult {
+            finalize: FinalizeResult::new_rejected(
+                transaction.id().into_array().into(),
+                RejectReason::ExecutionFailure("re-executed".to_string()),
+            ),
+        };
+        Ok(ExecutedTransaction::new(
+            transaction,
+            result,
+            vec![],
+            outputs,
+            Duration::from_millis(10),
+        ))
+    }
+
+    #[test]
+    fn it_updates_the_record_and_returns_the_diff() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let mut rec = create_tx_record(1);
+        rec.resolved_inputs = Some(vec![]);
+        rec.resulting_outputs = vec![create_output(1), create_output(2)];
+        rec.insert(&mut tx).unwrap();
+
+        let (updated, diff) = TransactionRecord::reexecute(&mut tx, rec.id(), false, |transaction, _inputs| {
+            execute_rejected(transaction, vec![create_output(2), create_output(3)])
+        })
+        .unwrap();
+        assert_eq!(diff.previous_decision, None);
+        assert_eq!(diff.new_decision, Decision::Abort);
+        assert_eq!(diff.outputs_added, vec![create_output(3)]);
+        assert_eq!(diff.outputs_removed, vec![create_output(1)]);
+        assert_eq!(updated.execution_time(), Some(Duration::from_millis(10)));
+
+        let stored = TransactionRecord::get(&*tx, rec.id()).unwrap();
+        assert_eq!(stored.execution_decision(), Some(Decision::Abort));
+        assert_eq!(stored.resulting_outputs(), [create_output(2), create_output(3)]);
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_refuses_finalized_transactions_unless_forced() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let mut rec = create_tx_record(1);
+        rec.resolved_inputs = Some(vec![]);
+        rec.final_decision = Some(Decision::Commit);
+        rec.insert(&mut tx).unwrap();
+
+        TransactionRecord::reexecute(&mut tx, rec.id(), false, |transaction, _inputs| {
+            execute_rejected(transaction, vec![])
+        })
+        .unwrap_err();
+
+        let (_, diff) = TransactionRecord::reexecute(&mut tx, rec.id(), true, |transaction, _inputs| {
+            execute_rejected(transaction, vec![])
+        })
+        .unwrap();
+        assert_eq!(diff.new_decision, Decision::Abort);
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_replaces_the_final_decision_of_a_forced_commit_to_abort() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let mut rec = create_tx_record(1);
+        rec.resolved_inputs = Some(vec![]);
+        rec.final_decision = Some(Decision::Commit);
+        rec.insert(&mut tx).unwrap();
+
+        let (updated, _) = TransactionRecord::reexecute(&mut tx, rec.id(), true, |transaction, _inputs| {
+            execute_rejected(transaction, vec![])
+        })
+        .unwrap();
+        assert_eq!(updated.final_decision(), Some(Decision::Abort));
+
+        let stored = TransactionRecord::get(&*tx, rec.id()).unwrap();
+        assert_eq!(stored.final_decision(), Some(Decision::Abort));
+        assert_eq!(stored.abort_reason(), Some(AbortReason::ExecutionFailure));
+        assert!(stored.abort_details().unwrap().contains("re-executed"));
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_clears_the_abort_details_of_a_forced_abort_to_commit() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let mut rec = create_tx_record(1);
+        rec.resolved_inputs = Some(vec![]);
+        rec.set_abort(AbortReason::LockConflict, "substate is write-locked");
+        rec.insert(&mut tx).unwrap();
+
+        TransactionRecord::reexecute(&mut tx, rec.id(), true, |transaction, _inputs| {
+            let result = ExecuteResult {
+                finalize: FinalizeResult::new(
+                    transaction.id().into_array().into(),
+                    vec![],
+                    vec![],
+                    TransactionResult::Accept(SubstateDiff::new()),
+                    FeeReceipt::default(),
+                ),
+            };
+            Ok::<_, StorageError>(ExecutedTransaction::new(
+                transaction,
+                result,
+                vec![],
+                vec![],
+                Duration::from_millis(10),
+            ))
+        })
+        .unwrap();
+
+        let stored = TransactionRecord::get(&*tx, rec.id()).unwrap();
+        assert_eq!(stored.final_decision(), Some(Decision::Commit));
+        assert_eq!(stored.abort_details(), None);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod block_diffs {
+    use std::str::FromStr;
+
+    use tari_dan_common_types::shard::Shard;
+    use tari_dan_storage::consensus_models::{BlockDiff, BlockId, SubstateChange};
+    use tari_engine_types::{
+        fee_claim::FeeClaim,
+        substate::{Substate, SubstateId},
+    };
+    use tari_transaction::VersionedSubstateId;
+
+    use super::*;
+
+    fn create_changes() -> Vec<SubstateChange> {
+        let id = SubstateId::from_str(&format!("component_{:056x}", 1)).unwrap();
+        let substate = Substate::new(1, FeeClaim {
+            epoch: 1,
+            validator_public_key: Default::default(),
+            amount: Default::default(),
+        });
+        vec![
+            SubstateChange::Down {
+                id: VersionedSubstateId::new(id.clone(), 0),
+                shard: Shard::from(3),
+                transaction_id: create_tx_atom().id,
+            },
+            SubstateChange::Up {
+                id: VersionedSubstateId::new(id, 1),
+                shard: Shard::from(3),
+                transaction_id: create_tx_atom().id,
+                substate,
+            },
+        ]
+    }
+
+    fn assert_change_eq(a: &SubstateChange, b: &SubstateChange) {
+        assert_eq!(a.as_change_string().unwrap(), b.as_change_string().unwrap());
+        assert_eq!(a.versioned_substate_id(), b.versioned_substate_id());
+        assert_eq!(a.shard(), b.shard());
+        assert_eq!(a.transaction_id(), b.transaction_id());
+        assert_eq!(
+            a.substate().map(|s| s.to_value_hash()),
+            b.substate().map(|s| s.to_value_hash())
+        );
+        assert_eq!(a.substate().map(|s| s.version()), b.substate().map(|s| s.version()));
+    }
+
+    #[test]
+    fn it_round_trips_change_strings() {
+        let changes = create_changes();
+        assert!(changes.iter().any(|c| c.is_up()));
+        assert!(changes.iter().any(|c| c.is_down()));
+        for change in changes {
+            let converted = SubstateChange::from_change_string(&change.as_change_string().unwrap()).unwrap();
+            assert_change_eq(&converted, &change);
+        }
+
+        SubstateChange::from_change_string("Up").unwrap_err();
+        SubstateChange::from_change_string("Sideways").unwrap_err();
+    }
+
+    #[test]
+    fn it_round_trips_block_diffs() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let block_id = BlockId::zero();
+        let changes = create_changes();
+        tx.block_diffs_insert(&BlockDiff::new(block_id, changes.clone()))
+            .unwrap();
+
+        let diff = tx.block_diffs_get(&block_id).unwrap();
+        assert_eq!(diff.changes.len(), changes.len());
+        for (a, b) in diff.changes.iter().zip(&changes) {
+            assert_change_eq(a, b);
+        }
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod transaction_pool_history {
+    use std::time::Duration;
+
+    use diesel::{RunQueryDsl, SqliteConnection};
+
+    use super::*;
+
+    fn insert_history(conn: &mut SqliteConnection, change_time: &str) {
+        diesel::sql_query(format!(
+            "INSERT INTO transaction_pool_history (id, transaction_id, original_decision, stage, new_stage, is_ready, \
+             new_is_ready, updated_at, created_at, change_time) VALUES (1, '00', 'Commit', 'New', 'Prepared', false, \
+             true, '2024-01-01 00:00:00', '2024-01-01 00:00:00', {change_time})"
+        ))
+        .execute(conn)
+        .unwrap();
+    }
+
+    #[test]
+    fn it_prunes_only_old_history_rows() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let atom = create_tx_atom();
+        tx.transaction_pool_insert_new(atom.id, atom.decision).unwrap();
+
+        insert_history(tx.connection(), "'2000-01-01 00:00:00.000'");
+        insert_history(tx.connection(), "STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW', '-2 days')");
+        insert_history(tx.connection(), "STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW', '-30 minutes')");
+        insert_history(tx.connection(), "STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW')");
+
+        let num_pruned = tx.transaction_pool_history_prune(Duration::from_secs(60 * 60)).unwrap();
+        assert_eq!(num_pruned, 2);
+        let num_pruned = tx.transaction_pool_history_prune(Duration::from_secs(60 * 60)).unwrap();
+        assert_eq!(num_pruned, 0);
+        let num_pruned = tx.transaction_pool_history_prune(Duration::from_secs(60)).unwrap();
+        assert_eq!(num_pruned, 1);
+
+        // The live pool is untouched
+        assert_eq!(tx.transaction_pool_count(None, None, None).unwrap(), 1);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod streaming {
+    use std::str::FromStr;
+
+    use tari_dan_common_types::shard::Shard;
+    use tari_dan_storage::consensus_models::{BlockId, QcId, StateTransitionId, SubstateRecord};
+    use tari_engine_types::{fee_claim::FeeClaim, substate::SubstateId};
+
+    use super::*;
+
+    pub(super) fn create_substate(n: u32, epoch: u64) -> SubstateRecord {
+        SubstateRecord::new(
+            SubstateId::from_str(&format!("component_{n:056x}")).unwrap(),
+            0,
+            FeeClaim {
+                epoch,
+                validator_public_key: Default::default(),
+                amount: Default::default(),
+            }
+            .into(),
+            Shard::from(0),
+            Epoch(epoch),
+            NodeHeight(1),
+            BlockId::zero(),
+            create_tx_atom().id,
+            QcId::zero(),
+        )
+    }
+
+    #[test]
+    fn it_streams_substates_and_state_transitions() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let substates = (0..10)
+            .map(|n| create_substate(n, 1 + u64::from(n) / 5))
+            .collect::<Vec<_>>();
+        for substate in &substates {
+            tx.substates_create(substate.clone(), None).unwrap();
+        }
+
+        let streamed = tx
+            .substates_stream_after(&substates[2].to_substate_address())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(streamed.len(), 7);
+        assert_eq!(streamed[0].to_substate_address(), substates[3].to_substate_address());
+        assert_eq!(streamed[6].to_substate_address(), substates[9].to_substate_address());
+
+        // Only epoch 1 transitions (seq 0 to 4) are before the end epoch
+        let transitions = tx
+            .state_transitions_stream_after(StateTransitionId::new(Epoch(1), Shard::from(0), 1), Epoch(2))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let collected = tx
+            .state_transitions_get_n_after(100, StateTransitionId::new(Epoch(1), Shard::from(0), 1), Epoch(2))
+            .unwrap();
+        assert_eq!(transitions.len(), 4);
+        assert_eq!(transitions.len(), collected.len());
+        for (streamed, collected) in transitions.iter().zip(&collected) {
+            assert_eq!(streamed.id, collected.id);
+        }
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod substates_at_version {
+    use tari_dan_common_types::shard::Shard;
+    use tari_dan_storage::consensus_models::QcId;
+
+    use super::{streaming::create_substate, *};
+
+    #[test]
+    fn it_gets_live_and_destroyed_versions_of_a_substate() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let v0 = create_substate(1, 1);
+        tx.substates_create(v0.clone(), None).unwrap();
+        tx.substates_down(
+            v0.to_versioned_substate_id(),
+            Shard::from(0),
+            Epoch(1),
+            NodeHeight(2),
+            &create_tx_atom().id,
+            &QcId::zero(),
+            None,
+        )
+        .unwrap();
+        let mut v1 = create_substate(1, 1);
+        v1.version = 1;
+        tx.substates_create(v1.clone(), None).unwrap();
+
+        let found = tx.substates_get_at_version(&v0.substate_id, 0).unwrap().unwrap();
+        assert_eq!(found.version, 0);
+        assert!(found.is_destroyed());
+
+        let found = tx.substates_get_at_version(&v0.substate_id, 1).unwrap().unwrap();
+        assert_eq!(found.version, 1);
+        assert!(!found.is_destroyed());
+
+        assert!(tx.substates_get_at_version(&v0.substate_id, 2).unwrap().is_none());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod state_transition_gaps {
+    use diesel::{sql_query, Connection, RunQueryDsl, SqliteConnection};
+    use tari_dan_common_types::shard::Shard;
+
+    use super::{streaming::create_substate, *};
+
+    #[test]
+    fn it_finds_missing_sequence_numbers() {
+        // A file database is used so that transitions can be removed through a second connection
+        let path = std::env::temp_dir().join(format!("state_transition_gaps_{}.sqlite", OsRng.next_u64()));
+        let url = path.to_str().unwrap();
+        let db = SqliteStateStore::<String>::connect(url).unwrap();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+        for n in 0..8 {
+            tx.substates_create(create_substate(n, 1), None).unwrap();
+        }
+        assert!(tx.state_transitions_find_gaps(Shard::from(0)).unwrap().is_empty());
+        tx.commit().unwrap();
+
+        let mut conn = SqliteConnection::establish(url).unwrap();
+        sql_query("DELETE FROM state_transitions WHERE seq IN (0, 3, 4, 6)")
+            .execute(&mut conn)
+            .unwrap();
+
+        let tx = db.create_read_tx().unwrap();
+        let gaps = tx.state_transitions_find_gaps(Shard::from(0)).unwrap();
+        assert_eq!(gaps, vec![(0, 0), (3, 4), (6, 6)]);
+        assert!(tx.state_transitions_find_gaps(Shard::from(1)).unwrap().is_empty());
+        drop(tx);
+
+        drop(conn);
+        drop(db);
+        let _ignore = std::fs::remove_file(path);
+    }
+}
+
+mod chunked_inserts {
+    use std::{collections::HashSet, str::FromStr};
+
+    use tari_dan_common_types::shard::Shard;
+    use tari_dan_storage::consensus_models::{BlockDiff, BlockId, LockedSubstate, SubstateChange, SubstateLockFlag};
+    use tari_engine_types::substate::SubstateId;
+    use tari_state_store_sqlite::{SqliteStoreConfig, SQLITE_MAX_VARIABLE_NUMBER};
+    use tari_transaction::VersionedSubstateId;
+
+    use super::*;
+
+    // Large enough to exceed the SQLite variable limit in a single statement
+    const NUM_ROWS: u32 = 10_000;
+
+    fn create_substate_id(n: u32) -> SubstateId {
+        SubstateId::from_str(&format!("component_{n:056x}")).unwrap()
+    }
+
+    #[test]
+    fn it_inserts_large_block_diffs() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let transaction_id = create_tx_atom().id;
+        let changes = (0..NUM_ROWS)
+            .map(|n| SubstateChange::Down {
+                id: VersionedSubstateId::new(create_substate_id(n), 0),
+                shard: Shard::from(0),
+                transaction_id,
+            })
+            .collect();
+        tx.block_diffs_insert(&BlockDiff::new(BlockId::zero(), changes))
+            .unwrap();
+
+        let diff = tx.block_diffs_get(&BlockId::zero()).unwrap();
+        assert_eq!(diff.len(), NUM_ROWS as usize);
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_chunks_inserts_by_the_configured_variable_limit() {
+        let config = SqliteStoreConfig {
+            max_sql_variables: 20,
+            ..Default::default()
+        };
+        let db = SqliteStateStore::<String>::connect_with_config(":memory:", &config).unwrap();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        // Each block diff row binds 7 variables, so only 2 rows fit in each statement and these rows need 13 chunks
+        const NUM_CHUNKED_ROWS: u32 = 25;
+        let transaction_id = create_tx_atom().id;
+        let changes = (0..NUM_CHUNKED_ROWS)
+            .map(|n| SubstateChange::Down {
+                id: VersionedSubstateId::new(create_substate_id(n), 0),
+                shard: Shard::from(0),
+                transaction_id,
+            })
+            .collect();
+        tx.block_diffs_insert(&BlockDiff::new(BlockId::zero(), changes))
+            .unwrap();
+
+        let diff = tx.block_diffs_get(&BlockId::zero()).unwrap();
+        assert_eq!(diff.len(), NUM_CHUNKED_ROWS as usize);
+        let stored = diff
+            .changes
+            .iter()
+            .map(|ch| ch.versioned_substate_id().substate_id().clone())
+            .collect::<HashSet<_>>();
+        for n in 0..NUM_CHUNKED_ROWS {
+            assert!(stored.contains(&create_substate_id(n)), "row {n} was not stored");
+        }
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_fails_if_the_configured_variable_limit_exceeds_the_sqlite_limit() {
+        let config = SqliteStoreConfig {
+            max_sql_variables: SQLITE_MAX_VARIABLE_NUMBER * 10,
+            ..Default::default()
+        };
+        let db = SqliteStateStore::<String>::connect_with_config(":memory:", &config).unwrap();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let transaction_id = create_tx_atom().id;
+        let changes = (0..NUM_ROWS)
+            .map(|n| SubstateChange::Down {
+                id: VersionedSubstateId::new(create_substate_id(n), 0),
+                shard: Shard::from(0),
+                transaction_id,
+            })
+            .collect();
+        tx.block_diffs_insert(&BlockDiff::new(BlockId::zero(), changes))
+            .unwrap_err();
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_inserts_large_numbers_of_substate_locks() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let transaction_id = create_tx_atom().id;
+        let locks = (0..NUM_ROWS / 2).map(|n| {
+            (create_substate_id(n), vec![
+                LockedSubstate::new(transaction_id, 0, SubstateLockFlag::Read, false),
+                LockedSubstate::new(transaction_id, 0, SubstateLockFlag::Read, true),
+            ])
+        });
+        tx.substate_locks_insert_all(BlockId::zero(), locks).unwrap();
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_inserts_new_pool_transactions_in_a_batch() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let atoms = (0..300).map(|_| create_tx_atom()).collect::<Vec<_>>();
+        // Already in the pool, so the batch leaves it unchanged
+        tx.transaction_pool_insert_new(atoms[0].id, Decision::Abort).unwrap();
+
+        tx.transaction_pool_insert_new_batch(atoms.iter().map(|atom| (atom.id, atom.decision)))
+            .unwrap();
+
+        assert_eq!(tx.transaction_pool_count(None, None, None).unwrap(), 300);
+        let records = tx.transaction_pool_get_all().unwrap();
+        let existing = records.iter().find(|r| *r.transaction_id() == atoms[0].id).unwrap();
+        assert!(existing.original_decision().is_abort());
+        assert!(records.iter().all(|r| r.committed_stage().is_new() && r.is_ready()));
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod quorum_certificates {
+    use tari_dan_common_types::ShardGroup;
+    use tari_dan_storage::consensus_models::{BlockId, QuorumCertificate, QuorumDecision, ValidatorSignature};
+
+    use super::*;
+
+    #[test]
+    fn it_gets_the_signature_count() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let genesis = QuorumCertificate::genesis(Epoch(0), ShardGroup::new(0, 63));
+        genesis.insert(&mut tx).unwrap();
+        let qc = QuorumCertificate::new(
+            BlockId::zero(),
+            NodeHeight(1),
+            Epoch(1),
+            ShardGroup::new(0, 63),
+            (0..3)
+                .map(|_| ValidatorSignature::new(Default::default(), Default::default()))
+                .collect(),
+            vec![],
+            QuorumDecision::Accept,
+        );
+        qc.insert(&mut tx).unwrap();
+
+        assert_eq!(tx.quorum_certificates_get_signature_count(genesis.id()).unwrap(), 0);
+        assert_eq!(tx.quorum_certificates_get_signature_count(qc.id()).unwrap(), 3);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod pool_consistency {
+    use super::*;
+
+    #[test]
+    fn it_finds_pool_entries_without_a_transaction() {
+        let db = create_db();
+        // Need FK=off to be able to create an orphaned pool entry
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let rec = create_tx_record(1);
+        rec.insert(&mut tx).unwrap();
+        tx.transaction_pool_insert_new(*rec.id(), Decision::Commit).unwrap();
+        assert!(tx.check_pool_transaction_consistency().unwrap().is_empty());
+
+        let orphan = create_tx_atom();
+        tx.transaction_pool_insert_new(orphan.id, orphan.decision).unwrap();
+
+        let orphaned = tx.check_pool_transaction_consistency().unwrap();
+        assert_eq!(orphaned, vec![orphan.id]);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod transactions_purge {
+    use std::{str::FromStr, time::Duration};
+
+    use diesel::{dsl::sql, sql_types::BigInt, RunQueryDsl, SqliteConnection};
+    use tari_dan_storage::consensus_models::{BlockId, TransactionExecution, VersionedSubstateIdLockIntent};
+    use tari_engine_types::{
+        commit_result::{ExecuteResult, FinalizeResult, RejectReason},
+        substate::SubstateId,
+    };
+    use tari_transaction::VersionedSubstateId;
+
+    use super::*;
+
+    fn count_rows(conn: &mut SqliteConnection, table: &str, transaction_id: &TransactionId) -> i64 {
+        diesel::select(sql::<BigInt>(&format!(
+            "(SELECT COUNT(*) FROM {table} WHERE transaction_id = '{transaction_id}')"
+        )))
+        .get_result(conn)
+        .unwrap()
+    }
+
+    const TABLES: &[&str] = &[
+        "transactions",
+        "transaction_pool",
+        "transaction_pool_state_updates",
+        "transaction_executions",
+        "transaction_inputs",
+        "transaction_outputs",
+    ];
+
+    #[test]
+    fn it_removes_all_associated_rows() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let id = VersionedSubstateId::new(SubstateId::from_str(&format!("component_{:056x}", 1)).unwrap(), 0);
+        let mut rec = create_tx_record(1);
+        rec.resolved_inputs = Some(vec![VersionedSubstateIdLockIntent::write(id.clone())]);
+        rec.resulting_outputs = vec![id.to_next_version()];
+        rec.insert(&mut tx).unwrap();
+        // Another transaction that must not be affected
+        let other = create_tx_record(2);
+        other.insert(&mut tx).unwrap();
+
+        tx.transaction_pool_insert_new(*rec.id(), Decision::Commit).unwrap();
+        tx.transaction_pool_add_pending_update(&TransactionPoolStatusUpdate {
+            block_id: BlockId::zero(),
+            block_height: NodeHeight(1),
+            transaction_id: *rec.id(),
+            stage: TransactionPoolStage::Prepared,
+            evidence: Default::default(),
+            is_ready: false,
+            local_decision: Decision::Commit,
+        })
+        .unwrap();
+        tx.transaction_executions_insert_or_ignore(&TransactionExecution::new(
+            BlockId::zero(),
+            *rec.id(),
+            ExecuteResult {
+                finalize: FinalizeResult::new_rejected(
+                    rec.id().into_array().into(),
+                    RejectReason::ExecutionFailure("test".to_string()),
+                ),
+            },
+            rec.resolved_inputs.clone().unwrap(),
+            rec.resulting_outputs.clone(),
+            Duration::from_millis(1),
+        ))
+        .unwrap();
+
+        for table in TABLES {
+            assert_eq!(count_rows(tx.connection(), table, rec.id()), 1, "{table}");
+        }
+
+        tx.transactions_purge(rec.id()).unwrap();
+
+        for table in TABLES {
+            assert_eq!(count_rows(tx.connection(), table, rec.id()), 0, "{table}");
+        }
+        assert!(TransactionRecord::exists(&*tx, other.id()).unwrap());
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_removes_nothing_if_the_purge_fails() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        // A pool entry without a transaction record, so the purge fails after deleting the pool entry
+        let atom = create_tx_atom();
+        tx.transaction_pool_insert_new(atom.id, atom.decision).unwrap();
+
+        tx.transactions_purge(&atom.id).unwrap_err();
+        assert_eq!(count_rows(tx.connection(), "transaction_pool", &atom.id), 1);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod transaction_executions_latest {
+    use std::time::Duration;
+
+    use tari_dan_common_types::{NumPreshards, ShardGroup};
+    use tari_dan_storage::consensus_models::{BlockId, TransactionExecution};
+    use tari_engine_types::commit_result::{ExecuteResult, FinalizeResult, RejectReason};
+
+    use super::*;
+
+    fn create_block(parent: &Block, height: u64) -> Block {
+        Block::new(
+            parent.network(),
+            *parent.id(),
+            parent.justify().clone(),
+            NodeHeight(height),
+            Epoch(0),
+            ShardGroup::new(0, 63),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            EpochTime::now().as_u64(),
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    fn create_execution(block_id: BlockId, transaction_id: TransactionId) -> TransactionExecution {
+        TransactionExecution::new(
+            block_id,
+            transaction_id,
+            ExecuteResult {
+                finalize: FinalizeResult::new_rejected(
+                    transaction_id.into_array().into(),
+                    RejectReason::ExecutionFailure("test".to_string()),
+                ),
+            },
+            vec![],
+            vec![],
+            Duration::from_millis(1),
+        )
+    }
+
+    #[test]
+    fn it_returns_the_execution_in_the_highest_block() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        let block1 = create_block(&zero_block, 1);
+        block1.insert(&mut tx).unwrap();
+        let block2 = create_block(&block1, 2);
+        block2.insert(&mut tx).unwrap();
+
+        let atom = create_tx_atom();
+        // Insert the higher block's execution first so that insertion order does not match height order
+        tx.transaction_executions_insert_or_ignore(&create_execution(*block2.id(), atom.id))
+            .unwrap();
+        tx.transaction_executions_insert_or_ignore(&create_execution(*block1.id(), atom.id))
+            .unwrap();
+
+        let latest = tx.transaction_executions_get_latest(&atom.id).unwrap().unwrap();
+        assert_eq!(latest.block_id(), block2.id());
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_returns_all_executions_for_a_block() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        let block1 = create_block(&zero_block, 1);
+        block1.insert(&mut tx).unwrap();
+        let block2 = create_block(&block1, 2);
+        block2.insert(&mut tx).unwrap();
+
+        let mut ids = (0..3).map(|_| create_tx_atom().id).collect::<Vec<_>>();
+        for id in &ids {
+            tx.transaction_executions_insert_or_ignore(&create_execution(*block1.id(), *id))
+                .unwrap();
+        }
+        tx.transaction_executions_insert_or_ignore(&create_execution(*block2.id(), create_tx_atom().id))
+            .unwrap();
+
+        let executions = tx.transaction_executions_get_for_block(block1.id()).unwrap();
+        ids.sort();
+        assert_eq!(executions.iter().map(|e| *e.transaction_id()).collect::<Vec<_>>(), ids);
+        assert!(executions.iter().all(|e| e.block_id() == block1.id()));
+        assert!(tx
+            .transaction_executions_get_for_block(&BlockId::zero())
+            .unwrap()
+            .is_empty());
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_returns_none_if_there_are_no_executions() {
+        let db = create_db();
+        let tx = db.create_read_tx().unwrap();
+
+        let atom = create_tx_atom();
+        assert!(tx.transaction_executions_get_latest(&atom.id).unwrap().is_none());
+    }
+}
+
+mod transactions_get_full {
+    use std::time::Duration;
+
+    use tari_dan_common_types::{optional::IsNotFoundError, NumPreshards, ShardGroup};
+    use tari_dan_storage::consensus_models::{BlockId, Evidence, TransactionExecution};
+    use tari_engine_types::commit_result::{ExecuteResult, FinalizeResult, RejectReason};
+
+    use super::*;
+
+    fn create_block(parent: &Block, height: u64) -> Block {
+        Block::new(
+            parent.network(),
+            *parent.id(),
+            parent.justify().clone(),
+            NodeHeight(height),
+            Epoch(0),
+            ShardGroup::all_shards(NumPreshards::P64),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            EpochTime::now().as_u64(),
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    fn create_execution(block_id: BlockId, transaction_id: TransactionId) -> TransactionExecution {
+        TransactionExecution::new(
+            block_id,
+            transaction_id,
+            ExecuteResult {
+                finalize: FinalizeResult::new_rejected(
+                    transaction_id.into_array().into(),
+                    RejectReason::ExecutionFailure("test".to_string()),
+                ),
+            },
+            vec![],
+            vec![],
+            Duration::from_millis(1),
+        )
+    }
+
+    #[test]
+    fn it_joins_the_latest_execution_and_pool_entry() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        let block1 = create_block(&zero_block, 1);
+        block1.insert(&mut tx).unwrap();
+        let block2 = create_block(&block1, 2);
+        block2.insert(&mut tx).unwrap();
+
+        let mut rec = create_tx_record(1);
+        rec.final_decision = Some(Decision::Commit);
+        rec.insert(&mut tx).unwrap();
+        tx.transaction_executions_insert_or_ignore(&create_execution(*block1.id(), *rec.id()))
+            .unwrap();
+        tx.transaction_executions_insert_or_ignore(&create_execution(*block2.id(), *rec.id()))
+            .unwrap();
+        tx.transaction_pool_insert_new(*rec.id(), Decision::Commit).unwrap();
+
+        let view = tx.transactions_get_full(rec.id()).unwrap();
+        assert_eq!(view.record.id(), rec.id());
+        assert_eq!(view.final_decision, Some(Decision::Commit));
+        let latest_execution = view.latest_execution.as_ref().unwrap();
+        assert_eq!(latest_execution.block_id(), block2.id());
+        assert_eq!(latest_execution.transaction_id(), rec.id());
+        assert_eq!(view.pool_stage, Some(TransactionPoolStage::New));
+        assert_eq!(view.evidence, Some(Evidence::empty()));
+        assert!(view.is_in_pool());
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_returns_the_record_alone_if_not_executed_or_in_the_pool() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let rec = create_tx_record(1);
+        rec.insert(&mut tx).unwrap();
+
+        let view = tx.transactions_get_full(rec.id()).unwrap();
+        assert_eq!(view.record.id(), rec.id());
+        assert!(view.final_decision.is_none());
+        assert!(view.latest_execution.is_none());
+        assert!(view.pool_stage.is_none());
+        assert!(view.evidence.is_none());
+        assert!(!view.is_in_pool());
+
+        let err = tx.transactions_get_full(&create_tx_atom().id).unwrap_err();
+        assert!(err.is_not_found_error());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod blocks_tip_n {
+    use tari_dan_common_types::{NumPreshards, ShardGroup};
+
+    use super::*;
+
+    fn create_block(parent: &Block, height: u64, shard_group: ShardGroup, timestamp: u64) -> Block {
+        Block::new(
+            parent.network(),
+            *parent.id(),
+            parent.justify().clone(),
+            NodeHeight(height),
+            Epoch(0),
+            shard_group,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            timestamp,
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    #[test]
+    fn it_returns_the_most_recent_blocks() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        // Blocks are loaded together with their justify QC
+        zero_block.justify().insert(&mut tx).unwrap();
+
+        let now = EpochTime::now().as_u64();
+        let block1 = create_block(&zero_block, 1, ShardGroup::new(0, 31), now);
+        block1.insert(&mut tx).unwrap();
+        let other_block1 = create_block(&zero_block, 1, ShardGroup::new(32, 63), now + 1);
+        other_block1.insert(&mut tx).unwrap();
+        let dummy = Block::dummy_block(
+            block1.network(),
+            *block1.id(),
+            block1.proposed_by().clone(),
+            NodeHeight(2),
+            block1.justify().clone(),
+            block1.epoch(),
+            block1.shard_group(),
+            *block1.merkle_root(),
+            block1.timestamp(),
+            block1.base_layer_block_height(),
+            *block1.base_layer_block_hash(),
+        );
+        dummy.insert(&mut tx).unwrap();
+
+        let blocks = tx.blocks_get_tip_n(2, true).unwrap();
+        assert_eq!(blocks.iter().map(|b| *b.id()).collect::<Vec<_>>(), vec![
+            *dummy.id(),
+            *other_block1.id()
+        ]);
+        assert!(blocks[0].is_dummy());
+
+        let blocks = tx.blocks_get_tip_n(10, false).unwrap();
+        assert_eq!(blocks.iter().map(|b| *b.id()).collect::<Vec<_>>(), vec![
+            *other_block1.id(),
+            *block1.id(),
+            *zero_block.id()
+        ]);
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_returns_the_highest_committed_block() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        assert!(tx.blocks_get_commit_tip().unwrap().is_none());
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        zero_block.justify().insert(&mut tx).unwrap();
+
+        let now = EpochTime::now().as_u64();
+        let block1 = create_block(&zero_block, 1, ShardGroup::new(0, 63), now);
+        block1.insert(&mut tx).unwrap();
+        let block2 = create_block(&block1, 2, ShardGroup::new(0, 63), now);
+        block2.insert(&mut tx).unwrap();
+
+        tx.blocks_set_flags(block1.id(), Some(true), None).unwrap();
+        let commit_tip = tx.blocks_get_commit_tip().unwrap().unwrap();
+        assert_eq!(commit_tip.id(), block1.id());
+
+        tx.blocks_set_flags(block2.id(), Some(true), None).unwrap();
+        let commit_tip = tx.blocks_get_commit_tip().unwrap().unwrap();
+        assert_eq!(commit_tip.id(), block2.id());
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_returns_the_block_nearest_to_a_timestamp() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        assert!(tx.blocks_get_nearest_to_timestamp(100).unwrap().is_none());
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        zero_block.justify().insert(&mut tx).unwrap();
+
+        let block1 = create_block(&zero_block, 1, ShardGroup::new(0, 63), 100);
+        block1.insert(&mut tx).unwrap();
+        let block2 = create_block(&block1, 2, ShardGroup::new(0, 63), 200);
+        block2.insert(&mut tx).unwrap();
+        let dummy = Block::dummy_block(
+            block2.network(),
+            *block2.id(),
+            block2.proposed_by().clone(),
+            NodeHeight(3),
+            block2.justify().clone(),
+            block2.epoch(),
+            block2.shard_group(),
+            *block2.merkle_root(),
+            250,
+            block2.base_layer_block_height(),
+            *block2.base_layer_block_hash(),
+        );
+        dummy.insert(&mut tx).unwrap();
+        let block4 = create_block(&dummy, 4, ShardGroup::new(0, 63), 300);
+        block4.insert(&mut tx).unwrap();
+
+        let nearest = |timestamp| *tx.blocks_get_nearest_to_timestamp(timestamp).unwrap().unwrap().id();
+        assert_eq!(nearest(0), *block1.id());
+        // Equally close to block 1 and block 2
+        assert_eq!(nearest(150), *block1.id());
+        // The dummy block is closest but is excluded
+        assert_eq!(nearest(245), *block2.id());
+        assert_eq!(nearest(280), *block4.id());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod epoch_audit {
+    use std::collections::BTreeSet;
+
+    use diesel::RunQueryDsl;
+    use tari_dan_common_types::{NumPreshards, ShardGroup};
+    use tari_dan_storage::consensus_models::{EpochAuditBundle, QuorumDecision, ValidatorSignature, Vote};
+    use tari_state_store_sqlite::SqliteStoreConfig;
+
+    use super::*;
+
+    fn create_block(parent: &Block, height: u64, epoch: Epoch, commands: BTreeSet<Command>) -> Block {
+        Block::new(
+            parent.network(),
+            *parent.id(),
+            parent.justify().clone(),
+            NodeHeight(height),
+            epoch,
+            ShardGroup::new(0, 63),
+            Default::default(),
+            commands,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            EpochTime::now().as_u64(),
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    #[test]
+    fn it_exports_the_consensus_data_for_the_epoch() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        // Blocks are loaded together with their justify QC
+        zero_block.justify().insert(&mut tx).unwrap();
+
+        let atom = create_tx_atom();
+        let block1 = create_block(&zero_block, 1, Epoch(0), [Command::Prepare(atom.clone())].into());
+        block1.insert(&mut tx).unwrap();
+        let next_epoch_block = create_block(&block1, 2, Epoch(1), BTreeSet::new());
+        next_epoch_block.insert(&mut tx).unwrap();
+
+        tx.votes_insert(&Vote {
+            epoch: Epoch(0),
+            block_id: *block1.id(),
+            decision: QuorumDecision::Accept,
+            sender_leaf_hash: FixedHash::zero(),
+            signature: ValidatorSignature::new(Default::default(), Default::default()),
+        })
+        .unwrap();
+
+        // Changing the stage of the pool record is recorded in the pool history
+        tx.transaction_pool_insert_new(atom.id, atom.decision).unwrap();
+        diesel::sql_query(format!(
+            "UPDATE transaction_pool SET stage = 'Prepared' WHERE transaction_id = '{}'",
+            atom.id
+        ))
+        .execute(tx.connection())
+        .unwrap();
+
+        let bundle = EpochAuditBundle::export(&*tx, Epoch(0)).unwrap();
+        assert_eq!(bundle.epoch, Epoch(0));
+        assert!(bundle.checkpoint.is_none());
+        assert_eq!(bundle.blocks.iter().map(|b| *b.block.id()).collect::<Vec<_>>(), vec![
+            *zero_block.id(),
+            *block1.id()
+        ]);
+        assert_eq!(bundle.blocks[1].votes.len(), 1);
+        assert!(bundle.blocks[1].certified_by.is_none());
+        assert_eq!(bundle.transaction_pool_history.len(), 1);
+        assert_eq!(bundle.transaction_pool_history[0].transaction_id, atom.id);
+        assert_eq!(
+            bundle.transaction_pool_history[0].new_stage,
+            TransactionPoolStage::Prepared
+        );
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_exports_the_pool_history_of_more_transactions_than_the_sql_variable_limit() {
+        let config = SqliteStoreConfig {
+            max_sql_variables: 2,
+            ..Default::default()
+        };
+        let db = SqliteStateStore::<String>::connect_with_config(":memory:", &config).unwrap();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        zero_block.justify().insert(&mut tx).unwrap();
+
+        let atoms = (0..5).map(|_| create_tx_atom()).collect::<Vec<_>>();
+        let block1 = create_block(
+            &zero_block,
+            1,
+            Epoch(0),
+            atoms.iter().cloned().map(Command::Prepare).collect(),
+        );
+        block1.insert(&mut tx).unwrap();
+
+        for atom in &atoms {
+            tx.transaction_pool_insert_new(atom.id, atom.decision).unwrap();
+            diesel::sql_query(format!(
+                "UPDATE transaction_pool SET stage = 'Prepared' WHERE transaction_id = '{}'",
+                atom.id
+            ))
+            .execute(tx.connection())
+            .unwrap();
+        }
+
+        let bundle = EpochAuditBundle::export(&*tx, Epoch(0)).unwrap();
+        assert_eq!(bundle.blocks.len(), 2);
+        // History is returned oldest first across all chunks
+        assert_eq!(
+            bundle
+                .transaction_pool_history
+                .iter()
+                .map(|entry| entry.transaction_id)
+                .collect::<Vec<_>>(),
+            atoms.iter().map(|atom| atom.id).collect::<Vec<_>>()
+        );
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod foreign_proposals_gc {
+    use tari_dan_common_types::ShardGroup;
+    use tari_dan_storage::consensus_models::{BlockId, ForeignProposal};
+
+    use super::*;
+
+    fn create_foreign_proposal(base_layer_block_height: u64) -> ForeignProposal {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        ForeignProposal::new(
+            ShardGroup::new(0, 31),
+            BlockId::from(FixedHash::from(bytes)),
+            vec![],
+            base_layer_block_height,
+        )
+    }
+
+    #[test]
+    fn it_deletes_proposals_below_the_base_layer_height() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let stale1 = create_foreign_proposal(5);
+        let stale2 = create_foreign_proposal(9);
+        let current = create_foreign_proposal(10);
+        for proposal in [&stale1, &stale2, &current] {
+            tx.foreign_proposal_upsert(proposal).unwrap();
+        }
+
+        let num_deleted = tx.foreign_proposals_delete_below_base_layer_height(10).unwrap();
+        assert_eq!(num_deleted, 2);
+        assert!(!tx.foreign_proposal_exists(&stale1).unwrap());
+        assert!(!tx.foreign_proposal_exists(&stale2).unwrap());
+        assert!(tx.foreign_proposal_exists(&current).unwrap());
+
+        let num_deleted = tx.foreign_proposals_delete_below_base_layer_height(10).unwrap();
+        assert_eq!(num_deleted, 0);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod state_tree_versions {
+    use tari_dan_common_types::shard::Shard;
+    use tari_state_tree::{Node, NodeKey};
+
+    use super::*;
+
+    #[test]
+    fn it_gets_the_latest_versions_for_many_shards() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        tx.state_tree_shard_versions_set(Shard::from(1), 5).unwrap();
+        tx.state_tree_shard_versions_set(Shard::from(2), 3).unwrap();
+        tx.state_tree_shard_versions_set(Shard::from(2), 7).unwrap();
+        tx.state_tree_shard_versions_set(Shard::from(3), 1).unwrap();
+
+        let shards = [Shard::from(1), Shard::from(2), Shard::from(4)];
+        let versions = tx.state_tree_versions_get_latest_many(&shards).unwrap();
+        assert_eq!(versions.len(), 2);
+        for shard in shards {
+            assert_eq!(
+                versions.get(&shard).copied(),
+                tx.state_tree_versions_get_latest(shard).unwrap()
+            );
+        }
+        assert_eq!(versions[&Shard::from(2)], 7);
+
+        assert!(tx.state_tree_versions_get_latest_many(&[]).unwrap().is_empty());
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_verifies_that_the_shard_version_has_a_root_node() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+        let shard = Shard::from(1);
+
+        // No version has been recorded yet
+        assert!(tx.state_tree_verify_shard_version(shard).unwrap());
+
+        tx.state_tree_nodes_insert(shard, NodeKey::new_empty_path(1), Node::Null)
+            .unwrap();
+        tx.state_tree_shard_versions_set(shard, 1).unwrap();
+        assert!(tx.state_tree_verify_shard_version(shard).unwrap());
+
+        // The version advanced without inserting the root node for it
+        tx.state_tree_shard_versions_set(shard, 2).unwrap();
+        assert!(!tx.state_tree_verify_shard_version(shard).unwrap());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod last_sent_vote {
+    use tari_dan_storage::consensus_models::{BlockId, LastSentVote, QuorumDecision, ValidatorSignature};
+
+    use super::*;
+
+    #[test]
+    fn it_gets_and_clears_the_last_sent_vote() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        assert!(tx.last_sent_vote_get().unwrap().is_none());
+
+        let signature = ValidatorSignature::new(Default::default(), Default::default());
+        let block_id = BlockId::from(FixedHash::from([1u8; 32]));
+        tx.last_sent_vote_set(&LastSentVote {
+            epoch: Epoch(1),
+            block_id,
+            block_height: NodeHeight(10),
+            decision: QuorumDecision::Accept,
+            signature: signature.clone(),
+        })
+        .unwrap();
+
+        let vote = tx.last_sent_vote_get().unwrap().unwrap();
+        assert_eq!(vote.epoch, Epoch(1));
+        assert_eq!(vote.block_id, block_id);
+        assert_eq!(vote.block_height, NodeHeight(10));
+        assert_eq!(vote.signature.public_key, signature.public_key);
+        assert_eq!(vote.signature.signature, signature.signature);
+
+        tx.last_sent_vote_clear().unwrap();
+        assert!(tx.last_sent_vote_get().unwrap().is_none());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod quorum_certificate_chain {
+    use tari_dan_common_types::{NumPreshards, ShardGroup};
+    use tari_dan_storage::consensus_models::{QuorumCertificate, QuorumDecision};
+
+    use super::*;
+
+    fn create_block(parent: &Block, justify: QuorumCertificate) -> Block {
+        Block::new(
+            parent.network(),
+            *parent.id(),
+            justify,
+            parent.height() + NodeHeight(1),
+            Epoch(0),
+            ShardGroup::all_shards(NumPreshards::P64),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            0,
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    fn create_qc(block: &Block) -> QuorumCertificate {
+        QuorumCertificate::new(
+            *block.id(),
+            block.height(),
+            block.epoch(),
+            block.shard_group(),
+            vec![],
+            vec![],
+            QuorumDecision::Accept,
+        )
+    }
+
+    #[test]
+    fn it_walks_the_justify_chain_to_the_zero_qc() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.justify().insert(&mut tx).unwrap();
+        zero_block.insert(&mut tx).unwrap();
+
+        let block1 = create_block(&zero_block, zero_block.justify().clone());
+        block1.insert(&mut tx).unwrap();
+        let qc1 = create_qc(&block1);
+        qc1.insert(&mut tx).unwrap();
+
+        let block2 = create_block(&block1, qc1.clone());
+        block2.insert(&mut tx).unwrap();
+        let qc2 = create_qc(&block2);
+        qc2.insert(&mut tx).unwrap();
+
+        let block3 = create_block(&block2, qc2.clone());
+        block3.insert(&mut tx).unwrap();
+
+        let chain = tx.quorum_certificates_get_chain(block3.id(), 10).unwrap();
+        assert_eq!(chain.iter().map(|qc| *qc.id()).collect::<Vec<_>>(), vec![
+            *qc2.id(),
+            *qc1.id(),
+            *zero_block.justify().id()
+        ]);
+        assert!(chain.last().unwrap().is_zero());
+
+        let chain = tx.quorum_certificates_get_chain(block3.id(), 2).unwrap();
+        assert_eq!(chain.iter().map(|qc| *qc.id()).collect::<Vec<_>>(), vec![
+            *qc2.id(),
+            *qc1.id()
+        ]);
+
+        assert!(tx.quorum_certificates_get_chain(block3.id(), 0).unwrap().is_empty());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod votes_bulk_insert {
+    use tari_dan_storage::consensus_models::{BlockId, QuorumDecision, ValidatorSignature, Vote};
+
+    use super::*;
+
+    fn create_vote(block_id: BlockId, n: u64) -> Vote {
+        let mut sender_leaf_hash = [0u8; 32];
+        sender_leaf_hash[..8].copy_from_slice(&n.to_le_bytes());
+        Vote {
+            epoch: Epoch(0),
+            block_id,
+            decision: QuorumDecision::Accept,
+            sender_leaf_hash: FixedHash::from(sender_leaf_hash),
+            signature: ValidatorSignature::new(Default::default(), Default::default()),
+        }
+    }
+
+    #[test]
+    fn it_inserts_many_votes_and_skips_duplicates() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let block_id = BlockId::from(FixedHash::from([1u8; 32]));
+        let votes = (0..200).map(|n| create_vote(block_id, n)).collect::<Vec<_>>();
+        tx.votes_insert_all(&votes).unwrap();
+        assert_eq!(tx.votes_get_for_block(Epoch(0), &block_id).unwrap().len(), 200);
+
+        // Re-inserting some of the same votes alongside a new one only adds the new vote
+        let new_vote = create_vote(block_id, 200);
+        tx.votes_insert_all(votes.iter().take(10).chain([&new_vote])).unwrap();
+        assert_eq!(tx.votes_get_for_block(Epoch(0), &block_id).unwrap().len(), 201);
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_returns_the_latest_vote_from_each_sender_in_the_epoch() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let block_id = BlockId::from(FixedHash::from([1u8; 32]));
+        tx.votes_insert(&create_vote(block_id, 1)).unwrap();
+        tx.votes_insert(&create_vote(block_id, 2)).unwrap();
+        let latest_vote = Vote {
+            decision: QuorumDecision::Reject,
+            ..create_vote(block_id, 1)
+        };
+        tx.votes_insert(&latest_vote).unwrap();
+        let other_epoch_vote = Vote {
+            epoch: Epoch(1),
+            ..create_vote(block_id, 3)
+        };
+        tx.votes_insert(&other_epoch_vote).unwrap();
+
+        let votes = tx.votes_get_for_block(Epoch(0), &block_id).unwrap();
+        assert_eq!(votes.len(), 2);
+        assert_eq!(votes[0].sender_leaf_hash, create_vote(block_id, 2).sender_leaf_hash);
+        assert_eq!(votes[1].sender_leaf_hash, latest_vote.sender_leaf_hash);
+        assert_eq!(votes[1].decision, QuorumDecision::Reject);
+
+        let votes = tx.votes_get_for_block(Epoch(1), &block_id).unwrap();
+        assert_eq!(votes.len(), 1);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod substate_locks_conflicts {
+    use std::str::FromStr;
+
+    use tari_dan_storage::consensus_models::{BlockId, LockedSubstate, SubstateLockFlag};
+    use tari_engine_types::substate::SubstateId;
+
+    use super::*;
+
+    fn create_substate_id(n: u32) -> SubstateId {
+        SubstateId::from_str(&format!("component_{n:056x}")).unwrap()
+    }
+
+    #[test]
+    fn it_returns_locks_that_conflict_with_the_requested_lock() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let block_id = BlockId::from(FixedHash::from([1u8; 32]));
+        let read_locked = create_substate_id(1);
+        let write_locked = create_substate_id(2);
+        let reader = create_tx_atom().id;
+        let writer = create_tx_atom().id;
+        let other = create_tx_atom().id;
+        tx.substate_locks_insert_all(block_id, [
+            (read_locked.clone(), vec![LockedSubstate::new(
+                reader,
+                0,
+                SubstateLockFlag::Read,
+                false,
+            )]),
+            (write_locked.clone(), vec![LockedSubstate::new(
+                writer,
+                0,
+                SubstateLockFlag::Write,
+                false,
+            )]),
+        ])
+        .unwrap();
+
+        // Read locks do not conflict with each other
+        let conflicts = tx
+            .substate_locks_get_conflicting(&read_locked, &other, SubstateLockFlag::Read)
+            .unwrap();
+        assert!(conflicts.is_empty());
+
+        let conflicts = tx
+            .substate_locks_get_conflicting(&read_locked, &other, SubstateLockFlag::Write)
+            .unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].transaction_id(), reader);
+        assert!(conflicts[0].substate_lock().is_read());
+
+        let conflicts = tx
+            .substate_locks_get_conflicting(&write_locked, &other, SubstateLockFlag::Read)
+            .unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].transaction_id(), writer);
+
+        let conflicts = tx
+            .substate_locks_get_conflicting(&create_substate_id(3), &other, SubstateLockFlag::Write)
+            .unwrap();
+        assert!(conflicts.is_empty());
+
+        // A transaction's own locks never conflict with it
+        let conflicts = tx
+            .substate_locks_get_conflicting(&write_locked, &writer, SubstateLockFlag::Write)
+            .unwrap();
+        assert!(conflicts.is_empty());
+        let conflicts = tx
+            .substate_locks_get_conflicting(&read_locked, &reader, SubstateLockFlag::Write)
+            .unwrap();
+        assert!(conflicts.is_empty());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod substate_locks_remove_expired {
+    use std::str::FromStr;
+
+    use tari_dan_common_types::{NumPreshards, ShardGroup};
+    use tari_dan_storage::consensus_models::{LockedSubstate, SubstateLockFlag};
+    use tari_engine_types::substate::SubstateId;
+
+    use super::*;
+
+    fn create_block(parent: &Block, height: u64) -> Block {
+        Block::new(
+            parent.network(),
+            *parent.id(),
+            parent.justify().clone(),
+            NodeHeight(height),
+            Epoch(0),
+            ShardGroup::all_shards(NumPreshards::P64),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            EpochTime::now().as_u64(),
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    fn create_lock(n: u32, transaction_id: TransactionId) -> (SubstateId, Vec<LockedSubstate>) {
+        let substate_id = SubstateId::from_str(&format!("component_{n:056x}")).unwrap();
+        (substate_id, vec![LockedSubstate::new(
+            transaction_id,
+            0,
+            SubstateLockFlag::Write,
+            false,
+        )])
+    }
+
+    #[test]
+    fn it_removes_committed_locks_of_transactions_that_are_not_in_the_pool() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let zero_block = Block::zero_block(Default::default(), NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        let block1 = create_block(&zero_block, 1);
+        block1.insert(&mut tx).unwrap();
+        let block2 = create_block(&block1, 2);
+        block2.insert(&mut tx).unwrap();
+
+        let leaked = create_tx_atom();
+        let pooled = create_tx_atom();
+        let uncommitted = create_tx_atom();
+        tx.transaction_pool_insert_new(pooled.id, pooled.decision).unwrap();
+        tx.substate_locks_insert_all(*block1.id(), [create_lock(1, leaked.id), create_lock(2, pooled.id)])
+            .unwrap();
+        tx.substate_locks_insert_all(*block2.id(), [create_lock(3, uncommitted.id)])
+            .unwrap();
+
+        let num_removed = tx.substate_locks_remove_expired(*block1.id()).unwrap();
+        assert_eq!(num_removed, 1);
+
+        let locks = tx.substate_locks_get_all_for_block(*block1.id()).unwrap();
+        assert_eq!(locks.len(), 1);
+        assert_eq!(locks.values().next().unwrap()[0].transaction_id(), pooled.id);
+        let locks = tx.substate_locks_get_all_for_block(*block2.id()).unwrap();
+        assert_eq!(locks.len(), 1);
+
+        // Sweeping again is a no-op
+        let num_removed = tx.substate_locks_remove_expired(*block1.id()).unwrap();
+        assert_eq!(num_removed, 0);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod substate_data_size {
+    use std::str::FromStr;
+
+    use tari_dan_common_types::shard::Shard;
+    use tari_dan_storage::consensus_models::{BlockId, QcId, SubstateRecord};
+    use tari_engine_types::{fee_claim::FeeClaim, substate::SubstateId};
+
+    use super::*;
+
+    fn create_substate(n: u32, shard: Shard) -> SubstateRecord {
+        SubstateRecord::new(
+            SubstateId::from_str(&format!("component_{n:056x}")).unwrap(),
+            0,
+            FeeClaim {
+                epoch: 1,
+                validator_public_key: Default::default(),
+                amount: Default::default(),
+            }
+            .into(),
+            shard,
+            Epoch(1),
+            NodeHeight(1),
+            BlockId::zero(),
+            create_tx_atom().id,
+            QcId::zero(),
+        )
+    }
+
+    #[test]
+    fn it_sums_substate_data_by_shard() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        assert_eq!(tx.substates_total_data_bytes(None, true).unwrap(), 0);
+
+        let substates = (0..4)
+            .map(|n| create_substate(n, Shard::from(n % 2)))
+            .collect::<Vec<_>>();
+        for substate in &substates {
+            tx.substates_create(substate.clone(), None).unwrap();
+        }
+
+        let total = tx.substates_total_data_bytes(None, false).unwrap();
+        let shard0 = tx.substates_total_data_bytes(Some(Shard::from(0)), false).unwrap();
+        let shard1 = tx.substates_total_data_bytes(Some(Shard::from(1)), false).unwrap();
+        assert!(shard0 > 0);
+        assert!(shard1 > 0);
+        assert_eq!(total, shard0 + shard1);
+
+        tx.substates_down(
+            substates[0].to_versioned_substate_id(),
+            Shard::from(0),
+            Epoch(1),
+            NodeHeight(2),
+            &create_tx_atom().id,
+            &QcId::zero(),
+            None,
+        )
+        .unwrap();
+
+        // Destroyed substates are only counted when requested
+        assert!(tx.substates_total_data_bytes(Some(Shard::from(0)), false).unwrap() < shard0);
+        assert_eq!(
+            tx.substates_total_data_bytes(Some(Shard::from(0)), true).unwrap(),
+            shard0
+        );
+        assert_eq!(
+            tx.substates_total_data_bytes(Some(Shard::from(1)), false).unwrap(),
+            shard1
+        );
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod rejected_blocks {
+    use tari_dan_storage::consensus_models::BlockId;
+    use tari_state_store_sqlite::MAX_REJECTED_BLOCKS;
+
+    use super::*;
+
+    #[test]
+    fn it_returns_the_most_recently_rejected_blocks() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let block_ids = (1..=3u8)
+            .map(|n| BlockId::from(FixedHash::from([n; 32])))
+            .collect::<Vec<_>>();
+        for (i, block_id) in block_ids.iter().enumerate() {
+            tx.rejected_blocks_insert(block_id, NodeHeight(i as u64 + 1), &format!("reason {i}"))
+                .unwrap();
+        }
+
+        let recent = tx.rejected_blocks_get_recent(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].block_id, block_ids[2]);
+        assert_eq!(recent[0].height, NodeHeight(3));
+        assert_eq!(recent[0].reason, "reason 2");
+        assert_eq!(recent[1].block_id, block_ids[1]);
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_ignores_a_block_that_has_already_been_recorded() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let block_id = BlockId::from(FixedHash::from([1; 32]));
+        tx.rejected_blocks_insert(&block_id, NodeHeight(1), "first").unwrap();
+        tx.rejected_blocks_insert(&block_id, NodeHeight(1), "second").unwrap();
+
+        let recent = tx.rejected_blocks_get_recent(10).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].reason, "first");
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_only_retains_the_most_recently_rejected_blocks() {
+        let db = create_db();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let num_blocks = MAX_REJECTED_BLOCKS as u64 + 5;
+        for n in 0..num_blocks {
+            let mut hash = [0u8; 32];
+            hash[..8].copy_from_slice(&n.to_le_bytes());
+            tx.rejected_blocks_insert(&BlockId::from(FixedHash::from(hash)), NodeHeight(n), "reason")
+                .unwrap();
+        }
+
+        let recent = tx.rejected_blocks_get_recent(num_blocks).unwrap();
+        assert_eq!(recent.len(), MAX_REJECTED_BLOCKS as usize);
+        assert_eq!(recent[0].height, NodeHeight(num_blocks - 1));
+        assert_eq!(recent.last().unwrap().height, NodeHeight(5));
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod store_config {
+    use std::time::Duration;
+
+    use tari_state_store_sqlite::{JournalMode, SqliteStoreConfig, SynchronousMode};
+
+    use super::*;
+
+    #[test]
+    fn it_connects_with_the_given_config() {
+        let config = SqliteStoreConfig {
+            busy_timeout: Duration::from_millis(100),
+            journal_mode: JournalMode::Delete,
+            synchronous: SynchronousMode::Normal,
+            max_sql_variables: 999,
+        };
+        let db = SqliteStateStore::<String>::connect_with_config(":memory:", &config).unwrap();
+        let tx = db.create_read_tx().unwrap();
+        assert!(tx.rejected_blocks_get_recent(1).unwrap().is_empty());
+    }
+}
+
+mod transaction_pool_dump {
+    use tari_dan_storage::consensus_models::BlockId;
+
+    use super::*;
+
+    #[test]
+    fn it_returns_each_record_with_its_latest_update() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let atom1 = create_tx_atom();
+        let atom2 = create_tx_atom();
+        tx.transaction_pool_insert_new(atom1.id, atom1.decision).unwrap();
+        tx.transaction_pool_insert_new(atom2.id, atom2.decision).unwrap();
+
+        for (height, stage) in [
+            (1, TransactionPoolStage::Prepared),
+            (2, TransactionPoolStage::LocalPrepared),
+        ] {
+            tx.transaction_pool_add_pending_update(&TransactionPoolStatusUpdate {
+                block_id: BlockId::from(FixedHash::from([height; 32])),
+                block_height: NodeHeight(u64::from(height)),
+                transaction_id: atom1.id,
+                stage,
+                evidence: Default::default(),
+                is_ready: true,
+                local_decision: Decision::Commit,
+            })
+            .unwrap();
+        }
+
+        let dump = tx.transaction_pool_dump().unwrap();
+        assert_eq!(dump.len(), 2);
+
+        assert_eq!(*dump[0].record.transaction_id(), atom1.id);
+        assert!(dump[0].record.committed_stage().is_new());
+        assert!(dump[0].record.pending_stage().is_none());
+        let update = dump[0].update.as_ref().unwrap();
+        assert!(update.stage().is_local_prepared());
+        assert_eq!(update.block_height(), NodeHeight(2));
+
+        assert_eq!(*dump[1].record.transaction_id(), atom2.id);
+        assert!(dump[1].update.is_none());
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod transaction_pool_prune_orphaned_updates {
+    use std::collections::HashSet;
+
+    use tari_dan_storage::consensus_models::BlockId;
+    use tari_state_store_sqlite::SqliteStoreConfig;
+
+    use super::*;
+
+    fn block_id(n: u8) -> BlockId {
+        BlockId::from(FixedHash::from([n; 32]))
+    }
+
+    #[test]
+    fn it_removes_updates_for_orphaned_blocks_and_restores_the_latest_remaining_update() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let atom1 = create_tx_atom();
+        let atom2 = create_tx_atom();
+        tx.transaction_pool_insert_new(atom1.id, atom1.decision).unwrap();
+        tx.transaction_pool_insert_new(atom2.id, atom2.decision).unwrap();
+
+        // Block 2 is orphaned by the reorg
+        for (transaction_id, height, stage, is_ready) in [
+            (atom1.id, 1, TransactionPoolStage::Prepared, true),
+            (atom1.id, 2, TransactionPoolStage::LocalPrepared, false),
+            (atom2.id, 2, TransactionPoolStage::Prepared, false),
+        ] {
+            tx.transaction_pool_add_pending_update(&TransactionPoolStatusUpdate {
+                block_id: block_id(height),
+                block_height: NodeHeight(u64::from(height)),
+                transaction_id,
+                stage,
+                evidence: Default::default(),
+                is_ready,
+                local_decision: Decision::Commit,
+            })
+            .unwrap();
+        }
+
+        let dump = tx.transaction_pool_dump().unwrap();
+        assert!(!dump[1].record.is_ready());
+
+        let valid_block_ids = HashSet::from([block_id(1)]);
+        let num_removed = tx.transaction_pool_prune_orphaned_updates(&valid_block_ids).unwrap();
+        assert_eq!(num_removed, 2);
+
+        let dump = tx.transaction_pool_dump().unwrap();
+        assert_eq!(*dump[0].record.transaction_id(), atom1.id);
+        assert!(dump[0].record.is_ready());
+        let update = dump[0].update.as_ref().unwrap();
+        assert!(update.stage().is_prepared());
+        assert_eq!(*update.block_id(), block_id(1));
+
+        // No updates remain so the readiness is left unchanged
+        assert_eq!(*dump[1].record.transaction_id(), atom2.id);
+        assert!(!dump[1].record.is_ready());
+        assert!(dump[1].update.is_none());
+
+        // Nothing else is orphaned
+        let num_removed = tx.transaction_pool_prune_orphaned_updates(&valid_block_ids).unwrap();
+        assert_eq!(num_removed, 0);
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_accepts_more_valid_block_ids_than_the_sql_variable_limit() {
+        let config = SqliteStoreConfig {
+            max_sql_variables: 10,
+            ..Default::default()
+        };
+        let db = SqliteStateStore::<String>::connect_with_config(":memory:", &config).unwrap();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let atom = create_tx_atom();
+        tx.transaction_pool_insert_new(atom.id, atom.decision).unwrap();
+        for height in [1, 200] {
+            tx.transaction_pool_add_pending_update(&TransactionPoolStatusUpdate {
+                block_id: block_id(height),
+                block_height: NodeHeight(u64::from(height)),
+                transaction_id: atom.id,
+                stage: TransactionPoolStage::Prepared,
+                evidence: Default::default(),
+                is_ready: true,
+                local_decision: Decision::Commit,
+            })
+            .unwrap();
+        }
+
+        // Block 200 is not in the valid set
+        let valid_block_ids = (0..100).map(block_id).collect::<HashSet<_>>();
+        let num_removed = tx.transaction_pool_prune_orphaned_updates(&valid_block_ids).unwrap();
+        assert_eq!(num_removed, 1);
+
+        let dump = tx.transaction_pool_dump().unwrap();
+        assert_eq!(*dump[0].update.as_ref().unwrap().block_id(), block_id(1));
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod state_tree_ordering {
+    use std::str::FromStr;
+
+    use tari_dan_common_types::{shard::Shard, NumPreshards};
+    use tari_dan_storage::{
+        consensus_models::{BlockId, QcId, SubstateRecord, VersionedStateHashTreeDiff},
+        StorageError,
+    };
+    use tari_engine_types::{fee_claim::FeeClaim, substate::SubstateId};
+
+    use super::*;
+
+    fn create_substate(block_id: BlockId, shard: Shard) -> SubstateRecord {
+        SubstateRecord::new(
+            SubstateId::from_str(&format!("component_{:056x}", 1u32)).unwrap(),
+            0,
+            FeeClaim {
+                epoch: 1,
+                validator_public_key: Default::default(),
+                amount: Default::default(),
+            }
+            .into(),
+            shard,
+            Epoch(0),
+            NodeHeight(0),
+            block_id,
+            create_tx_atom().id,
+            QcId::zero(),
+        )
+    }
+
+    #[test]
+    fn it_rejects_substates_inserted_before_the_state_tree_diff_is_committed() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let block = Block::zero_block(Default::default(), NumPreshards::P64);
+        block.insert(&mut tx).unwrap();
+        let shard = Shard::from(0);
+
+        tx.pending_state_tree_diffs_insert(
+            *block.id(),
+            shard,
+            VersionedStateHashTreeDiff::new(1, Default::default()),
+        )
+        .unwrap();
+        let err = tx
+            .substates_create(create_substate(*block.id(), shard), None)
+            .unwrap_err();
+        assert!(matches!(err, StorageError::QueryError { .. }));
+
+        // Once the diff has been committed, the substate can be inserted
+        tx.pending_state_tree_diffs_remove_by_block(block.id()).unwrap();
+        tx.substates_create(create_substate(*block.id(), shard), None).unwrap();
+
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn it_rejects_state_tree_diffs_inserted_after_substates() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let block = Block::zero_block(Default::default(), NumPreshards::P64);
+        block.insert(&mut tx).unwrap();
+        let shard = Shard::from(0);
+
+        tx.substates_create(create_substate(*block.id(), shard), None).unwrap();
+        let err = tx
+            .pending_state_tree_diffs_insert(
+                *block.id(),
+                shard,
+                VersionedStateHashTreeDiff::new(1, Default::default()),
+            )
+            .unwrap_err();
+        assert!(matches!(err, StorageError::QueryError { .. }));
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod foreign_counters {
+    use tari_dan_common_types::shard::Shard;
+    use tari_dan_storage::consensus_models::{
+        BlockId,
+        ForeignCounterMismatch,
+        ForeignReceiveCounters,
+        ForeignSendCounters,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_reports_mismatched_send_and_receive_counts() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+        let block_id = BlockId::from(FixedHash::from([1u8; 32]));
+
+        // Counters that have not been set default to empty
+        let sent = ForeignSendCounters::get_or_default(&*tx, &block_id).unwrap();
+        let received = ForeignReceiveCounters::get_or_default(&*tx).unwrap();
+        assert!(sent.is_empty());
+        assert!(sent.mismatches(&received).is_empty());
+
+        let mut sent = ForeignSendCounters::new();
+        sent.increment_counter(Shard::from(0));
+        sent.increment_counter(Shard::from(0));
+        sent.increment_counter(Shard::from(1));
+        sent.set(&mut tx, &block_id).unwrap();
+
+        let mut received = ForeignReceiveCounters::new();
+        received.counters.insert(Shard::from(0), 2);
+        received.counters.insert(Shard::from(2), 1);
+        received.save(&mut tx).unwrap();
+
+        let sent = tx.foreign_send_counters_get(&block_id).unwrap();
+        let received = tx.foreign_receive_counters_get().unwrap();
+        assert_eq!(sent.mismatches(&received), vec![
+            ForeignCounterMismatch {
+                shard: Shard::from(1),
+                sent: 1,
+                received: 0,
+            },
+            ForeignCounterMismatch {
+                shard: Shard::from(2),
+                sent: 0,
+                received: 1,
+            },
+        ]);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod substates_by_transaction {
+    use std::str::FromStr;
+
+    use tari_dan_common_types::shard::Shard;
+    use tari_dan_storage::consensus_models::{BlockId, QcId, SubstateRecord};
+    use tari_engine_types::{fee_claim::FeeClaim, substate::SubstateId};
+
+    use super::*;
+
+    fn create_substate(n: u32, created_by: TransactionId) -> SubstateRecord {
+        SubstateRecord::new(
+            SubstateId::from_str(&format!("component_{n:056x}")).unwrap(),
+            0,
+            FeeClaim {
+                epoch: 1,
+                validator_public_key: Default::default(),
+                amount: Default::default(),
+            }
+            .into(),
+            Shard::from(0),
+            Epoch(1),
+            NodeHeight(1),
+            BlockId::zero(),
+            created_by,
+            QcId::zero(),
+        )
+    }
+
+    #[test]
+    fn it_returns_substates_created_and_destroyed_by_a_transaction() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let creator = create_tx_atom().id;
+        let destroyer = create_tx_atom().id;
+        let substates = [3, 1, 2]
+            .into_iter()
+            .map(|n| create_substate(n, creator))
+            .collect::<Vec<_>>();
+        for substate in &substates {
+            tx.substates_create(substate.clone(), None).unwrap();
+        }
+        tx.substates_create(create_substate(4, create_tx_atom().id), None)
+            .unwrap();
+
+        for substate in &substates[..2] {
+            tx.substates_down(
+                substate.to_versioned_substate_id(),
+                Shard::from(0),
+                Epoch(1),
+                NodeHeight(2),
+                &destroyer,
+                &QcId::zero(),
+                None,
+            )
+            .unwrap();
+        }
+
+        let created = tx.substates_get_many_by_created_transaction(&creator).unwrap();
+        let created_ids = created.iter().map(|s| s.substate_id.clone()).collect::<Vec<_>>();
+        assert_eq!(created_ids, vec![
+            substates[1].substate_id.clone(),
+            substates[2].substate_id.clone(),
+            substates[0].substate_id.clone(),
+        ]);
+
+        let destroyed = tx.substates_get_many_by_destroyed_transaction(&destroyer).unwrap();
+        let destroyed_ids = destroyed.iter().map(|s| s.substate_id.clone()).collect::<Vec<_>>();
+        assert_eq!(destroyed_ids, vec![
+            substates[1].substate_id.clone(),
+            substates[0].substate_id.clone(),
+        ]);
+        assert!(destroyed.iter().all(|s| s.is_destroyed()));
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod substates_verify_addresses {
+    use std::str::FromStr;
+
+    use diesel::RunQueryDsl;
+    use tari_dan_common_types::shard::Shard;
+    use tari_dan_storage::consensus_models::{BlockId, QcId, SubstateRecord};
+    use tari_engine_types::{fee_claim::FeeClaim, substate::SubstateId};
+
+    use super::*;
+
+    fn create_substate(n: u32) -> SubstateRecord {
+        SubstateRecord::new(
+            SubstateId::from_str(&format!("component_{n:056x}")).unwrap(),
+            0,
+            FeeClaim {
+                epoch: 1,
+                validator_public_key: Default::default(),
+                amount: Default::default(),
+            }
+            .into(),
+            Shard::from(0),
+            Epoch(1),
+            NodeHeight(1),
+            BlockId::zero(),
+            create_tx_atom().id,
+            QcId::zero(),
+        )
+    }
+
+    #[test]
+    fn it_returns_substates_with_a_mismatched_address() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let substates = (1..=3).map(create_substate).collect::<Vec<_>>();
+        for substate in &substates {
+            tx.substates_create(substate.clone(), None).unwrap();
+        }
+        assert!(tx.substates_verify_addresses().unwrap().is_empty());
+
+        diesel::sql_query(format!(
+            "UPDATE substates SET address = '{}' WHERE substate_id = '{}'",
+            "00".repeat(36),
+            substates[1].substate_id
+        ))
+        .execute(tx.connection())
+        .unwrap();
+
+        let mismatched = tx.substates_verify_addresses().unwrap();
+        assert_eq!(mismatched, vec![substates[1].substate_id.clone()]);
+
+        tx.rollback().unwrap();
+    }
+}
+
+mod blocks_get_full_context {
+    use tari_dan_common_types::{optional::IsNotFoundError, NumPreshards, ShardGroup};
+    use tari_dan_storage::consensus_models::BlockId;
+
+    use super::*;
+
+    #[test]
+    fn it_returns_the_block_with_its_transactions() {
+        let db = create_db();
+        db.foreign_keys_off().unwrap();
+        let mut tx = db.create_write_tx().unwrap();
+
+        let rec = create_tx_record(1);
+        rec.insert(&mut tx).unwrap();
+        let mut atom = create_tx_atom();
+        atom.id = *rec.id();
+
+        let network = Default::default();
+        let zero_block = Block::zero_block(network, NumPreshards::P64);
+        zero_block.insert(&mut tx).unwrap();
+        let block = Block::new(
+            network,
+            *zero_block.id(),
+            zero_block.justify().clone(),
+            NodeHeight(1),
+            Epoch(0),
+            ShardGroup::all_shards(NumPreshards::P64),
+            Default::default(),
+            [Command::Prepare(atom)].into_iter().collect(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            EpochTime::now().as_u64(),
+            0,
+            FixedHash::zero(),
+        );
+        block.insert(&mut tx).unwrap();
+
+        let context = tx.blocks_get_full_context(block.id()).unwrap();
+        assert_eq!(context.block.id(), block.id());
+        assert_eq!(context.block.justify().id(), zero_block.justify().id());
+        assert_eq!(context.transactions.len(), 1);
+        assert_eq!(context.transactions[0].id(), rec.id());
+        serde_json::to_string(&context).unwrap();
+
+        let err = tx
+            .blocks_get_full_context(&BlockId::new(FixedHash::from([1u8; 32])))
+            .unwrap_err();
+        assert!(err.is_not_found_error());
+
+        tx.rollback().unwrap();
+    }
+}
@@ -45,6 +45,7 @@ use super::{
 use crate::{
     consensus_models::{
         Command,
+        CommandSummary,
         HighQc,
         LastExecuted,
         LastProposed,
@@ -84,7 +85,12 @@ pub struct Block {
     // Body
     #[cfg_attr(feature = "ts", ts(type = "string"))]
     merkle_root: FixedHash,
-    // BTreeSet is used for the deterministic block hash, that is, transactions are always ordered by TransactionId.
+    /// Root of a merkle tree over the block's commands, as declared by the proposer. The block hash commits to the
+    /// commands through this root.
+    #[cfg_attr(feature = "ts", ts(type = "string"))]
+    command_merkle_root: FixedHash,
+    // BTreeSet is used for the deterministic command merkle root, that is, transactions are always ordered by
+    // TransactionId.
     commands: BTreeSet<Command>,
     /// If the block is a dummy block. This is metadata and not sent over
     /// the wire or part of the block hash.
@@ -130,6 +136,7 @@ impl Block {
         base_layer_block_height: u64,
         base_layer_block_hash: FixedHash,
     ) -> Self {
+        let command_merkle_root = compute_command_merkle_root(&commands);
         let mut block = Self {
             id: BlockId::zero(),
             network,
@@ -140,6 +147,7 @@ impl Block {
             shard_group,
             proposed_by,
             merkle_root,
+            command_merkle_root,
             commands,
             total_leader_fee,
             is_dummy: false,
@@ -169,6 +177,7 @@ impl Block {
         proposed_by: PublicKey,
         commands: BTreeSet<Command>,
         merkle_root: FixedHash,
+        command_merkle_root: Option<FixedHash>,
         total_leader_fee: u64,
         is_dummy: bool,
         is_processed: bool,
@@ -181,6 +190,9 @@ impl Block {
         base_layer_block_height: u64,
         base_layer_block_hash: FixedHash,
     ) -> Self {
+        // Blocks stored before the command merkle root was persisted do not have one. These blocks have been validated,
+        // so the root is derived from the stored commands.
+        let command_merkle_root = command_merkle_root.unwrap_or_else(|| compute_command_merkle_root(&commands));
         Self {
             id,
             network,
@@ -191,6 +203,7 @@ impl Block {
             shard_group,
             proposed_by,
             merkle_root,
+            command_merkle_root,
             commands,
             total_leader_fee,
             is_dummy,
@@ -239,6 +252,7 @@ impl Block {
             shard_group: ShardGroup::all_shards(num_preshards),
             proposed_by: PublicKey::default(),
             merkle_root: FixedHash::zero(),
+            command_merkle_root: FixedHash::zero(),
             commands: Default::default(),
             total_leader_fee: 0,
             is_dummy: false,
@@ -277,6 +291,7 @@ impl Block {
             shard_group,
             proposed_by,
             merkle_root: parent_merkle_root,
+            command_merkle_root: FixedHash::zero(),
             commands: BTreeSet::new(),
             total_leader_fee: 0,
             is_dummy: true,
@@ -319,7 +334,7 @@ impl Block {
             .chain(&self.proposed_by)
             .chain(&self.merkle_root)
             .chain(&self.is_dummy)
-            .chain(&self.commands)
+            .chain(&self.command_merkle_root)
             .chain(&self.foreign_indexes)
             .chain(&self.timestamp)
             .chain(&self.base_layer_block_height)
@@ -335,6 +350,18 @@ impl Block {
         self.height.is_zero()
     }
 
+    /// Returns true if this is the first block of its epoch. Every epoch starts with a genesis block at height zero,
+    /// but a block justified by a QC from an earlier epoch is also the first block of its epoch.
+    pub fn is_epoch_start(&self) -> bool {
+        self.is_genesis() || self.justify.epoch() < self.epoch
+    }
+
+    /// Returns true if this is the first block of its epoch, additionally comparing against the epoch of its parent.
+    pub fn is_epoch_start_after(&self, parent: &Block) -> bool {
+        self.is_epoch_start() || parent.epoch() < self.epoch
+    }
+
+    /// Returns true if this is the last block of its epoch, i.e. it contains the EndEpoch command.
     pub fn is_epoch_end(&self) -> bool {
         self.commands.iter().any(|c| c.is_epoch_end())
     }
@@ -343,6 +370,12 @@ impl Block {
         self.commands.iter().filter_map(|d| d.transaction().map(|t| t.id()))
     }
 
+    /// Returns the distinct ids of the transactions referenced by the block's commands. Foreign proposal and end
+    /// epoch commands do not reference a transaction of this block.
+    pub fn referenced_transaction_ids(&self) -> HashSet<TransactionId> {
+        self.all_transaction_ids().copied().collect()
+    }
+
     pub fn all_accepted_transactions_ids(&self) -> impl Iterator<Item = &TransactionId> + '_ {
         self.commands
             .iter()
@@ -358,6 +391,11 @@ impl Block {
         self.commands.len()
     }
 
+    /// Returns the number of commands of each kind in this block
+    pub fn command_summary(&self) -> CommandSummary {
+        self.commands.iter().collect()
+    }
+
     pub fn as_locked_block(&self) -> LockedBlock {
         LockedBlock {
             height: self.height,
@@ -450,10 +488,32 @@ impl Block {
         &self.proposed_by
     }
 
+    /// The root of the state tree for the block's shard group after applying the block's substate changes. This
+    /// cannot be recomputed from the command list alone, since it depends on the result of executing the block's
+    /// transactions, so it is checked after execution when deciding whether to vote. The command list is committed to
+    /// by [Block::command_merkle_root].
     pub fn merkle_root(&self) -> &FixedHash {
         &self.merkle_root
     }
 
+    /// The root of the merkle tree over the block's commands, as declared by the proposer.
+    pub fn command_merkle_root(&self) -> &FixedHash {
+        &self.command_merkle_root
+    }
+
+    /// Sets the command merkle root declared by the proposer and recalculates the block id, since the id commits to the
+    /// root. This is used when a block is received from a peer, so that the root can be checked against
+    /// [Block::recompute_merkle_root].
+    pub fn set_command_merkle_root(&mut self, command_merkle_root: FixedHash) {
+        self.command_merkle_root = command_merkle_root;
+        self.id = self.calculate_hash().into();
+    }
+
+    /// Rebuilds the command merkle root from the block's commands.
+    pub fn recompute_merkle_root(&self) -> FixedHash {
+        compute_command_merkle_root(&self.commands)
+    }
+
     pub fn commands(&self) -> &BTreeSet<Command> {
         &self.commands
     }
@@ -618,7 +678,21 @@ impl Block {
 
         block_diff.remove(tx)?;
 
+        // The state tree has already been updated for this block, so the latest shard versions do not change while
+        // the changes are committed
+        let shards = block_diff
+            .changes()
+            .iter()
+            .map(|change| change.shard())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let state_versions = tx.state_tree_versions_get_latest_many(&shards)?;
+
         for change in block_diff.into_changes() {
+            // Shards without a recorded state tree version are at version 0. Passing the version explicitly avoids
+            // looking it up again for every substate.
+            let state_version = state_versions.get(&change.shard()).copied().unwrap_or(0);
             match change {
                 SubstateChange::Up {
                     id,
@@ -626,32 +700,34 @@ impl Block {
                     transaction_id,
                     substate,
                 } => {
-                    SubstateRecord::new(
-                        id.substate_id,
-                        id.version,
-                        substate.into_substate_value(),
-                        shard,
-                        self.epoch(),
-                        self.height(),
-                        *self.id(),
-                        transaction_id,
-                        *self.justify().id(),
-                    )
-                    .create(tx)?;
+                    tx.substates_create(
+                        SubstateRecord::new(
+                            id.substate_id,
+                            id.version,
+                            substate.into_substate_value(),
+                            shard,
+                            self.epoch(),
+                            self.height(),
+                            *self.id(),
+                            transaction_id,
+                            *self.justify().id(),
+                        ),
+                        Some(state_version),
+                    )?;
                 },
                 SubstateChange::Down {
                     id,
                     transaction_id,
                     shard,
                 } => {
-                    SubstateRecord::destroy(
-                        tx,
+                    tx.substates_down(
                         id,
                         shard,
                         self.epoch(),
                         self.height(),
-                        self.justify().id(),
                         &transaction_id,
+                        self.justify().id(),
+                        Some(state_version),
                     )?;
                 },
             }
@@ -715,7 +791,7 @@ impl Block {
     }
 
     pub fn get_votes<TTx: StateStoreReadTransaction>(&self, tx: &TTx) -> Result<Vec<Vote>, StorageError> {
-        Vote::get_for_block(tx, &self.id)
+        Vote::get_for_block(tx, self.epoch, &self.id)
     }
 
     pub fn get_child_blocks<TTx: StateStoreReadTransaction>(&self, tx: &TTx) -> Result<Vec<Self>, StorageError> {
@@ -1065,3 +1141,251 @@ where
     }
     Ok(())
 }
+
+/// Computes a binary merkle root over the commands in their canonical (sorted) order. An odd node at any level is
+/// carried up unchanged. The root of an empty command set is zero.
+fn compute_command_merkle_root(commands: &BTreeSet<Command>) -> FixedHash {
+    let mut level = commands
+        .iter()
+        .map(|command| {
+            hashing::command_merkle_tree_hasher()
+                .chain(&0u8)
+                .chain(command)
+                .result()
+        })
+        .collect::<Vec<_>>();
+    if level.is_empty() {
+        return FixedHash::zero();
+    }
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hashing::command_merkle_tree_hasher()
+                    .chain(&1u8)
+                    .chain(left)
+                    .chain(right)
+                    .result(),
+                [single] => *single,
+                _ => unreachable!("chunks(2) yields one or two items"),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use tari_transaction::TransactionId;
+
+    use super::*;
+    use crate::consensus_models::{Decision, TransactionAtom};
+
+    fn create_atom(n: u8) -> TransactionAtom {
+        TransactionAtom {
+            id: TransactionId::new([n; 32]),
+            decision: Decision::Commit,
+            evidence: Default::default(),
+            transaction_fee: 0,
+            leader_fee: None,
+        }
+    }
+
+    #[test]
+    fn it_summarizes_commands_by_kind() {
+        let zero_block = Block::zero_block(Network::LocalNet, NumPreshards::P64);
+        let commands = [
+            Command::Prepare(create_atom(1)),
+            Command::Prepare(create_atom(2)),
+            Command::LocalPrepared(create_atom(3)),
+            Command::Accept(create_atom(4)),
+            Command::LocalOnly(create_atom(5)),
+            Command::ForeignProposal(ForeignProposal::new(
+                zero_block.shard_group(),
+                *zero_block.id(),
+                vec![],
+                0,
+            )),
+            Command::EndEpoch,
+        ];
+        let block = Block::new(
+            zero_block.network(),
+            *zero_block.id(),
+            zero_block.justify().clone(),
+            NodeHeight(1),
+            Epoch(0),
+            zero_block.shard_group(),
+            Default::default(),
+            commands.into_iter().collect(),
+            FixedHash::zero(),
+            0,
+            IndexMap::new(),
+            None,
+            0,
+            0,
+            FixedHash::zero(),
+        );
+
+        let summary = block.command_summary();
+        assert_eq!(summary, CommandSummary {
+            prepare: 2,
+            local_prepared: 1,
+            accept: 1,
+            foreign_proposal: 1,
+            local_only: 1,
+            end_epoch: 1,
+        });
+        assert_eq!(summary.total(), block.command_count());
+    }
+
+    #[test]
+    fn it_returns_the_transaction_ids_referenced_by_commands() {
+        let zero_block = Block::zero_block(Network::LocalNet, NumPreshards::P64);
+        let commands = [
+            Command::Prepare(create_atom(1)),
+            Command::LocalPrepared(create_atom(2)),
+            Command::Accept(create_atom(3)),
+            Command::LocalOnly(create_atom(4)),
+            Command::ForeignProposal(ForeignProposal::new(
+                zero_block.shard_group(),
+                *zero_block.id(),
+                vec![TransactionId::new([5; 32])],
+                0,
+            )),
+            Command::EndEpoch,
+        ];
+        let block = create_block(&zero_block, Epoch(0), commands.into_iter().collect());
+
+        let ids = block.referenced_transaction_ids();
+        assert_eq!(
+            ids,
+            (1..=4).map(|n| TransactionId::new([n; 32])).collect::<HashSet<_>>()
+        );
+    }
+
+    fn create_block(parent: &Block, epoch: Epoch, commands: BTreeSet<Command>) -> Block {
+        Block::new(
+            parent.network(),
+            *parent.id(),
+            parent.justify().clone(),
+            parent.height() + NodeHeight(1),
+            epoch,
+            parent.shard_group(),
+            Default::default(),
+            commands,
+            FixedHash::zero(),
+            0,
+            IndexMap::new(),
+            None,
+            0,
+            0,
+            FixedHash::zero(),
+        )
+    }
+
+    #[test]
+    fn it_detects_epoch_boundaries() {
+        let genesis = Block::genesis(Network::LocalNet, Epoch(1), ShardGroup::all_shards(NumPreshards::P64));
+        assert!(genesis.is_epoch_start());
+        assert!(!genesis.is_epoch_end());
+
+        let block = create_block(&genesis, Epoch(1), BTreeSet::new());
+        assert!(!block.is_epoch_start());
+        assert!(!block.is_epoch_start_after(&genesis));
+        assert!(!block.is_epoch_end());
+
+        let end_block = create_block(&block, Epoch(1), [Command::EndEpoch].into_iter().collect());
+        assert!(!end_block.is_epoch_start());
+        assert!(end_block.is_epoch_end());
+
+        // The parent is in an earlier epoch
+        let next_epoch_block = create_block(&end_block, Epoch(2), BTreeSet::new());
+        assert!(next_epoch_block.is_epoch_start_after(&end_block));
+        assert!(!next_epoch_block.is_epoch_end());
+    }
+
+    #[test]
+    fn it_detects_tampered_commands_with_the_command_merkle_root() {
+        let genesis = Block::genesis(Network::LocalNet, Epoch(1), ShardGroup::all_shards(NumPreshards::P64));
+        let block = create_block(
+            &genesis,
+            Epoch(1),
+            [Command::Prepare(create_atom(1)), Command::Prepare(create_atom(2))]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(BlockId::from(block.calculate_hash()), *block.id());
+        assert_eq!(block.recompute_merkle_root(), *block.command_merkle_root());
+
+        // The commands are replaced but the declared root is kept, so the header (and therefore the hash) is unchanged
+        let tampered = load_with(
+            &block,
+            [Command::Prepare(create_atom(1))].into_iter().collect(),
+            Some(*block.command_merkle_root()),
+        );
+        assert_eq!(BlockId::from(tampered.calculate_hash()), *tampered.id());
+        assert_ne!(tampered.recompute_merkle_root(), *tampered.command_merkle_root());
+
+        // Changing the declared root changes the block hash
+        let tampered = load_with(&block, block.commands().clone(), Some(FixedHash::zero()));
+        assert_ne!(BlockId::from(tampered.calculate_hash()), *tampered.id());
+    }
+
+    #[test]
+    fn it_derives_the_command_merkle_root_of_a_stored_block_without_one() {
+        let genesis = Block::genesis(Network::LocalNet, Epoch(1), ShardGroup::all_shards(NumPreshards::P64));
+        let block = create_block(
+            &genesis,
+            Epoch(1),
+            [Command::Prepare(create_atom(1))].into_iter().collect(),
+        );
+
+        let loaded = load_with(&block, block.commands().clone(), None);
+        assert_eq!(loaded.command_merkle_root(), block.command_merkle_root());
+        assert_eq!(loaded.calculate_hash(), block.calculate_hash());
+    }
+
+    #[test]
+    fn it_recalculates_the_block_id_when_setting_the_command_merkle_root() {
+        let genesis = Block::genesis(Network::LocalNet, Epoch(1), ShardGroup::all_shards(NumPreshards::P64));
+        let mut block = create_block(
+            &genesis,
+            Epoch(1),
+            [Command::Prepare(create_atom(1))].into_iter().collect(),
+        );
+        let id = *block.id();
+
+        block.set_command_merkle_root(FixedHash::zero());
+        assert_ne!(*block.id(), id);
+        assert_eq!(BlockId::from(block.calculate_hash()), *block.id());
+    }
+
+    /// Loads a copy of the block as if it were read from the database with the given commands and command merkle root
+    fn load_with(block: &Block, commands: BTreeSet<Command>, command_merkle_root: Option<FixedHash>) -> Block {
+        let now = time::OffsetDateTime::now_utc();
+        Block::load(
+            *block.id(),
+            block.network(),
+            *block.parent(),
+            block.justify().clone(),
+            block.height(),
+            block.epoch(),
+            block.shard_group(),
+            block.proposed_by().clone(),
+            commands,
+            *block.merkle_root(),
+            command_merkle_root,
+            block.total_leader_fee(),
+            block.is_dummy(),
+            false,
+            false,
+            block.foreign_indexes().clone(),
+            None,
+            PrimitiveDateTime::new(now.date(), now.time()),
+            None,
+            block.timestamp(),
+            block.base_layer_block_height(),
+            *block.base_layer_block_hash(),
+        )
+    }
+}
@@ -0,0 +1,15 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use serde::Serialize;
+
+use crate::consensus_models::{Block, TransactionRecord};
+
+/// A block together with the transactions referenced by its commands, intended to be serialized for debugging. The
+/// block's justify QC is loaded from the quorum certificates table and is included in the block.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockContext {
+    pub block: Block,
+    /// The transaction records for the block's commands that are known to this node, in command order
+    pub transactions: Vec<TransactionRecord>,
+}
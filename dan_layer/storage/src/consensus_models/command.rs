@@ -369,3 +369,46 @@ impl Display for Command {
         }
     }
 }
+
+/// The number of commands of each kind in a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "ts",
+    derive(ts_rs::TS),
+    ts(export, export_to = "../../bindings/src/types/")
+)]
+pub struct CommandSummary {
+    pub prepare: usize,
+    pub local_prepared: usize,
+    pub accept: usize,
+    pub foreign_proposal: usize,
+    pub local_only: usize,
+    pub end_epoch: usize,
+}
+
+impl CommandSummary {
+    pub fn add(&mut self, command: &Command) {
+        match command {
+            Command::Prepare(_) => self.prepare += 1,
+            Command::LocalPrepared(_) => self.local_prepared += 1,
+            Command::Accept(_) => self.accept += 1,
+            Command::ForeignProposal(_) => self.foreign_proposal += 1,
+            Command::LocalOnly(_) => self.local_only += 1,
+            Command::EndEpoch => self.end_epoch += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.prepare + self.local_prepared + self.accept + self.foreign_proposal + self.local_only + self.end_epoch
+    }
+}
+
+impl<'a> FromIterator<&'a Command> for CommandSummary {
+    fn from_iter<T: IntoIterator<Item = &'a Command>>(iter: T) -> Self {
+        let mut summary = Self::default();
+        for command in iter {
+            summary.add(command);
+        }
+        summary
+    }
+}
@@ -0,0 +1,105 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use std::collections::HashSet;
+
+use tari_dan_common_types::{optional::Optional, Epoch};
+
+use crate::{
+    consensus_models::{Block, EpochCheckpoint, QuorumCertificate, TransactionPoolHistoryEntry, Vote},
+    StateStoreReadTransaction,
+    StorageError,
+};
+
+/// The number of blocks read from the state store at a time
+const BLOCK_PAGE_SIZE: u64 = 100;
+
+/// A block in an epoch audit, together with the QC that certifies it (if any) and the votes received for it.
+#[derive(Debug, Clone)]
+pub struct EpochAuditBlock {
+    pub block: Block,
+    pub certified_by: Option<QuorumCertificate>,
+    pub votes: Vec<Vote>,
+}
+
+/// A single item of an epoch audit, produced in the order the items are read.
+#[derive(Debug, Clone)]
+pub enum EpochAuditItem {
+    Checkpoint(EpochCheckpoint),
+    Block(EpochAuditBlock),
+    TransactionPoolHistory(TransactionPoolHistoryEntry),
+}
+
+/// All consensus data recorded for an epoch.
+#[derive(Debug, Clone)]
+pub struct EpochAuditBundle {
+    pub epoch: Epoch,
+    /// The checkpoint for the epoch, or None if the epoch has not ended
+    pub checkpoint: Option<EpochCheckpoint>,
+    pub blocks: Vec<EpochAuditBlock>,
+    /// Pool history for every transaction included in a block of the epoch
+    pub transaction_pool_history: Vec<TransactionPoolHistoryEntry>,
+}
+
+impl EpochAuditBundle {
+    /// Exports the consensus data for the epoch. All reads are performed within the given read transaction, so the
+    /// bundle reflects a single consistent snapshot of the state store.
+    pub fn export<TTx: StateStoreReadTransaction>(tx: &TTx, epoch: Epoch) -> Result<Self, StorageError> {
+        let mut bundle = Self {
+            epoch,
+            checkpoint: None,
+            blocks: Vec::new(),
+            transaction_pool_history: Vec::new(),
+        };
+        Self::export_with(tx, epoch, |item| {
+            match item {
+                EpochAuditItem::Checkpoint(checkpoint) => bundle.checkpoint = Some(checkpoint),
+                EpochAuditItem::Block(block) => bundle.blocks.push(block),
+                EpochAuditItem::TransactionPoolHistory(entry) => bundle.transaction_pool_history.push(entry),
+            }
+            Ok(())
+        })?;
+        Ok(bundle)
+    }
+
+    /// Exports the consensus data for the epoch, passing each item to `sink` as soon as it is read rather than
+    /// collecting the whole bundle in memory. Blocks are read a page at a time and emitted one at a time with their
+    /// votes, followed by the pool history of the transactions they contain. As with [EpochAuditBundle::export], all
+    /// reads use the given transaction.
+    pub fn export_with<TTx, F>(tx: &TTx, epoch: Epoch, mut sink: F) -> Result<(), StorageError>
+    where
+        TTx: StateStoreReadTransaction,
+        F: FnMut(EpochAuditItem) -> Result<(), StorageError>,
+    {
+        if let Some(checkpoint) = tx.epoch_checkpoint_get(epoch).optional()? {
+            sink(EpochAuditItem::Checkpoint(checkpoint))?;
+        }
+
+        let mut transaction_ids = HashSet::new();
+        let mut offset = 0;
+        loop {
+            let blocks = tx.blocks_get_paginated_by_epoch(epoch, BLOCK_PAGE_SIZE, offset)?;
+            if blocks.is_empty() {
+                break;
+            }
+            offset += blocks.len() as u64;
+
+            for block in blocks {
+                transaction_ids.extend(block.all_transaction_ids().copied());
+                let certified_by = tx.quorum_certificates_get_by_block_id(block.id()).optional()?;
+                let votes = tx.votes_get_for_block(block.epoch(), block.id())?;
+                sink(EpochAuditItem::Block(EpochAuditBlock {
+                    block,
+                    certified_by,
+                    votes,
+                }))?;
+            }
+        }
+
+        for entry in tx.transaction_pool_history_get_for_transactions(&transaction_ids)? {
+            sink(EpochAuditItem::TransactionPoolHistory(entry))?;
+        }
+
+        Ok(())
+    }
+}
@@ -16,6 +16,7 @@ use tari_transaction::{Transaction, TransactionId, VersionedSubstateId};
 
 use crate::{
     consensus_models::{
+        AbortReason,
         BlockId,
         Decision,
         Evidence,
@@ -46,6 +47,7 @@ pub struct ExecutedTransaction {
     #[cfg_attr(feature = "ts", ts(type = "{secs: number, nanos: number} | null"))]
     finalized_time: Option<Duration>,
     abort_details: Option<String>,
+    abort_reason: Option<AbortReason>,
 }
 
 impl ExecutedTransaction {
@@ -65,6 +67,7 @@ impl ExecutedTransaction {
             final_decision: None,
             finalized_time: None,
             abort_details: None,
+            abort_reason: None,
         }
     }
 
@@ -192,8 +195,13 @@ impl ExecutedTransaction {
         self.abort_details.as_ref()
     }
 
-    pub fn set_abort<T: Into<String>>(&mut self, details: T) -> &mut Self {
+    pub fn abort_reason(&self) -> Option<AbortReason> {
+        self.abort_reason
+    }
+
+    pub fn set_abort<T: Into<String>>(&mut self, reason: AbortReason, details: T) -> &mut Self {
         self.final_decision = Some(Decision::Abort);
+        self.abort_reason = Some(reason);
         self.abort_details = Some(details.into());
         self
     }
@@ -367,6 +375,7 @@ impl TryFrom<TransactionRecord> for ExecutedTransaction {
             finalized_time: value.finalized_time,
             resulting_outputs: value.resulting_outputs,
             abort_details: value.abort_details,
+            abort_reason: value.abort_reason,
         })
     }
 }
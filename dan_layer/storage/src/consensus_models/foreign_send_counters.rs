@@ -1,11 +1,11 @@
 //    Copyright 2023 The Tari Project
 //    SPDX-License-Identifier: BSD-3-Clause
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use tari_dan_common_types::{optional::Optional, shard::Shard};
 
-use super::BlockId;
+use super::{BlockId, ForeignReceiveCounters};
 use crate::{StateStoreReadTransaction, StateStoreWriteTransaction, StorageError};
 
 #[derive(Debug, Clone)]
@@ -41,6 +41,31 @@ impl ForeignSendCounters {
     pub fn is_empty(&self) -> bool {
         self.counters.is_empty()
     }
+
+    /// Returns the shards for which the send count differs from the given receive count, ordered by shard. This can be
+    /// used to detect gaps in foreign messaging.
+    pub fn mismatches(&self, received: &ForeignReceiveCounters) -> Vec<ForeignCounterMismatch> {
+        self.counters
+            .keys()
+            .chain(received.counters.keys())
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|shard| {
+                let sent = self.get_count(shard);
+                let received = received.get_count(&shard);
+                (sent != received).then_some(ForeignCounterMismatch { shard, sent, received })
+            })
+            .collect()
+    }
+}
+
+/// A shard for which the number of foreign messages sent does not match the number received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForeignCounterMismatch {
+    pub shard: Shard,
+    pub sent: u64,
+    pub received: u64,
 }
 
 impl ForeignSendCounters {
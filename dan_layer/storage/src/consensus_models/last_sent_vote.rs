@@ -15,13 +15,17 @@ pub struct LastSentVote {
 }
 
 impl LastSentVote {
-    pub fn get<TTx: StateStoreReadTransaction + ?Sized>(tx: &TTx) -> Result<Self, StorageError> {
+    pub fn get<TTx: StateStoreReadTransaction + ?Sized>(tx: &TTx) -> Result<Option<Self>, StorageError> {
         tx.last_sent_vote_get()
     }
 
     pub fn set<TTx: StateStoreWriteTransaction>(&self, tx: &mut TTx) -> Result<(), StorageError> {
         tx.last_sent_vote_set(self)
     }
+
+    pub fn clear<TTx: StateStoreWriteTransaction>(tx: &mut TTx) -> Result<(), StorageError> {
+        tx.last_sent_vote_clear()
+    }
 }
 
 impl std::fmt::Display for LastSentVote {
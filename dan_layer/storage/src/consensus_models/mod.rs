@@ -2,8 +2,10 @@
 //   SPDX-License-Identifier: BSD-3-Clause
 
 mod block;
+mod block_context;
 mod block_diff;
 mod command;
+mod epoch_audit;
 mod epoch_checkpoint;
 mod executed_transaction;
 mod foreign_proposal;
@@ -18,6 +20,7 @@ mod leaf_block;
 mod locked_block;
 mod quorum;
 mod quorum_certificate;
+mod rejected_block;
 mod state_transition;
 mod state_tree_diff;
 mod substate;
@@ -27,14 +30,17 @@ mod transaction;
 mod transaction_decision;
 mod transaction_execution;
 mod transaction_pool;
+mod transaction_pool_history;
 mod transaction_pool_status_update;
 mod validated_block;
 mod vote;
 mod vote_signature;
 
 pub use block::*;
+pub use block_context::*;
 pub use block_diff::*;
 pub use command::*;
+pub use epoch_audit::*;
 pub use epoch_checkpoint::*;
 pub use executed_transaction::*;
 pub use foreign_proposal::*;
@@ -49,6 +55,7 @@ pub use leaf_block::*;
 pub use locked_block::*;
 pub use quorum::*;
 pub use quorum_certificate::*;
+pub use rejected_block::*;
 pub use state_transition::*;
 pub use state_tree_diff::*;
 pub use substate::*;
@@ -58,6 +65,7 @@ pub use transaction::*;
 pub use transaction_decision::*;
 pub use transaction_execution::*;
 pub use transaction_pool::*;
+pub use transaction_pool_history::*;
 pub use transaction_pool_status_update::*;
 pub use validated_block::*;
 pub use vote::*;
@@ -0,0 +1,16 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_dan_common_types::NodeHeight;
+use time::PrimitiveDateTime;
+
+use crate::consensus_models::BlockId;
+
+/// A block proposal that failed validation, together with the reason it was rejected.
+#[derive(Debug, Clone)]
+pub struct RejectedBlock {
+    pub block_id: BlockId,
+    pub height: NodeHeight,
+    pub reason: String,
+    pub created_at: PrimitiveDateTime,
+}
@@ -171,7 +171,7 @@ impl SubstateRecord {
     }
 
     pub fn create<TTx: StateStoreWriteTransaction>(self, tx: &mut TTx) -> Result<(), StorageError> {
-        tx.substates_create(self)?;
+        tx.substates_create(self, None)?;
         Ok(())
     }
 
@@ -320,6 +320,7 @@ impl SubstateRecord {
             destroyed_by_block,
             destroyed_by_transaction,
             destroyed_justify,
+            None,
         )
     }
 }
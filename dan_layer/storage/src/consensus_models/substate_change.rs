@@ -1,14 +1,15 @@
 //   Copyright 2024 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
-use tari_dan_common_types::{shard::Shard, SubstateAddress};
-use tari_engine_types::substate::Substate;
+use serde::{Deserialize, Serialize};
+use tari_dan_common_types::{shard::Shard, NumPreshards, SubstateAddress};
+use tari_engine_types::substate::{Substate, SubstateDiff};
 use tari_state_tree::SubstateTreeChange;
 use tari_transaction::{TransactionId, VersionedSubstateId};
 
-use crate::consensus_models::SubstateRecord;
+use crate::{consensus_models::SubstateRecord, StorageError};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SubstateChange {
     Up {
         id: VersionedSubstateId,
@@ -88,12 +89,56 @@ impl SubstateChange {
         }
     }
 
-    pub fn as_change_string(&self) -> &'static str {
-        match self {
-            SubstateChange::Up { .. } => "Up",
-            SubstateChange::Down { .. } => "Down",
-        }
+    /// Encodes the full change (including the substate for an Up change) as a string that can be decoded with
+    /// [SubstateChange::from_change_string].
+    pub fn as_change_string(&self) -> Result<String, StorageError> {
+        serde_json::to_string(self).map_err(|e| StorageError::EncodingError {
+            operation: "as_change_string",
+            item: "substate change",
+            details: e.to_string(),
+        })
     }
+
+    /// Decodes a change string produced by [SubstateChange::as_change_string].
+    pub fn from_change_string(change: &str) -> Result<Self, StorageError> {
+        serde_json::from_str(change).map_err(|e| StorageError::DecodingError {
+            operation: "from_change_string",
+            item: "substate change",
+            details: e.to_string(),
+        })
+    }
+}
+
+/// Builds the substate changes for a transaction's substate diff, assigning each change to its shard. All DOWN changes
+/// are returned before UP changes (each in the order they appear in the diff) so that a new substate version is only
+/// applied after its previous version has been downed.
+pub fn build_substate_diff(
+    transaction_id: TransactionId,
+    diff: &SubstateDiff,
+    num_preshards: NumPreshards,
+) -> Vec<SubstateChange> {
+    let downs = diff.down_iter().map(|(id, version)| {
+        let id = VersionedSubstateId::new(id.clone(), *version);
+        let shard = id.to_substate_address().to_shard(num_preshards);
+        SubstateChange::Down {
+            id,
+            shard,
+            transaction_id,
+        }
+    });
+
+    let ups = diff.up_iter().map(|(id, substate)| {
+        let id = VersionedSubstateId::new(id.clone(), substate.version());
+        let shard = id.to_substate_address().to_shard(num_preshards);
+        SubstateChange::Up {
+            id,
+            shard,
+            transaction_id,
+            substate: substate.clone(),
+        }
+    });
+
+    downs.chain(ups).collect()
 }
 
 impl From<SubstateRecord> for SubstateChange {
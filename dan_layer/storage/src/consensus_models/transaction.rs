@@ -1,21 +1,40 @@
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
-use std::{collections::HashSet, ops::Deref, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    time::Duration,
+};
 
-use serde::Deserialize;
-use tari_engine_types::commit_result::{ExecuteResult, FinalizeResult, RejectReason};
+use serde::{Deserialize, Serialize};
+use tari_dan_common_types::NodeHeight;
+use tari_engine_types::{
+    commit_result::{ExecuteResult, FinalizeResult, RejectReason},
+    substate::SubstateId,
+};
 use tari_transaction::{Transaction, TransactionId, VersionedSubstateId};
 
 use crate::{
-    consensus_models::{BlockId, Decision, ExecutedTransaction, TransactionAtom, VersionedSubstateIdLockIntent},
+    consensus_models::{
+        AbortReason,
+        BlockId,
+        Decision,
+        Evidence,
+        ExecutedTransaction,
+        SubstateLockFlag,
+        TransactionAtom,
+        TransactionExecution,
+        TransactionPoolStage,
+        VersionedSubstateIdLockIntent,
+    },
     Ordering,
     StateStoreReadTransaction,
     StateStoreWriteTransaction,
     StorageError,
 };
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionRecord {
     pub transaction: Transaction,
     pub execution_result: Option<ExecuteResult>,
@@ -25,6 +44,7 @@ pub struct TransactionRecord {
     pub final_decision: Option<Decision>,
     pub finalized_time: Option<Duration>,
     pub abort_details: Option<String>,
+    pub abort_reason: Option<AbortReason>,
 }
 
 impl TransactionRecord {
@@ -38,6 +58,7 @@ impl TransactionRecord {
             finalized_time: None,
             resulting_outputs: Vec::new(),
             abort_details: None,
+            abort_reason: None,
         }
     }
 
@@ -50,6 +71,7 @@ impl TransactionRecord {
         finalized_time: Option<Duration>,
         resulting_outputs: Vec<VersionedSubstateId>,
         abort_details: Option<String>,
+        abort_reason: Option<AbortReason>,
     ) -> Self {
         Self {
             transaction,
@@ -60,6 +82,7 @@ impl TransactionRecord {
             finalized_time,
             resulting_outputs,
             abort_details,
+            abort_reason,
         }
     }
 
@@ -95,6 +118,25 @@ impl TransactionRecord {
         self.resolved_inputs.as_deref()
     }
 
+    /// Returns the resolved inputs that are write-locked. Empty if the inputs have not been resolved.
+    pub fn write_lock_inputs(&self) -> Vec<&VersionedSubstateId> {
+        self.resolved_inputs_with_lock(SubstateLockFlag::Write)
+    }
+
+    /// Returns the resolved inputs that are read-locked. Empty if the inputs have not been resolved.
+    pub fn read_lock_inputs(&self) -> Vec<&VersionedSubstateId> {
+        self.resolved_inputs_with_lock(SubstateLockFlag::Read)
+    }
+
+    fn resolved_inputs_with_lock(&self, lock_flag: SubstateLockFlag) -> Vec<&VersionedSubstateId> {
+        self.resolved_inputs()
+            .unwrap_or_default()
+            .iter()
+            .filter(|input| input.lock_flag() == lock_flag)
+            .map(|input| input.versioned_substate_id())
+            .collect()
+    }
+
     pub fn execution_decision(&self) -> Option<Decision> {
         self.execution_result().map(|r| Decision::from(&r.finalize.result))
     }
@@ -131,13 +173,23 @@ impl TransactionRecord {
         self.abort_details.as_ref()
     }
 
-    pub fn set_abort<T: Into<String>>(&mut self, details: T) -> &mut Self {
+    /// Returns the reason that this transaction was aborted, or None if the current decision is not ABORT.
+    pub fn abort_reason(&self) -> Option<AbortReason> {
+        if !self.current_decision().is_abort() {
+            return None;
+        }
+        Some(self.abort_reason.unwrap_or(AbortReason::Unknown))
+    }
+
+    pub fn set_abort<T: Into<String>>(&mut self, reason: AbortReason, details: T) -> &mut Self {
         self.final_decision = Some(Decision::Abort);
+        self.abort_reason = Some(reason);
         self.abort_details = Some(details.into());
         self
     }
 
-    pub fn set_current_decision_to_abort<T: Into<String>>(&mut self, details: T) -> &mut Self {
+    pub fn set_current_decision_to_abort<T: Into<String>>(&mut self, reason: AbortReason, details: T) -> &mut Self {
+        self.abort_reason = Some(reason);
         self.abort_details = Some(details.into());
         self
     }
@@ -176,6 +228,43 @@ impl TransactionRecord {
     }
 }
 
+/// A transaction record together with its latest execution and transaction pool state, read in a single transaction.
+#[derive(Debug, Clone)]
+pub struct FullTransactionView {
+    pub record: TransactionRecord,
+    pub latest_execution: Option<TransactionExecution>,
+    /// The committed pool stage, or None if the transaction is not in the pool (e.g. it has already been finalized)
+    pub pool_stage: Option<TransactionPoolStage>,
+    /// The evidence held in the pool, or None if the transaction is not in the pool
+    pub evidence: Option<Evidence>,
+    pub final_decision: Option<Decision>,
+}
+
+impl FullTransactionView {
+    pub fn is_in_pool(&self) -> bool {
+        self.pool_stage.is_some()
+    }
+}
+
+/// The differences between the previous and new execution of a re-executed transaction.
+#[derive(Debug, Clone)]
+pub struct TransactionReexecutionDiff {
+    pub previous_decision: Option<Decision>,
+    pub new_decision: Decision,
+    /// Outputs produced by the new execution that were not produced by the previous execution
+    pub outputs_added: Vec<VersionedSubstateId>,
+    /// Outputs produced by the previous execution that are not produced by the new execution
+    pub outputs_removed: Vec<VersionedSubstateId>,
+}
+
+impl TransactionReexecutionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.previous_decision == Some(self.new_decision) &&
+            self.outputs_added.is_empty() &&
+            self.outputs_removed.is_empty()
+    }
+}
+
 impl TransactionRecord {
     pub fn insert<TTx: StateStoreWriteTransaction>(&self, tx: &mut TTx) -> Result<(), StorageError> {
         tx.transactions_insert(self)
@@ -221,6 +310,80 @@ impl TransactionRecord {
         tx.transactions_get(tx_id)
     }
 
+    /// Re-executes a stored transaction against its stored resolved inputs and updates the execution result, execution
+    /// time and outputs of the record. This is intended as a maintenance/recovery tool. Finalized transactions are
+    /// rejected unless `force` is true, in which case the final decision and abort details are replaced to match the
+    /// new result.
+    pub fn reexecute<TTx, F, E>(
+        tx: &mut TTx,
+        tx_id: &TransactionId,
+        force: bool,
+        execute: F,
+    ) -> Result<(Self, TransactionReexecutionDiff), E>
+    where
+        TTx: StateStoreWriteTransaction + Deref,
+        TTx::Target: StateStoreReadTransaction,
+        F: FnOnce(Transaction, &[VersionedSubstateIdLockIntent]) -> Result<ExecutedTransaction, E>,
+        E: From<StorageError>,
+    {
+        let mut rec = Self::get(&**tx, tx_id)?;
+        if rec.is_finalized() && !force {
+            return Err(StorageError::General {
+                details: format!("Transaction {tx_id} is already finalized and cannot be re-executed"),
+            }
+            .into());
+        }
+        let resolved_inputs = rec.resolved_inputs.clone().ok_or_else(|| StorageError::General {
+            details: format!("Transaction {tx_id} has no resolved inputs and cannot be re-executed"),
+        })?;
+
+        let is_finalized = rec.is_finalized();
+        let executed = execute(rec.transaction.clone(), &resolved_inputs)?;
+        let execution_time = executed.execution_time();
+        let (_, result, _, resulting_outputs) = executed.dissolve();
+
+        let diff = TransactionReexecutionDiff {
+            previous_decision: rec.execution_decision(),
+            new_decision: Decision::from(&result.finalize.result),
+            outputs_added: resulting_outputs
+                .iter()
+                .filter(|o| !rec.resulting_outputs.contains(o))
+                .cloned()
+                .collect(),
+            outputs_removed: rec
+                .resulting_outputs
+                .iter()
+                .filter(|o| !resulting_outputs.contains(o))
+                .cloned()
+                .collect(),
+        };
+
+        if is_finalized {
+            rec.final_decision = Some(diff.new_decision);
+            let reject = result.finalize.reject();
+            rec.abort_reason = reject.map(|_| AbortReason::ExecutionFailure);
+            rec.abort_details = reject.map(|reason| reason.to_string());
+        }
+
+        rec.execution_result = Some(result);
+        rec.execution_time = Some(execution_time);
+        rec.resulting_outputs = resulting_outputs;
+        rec.update(tx)?;
+        if is_finalized {
+            // The update leaves the stored abort details unchanged if there are none, so they are cleared explicitly
+            tx.transactions_set_abort_details(tx_id, rec.abort_reason, rec.abort_details.as_deref())?;
+        }
+
+        Ok((rec, diff))
+    }
+
+    pub fn get_full<TTx: StateStoreReadTransaction>(
+        tx: &TTx,
+        tx_id: &TransactionId,
+    ) -> Result<FullTransactionView, StorageError> {
+        tx.transactions_get_full(tx_id)
+    }
+
     pub fn exists<TTx: StateStoreReadTransaction + ?Sized>(
         tx: &TTx,
         tx_id: &TransactionId,
@@ -280,6 +443,29 @@ impl TransactionRecord {
     {
         tx.transactions_finalize_all(block_id, transactions)
     }
+
+    /// Records an abort decided in the given block. The abort is applied to the transaction by
+    /// [TransactionRecord::commit_pending_aborts] once the block is committed.
+    pub fn add_pending_abort<TTx: StateStoreWriteTransaction>(
+        tx: &mut TTx,
+        block_id: &BlockId,
+        block_height: NodeHeight,
+        transaction_id: &TransactionId,
+        reason: AbortReason,
+        details: &str,
+    ) -> Result<(), StorageError> {
+        tx.transactions_add_pending_abort(block_id, block_height, transaction_id, reason, details)
+    }
+
+    /// Applies the aborts that were decided in the given block, now that it is committed. Aborts pending in other
+    /// blocks at or below its height can no longer be committed and are discarded.
+    pub fn commit_pending_aborts<TTx: StateStoreWriteTransaction>(
+        tx: &mut TTx,
+        block_id: &BlockId,
+        block_height: NodeHeight,
+    ) -> Result<(), StorageError> {
+        tx.transactions_commit_pending_aborts(block_id, block_height)
+    }
 }
 
 impl From<ExecutedTransaction> for TransactionRecord {
@@ -288,6 +474,7 @@ impl From<ExecutedTransaction> for TransactionRecord {
         let final_decision = tx.final_decision();
         let finalized_time = tx.finalized_time();
         let abort_details = tx.abort_details().cloned();
+        let abort_reason = tx.abort_reason();
         let (transaction, result, resolved_inputs, resulting_outputs) = tx.dissolve();
 
         Self {
@@ -299,6 +486,136 @@ impl From<ExecutedTransaction> for TransactionRecord {
             finalized_time,
             resulting_outputs,
             abort_details,
+            abort_reason,
+        }
+    }
+}
+
+/// Returns true if the transactions access a common substate and at least one of them writes to it. The resolved
+/// inputs are used if available, otherwise the declared inputs are used and are assumed to be written. Resulting
+/// outputs are always treated as writes.
+pub fn transactions_conflict(a: &TransactionRecord, b: &TransactionRecord) -> bool {
+    let b_accesses = substate_accesses(b);
+    substate_accesses(a)
+        .into_iter()
+        .any(|(id, a_is_write)| b_accesses.get(id).is_some_and(|b_is_write| a_is_write || *b_is_write))
+}
+
+/// Returns the substates accessed by the transaction, mapped to whether the access is a write.
+fn substate_accesses(transaction: &TransactionRecord) -> HashMap<&SubstateId, bool> {
+    let mut accesses = HashMap::new();
+    match transaction.resolved_inputs() {
+        Some(inputs) => {
+            for input in inputs {
+                let is_write = accesses.entry(input.versioned_substate_id().substate_id()).or_default();
+                *is_write |= !input.lock_flag().is_read();
+            }
+        },
+        None => {
+            for input in transaction.transaction().inputs() {
+                accesses.insert(input.substate_id(), true);
+            }
+        },
+    }
+    for output in transaction.resulting_outputs() {
+        accesses.insert(output.substate_id(), true);
+    }
+    accesses
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tari_transaction::SubstateRequirement;
+
+    use super::*;
+
+    fn create_versioned_substate_id(n: u32) -> VersionedSubstateId {
+        VersionedSubstateId::new(SubstateId::from_str(&format!("component_{n:056x}")).unwrap(), 0)
+    }
+
+    #[test]
+    fn it_filters_resolved_inputs_by_lock_flag() {
+        let mut record = TransactionRecord::new(Transaction::builder().build());
+        assert!(record.write_lock_inputs().is_empty());
+        assert!(record.read_lock_inputs().is_empty());
+
+        record.resolved_inputs = Some(vec![
+            VersionedSubstateIdLockIntent::write(create_versioned_substate_id(1)),
+            VersionedSubstateIdLockIntent::read(create_versioned_substate_id(2)),
+            VersionedSubstateIdLockIntent::write(create_versioned_substate_id(3)),
+        ]);
+        assert_eq!(record.write_lock_inputs(), vec![
+            &create_versioned_substate_id(1),
+            &create_versioned_substate_id(3)
+        ]);
+        assert_eq!(record.read_lock_inputs(), vec![&create_versioned_substate_id(2)]);
+    }
+
+    mod transactions_conflict {
+        use super::*;
+
+        fn create_record(inputs: Vec<VersionedSubstateIdLockIntent>) -> TransactionRecord {
+            let mut record = TransactionRecord::new(Transaction::builder().build());
+            record.resolved_inputs = Some(inputs);
+            record
+        }
+
+        #[test]
+        fn it_does_not_conflict_on_read_read() {
+            let a = create_record(vec![VersionedSubstateIdLockIntent::read(create_versioned_substate_id(
+                1,
+            ))]);
+            let b = create_record(vec![VersionedSubstateIdLockIntent::read(create_versioned_substate_id(
+                1,
+            ))]);
+            assert!(!transactions_conflict(&a, &b));
+            assert!(!transactions_conflict(&b, &a));
+        }
+
+        #[test]
+        fn it_conflicts_on_read_write() {
+            let a = create_record(vec![VersionedSubstateIdLockIntent::read(create_versioned_substate_id(
+                1,
+            ))]);
+            let b = create_record(vec![VersionedSubstateIdLockIntent::write(
+                create_versioned_substate_id(1),
+            )]);
+            assert!(transactions_conflict(&a, &b));
+            assert!(transactions_conflict(&b, &a));
+        }
+
+        #[test]
+        fn it_does_not_conflict_on_disjoint_substates() {
+            let a = create_record(vec![VersionedSubstateIdLockIntent::write(
+                create_versioned_substate_id(1),
+            )]);
+            let mut b = create_record(vec![VersionedSubstateIdLockIntent::write(
+                create_versioned_substate_id(2),
+            )]);
+            assert!(!transactions_conflict(&a, &b));
+
+            // An output of one transaction conflicts with a read of the same substate by the other
+            b.resulting_outputs = vec![create_versioned_substate_id(3)];
+            let c = create_record(vec![VersionedSubstateIdLockIntent::read(create_versioned_substate_id(
+                3,
+            ))]);
+            assert!(transactions_conflict(&b, &c));
+        }
+
+        #[test]
+        fn it_falls_back_to_declared_inputs() {
+            let substate_id = create_versioned_substate_id(1).substate_id().clone();
+            let a = TransactionRecord::new(
+                Transaction::builder()
+                    .with_inputs([SubstateRequirement::new(substate_id, None)])
+                    .build(),
+            );
+            let b = create_record(vec![VersionedSubstateIdLockIntent::read(create_versioned_substate_id(
+                1,
+            ))]);
+            assert!(transactions_conflict(&a, &b));
         }
     }
 }
@@ -72,3 +72,53 @@ impl From<&TransactionResult> for Decision {
         }
     }
 }
+
+/// The reason that a transaction was aborted. The reason is stored alongside the free-form abort details. Aborted
+/// transactions recorded without a reason predate structured reasons and are classified as [AbortReason::Unknown].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "ts", derive(TS), ts(export, export_to = "../../bindings/src/types/"))]
+pub enum AbortReason {
+    /// The transaction has no involved shards after execution
+    NoInvolvedShards,
+    /// The transaction failed validation before it was executed
+    TransactionValidationFailed,
+    /// The transaction could not obtain the substate locks it requires
+    LockConflict,
+    /// The transaction execution was rejected
+    ExecutionFailure,
+    /// The abort was recorded without a structured reason (legacy)
+    Unknown,
+}
+
+impl AbortReason {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            AbortReason::NoInvolvedShards => "NoInvolvedShards",
+            AbortReason::TransactionValidationFailed => "TransactionValidationFailed",
+            AbortReason::LockConflict => "LockConflict",
+            AbortReason::ExecutionFailure => "ExecutionFailure",
+            AbortReason::Unknown => "Unknown",
+        }
+    }
+}
+
+impl Display for AbortReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AbortReason {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NoInvolvedShards" => Ok(AbortReason::NoInvolvedShards),
+            "TransactionValidationFailed" => Ok(AbortReason::TransactionValidationFailed),
+            "LockConflict" => Ok(AbortReason::LockConflict),
+            "ExecutionFailure" => Ok(AbortReason::ExecutionFailure),
+            "Unknown" => Ok(AbortReason::Unknown),
+            _ => Err(()),
+        }
+    }
+}
@@ -581,6 +581,14 @@ impl TransactionPoolRecord {
     }
 }
 
+/// A transaction pool record as stored, together with its most recent pending state update (if any). The update is
+/// not merged into the record, so both the committed and pending state are visible.
+#[derive(Debug, Clone)]
+pub struct TransactionPoolRecordWithUpdate {
+    pub record: TransactionPoolRecord,
+    pub update: Option<TransactionPoolStatusUpdate>,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum TransactionPoolError {
     #[error("Storage error: {0}")]
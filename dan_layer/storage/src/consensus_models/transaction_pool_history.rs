@@ -0,0 +1,22 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_transaction::TransactionId;
+use time::PrimitiveDateTime;
+
+use crate::consensus_models::{Decision, TransactionPoolStage};
+
+/// A snapshot of a transaction pool record, taken whenever the record's stage or readiness changes.
+#[derive(Debug, Clone)]
+pub struct TransactionPoolHistoryEntry {
+    pub transaction_id: TransactionId,
+    pub original_decision: Decision,
+    pub local_decision: Option<Decision>,
+    pub remote_decision: Option<Decision>,
+    pub transaction_fee: Option<u64>,
+    pub stage: TransactionPoolStage,
+    pub new_stage: TransactionPoolStage,
+    pub is_ready: bool,
+    pub new_is_ready: bool,
+    pub change_time: Option<PrimitiveDateTime>,
+}
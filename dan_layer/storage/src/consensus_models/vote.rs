@@ -70,8 +70,9 @@ impl Vote {
 
     pub fn get_for_block<TTx: StateStoreReadTransaction>(
         tx: &TTx,
+        epoch: Epoch,
         block_id: &BlockId,
     ) -> Result<Vec<Self>, StorageError> {
-        tx.votes_get_for_block(block_id)
+        tx.votes_get_for_block(epoch, block_id)
     }
 }
@@ -5,6 +5,7 @@ use std::{
     borrow::Borrow,
     collections::{HashMap, HashSet},
     ops::{Deref, RangeInclusive},
+    time::Duration,
 };
 
 use indexmap::IndexMap;
@@ -19,7 +20,9 @@ use ts_rs::TS;
 
 use crate::{
     consensus_models::{
+        AbortReason,
         Block,
+        BlockContext,
         BlockDiff,
         BlockId,
         Decision,
@@ -28,6 +31,7 @@ use crate::{
         ForeignProposal,
         ForeignReceiveCounters,
         ForeignSendCounters,
+        FullTransactionView,
         HighQc,
         LastExecuted,
         LastProposed,
@@ -39,17 +43,22 @@ use crate::{
         PendingShardStateTreeDiff,
         QcId,
         QuorumCertificate,
+        RejectedBlock,
         StateTransition,
         StateTransitionId,
         SubstateChange,
+        SubstateLockFlag,
         SubstateRecord,
         TransactionAtom,
         TransactionExecution,
+        TransactionPoolHistoryEntry,
         TransactionPoolRecord,
+        TransactionPoolRecordWithUpdate,
         TransactionPoolStage,
         TransactionPoolStatusUpdate,
         TransactionRecord,
         VersionedStateHashTreeDiff,
+        VersionedSubstateIdLockIntent,
         Vote,
     },
     StorageError,
@@ -92,9 +101,15 @@ pub trait StateStore {
     }
 }
 
+/// Read operations on the state store.
+///
+/// Methods named `*_stream_after` return an iterator that reads rows from a database cursor as it is advanced, so
+/// memory use stays bounded regardless of the size of the result set. They borrow the transaction mutably so that it
+/// cannot be used for other queries until the iterator is dropped. All other methods collect their results into memory
+/// before returning.
 pub trait StateStoreReadTransaction: Sized {
     type Addr: NodeAddressable;
-    fn last_sent_vote_get(&self) -> Result<LastSentVote, StorageError>;
+    fn last_sent_vote_get(&self) -> Result<Option<LastSentVote>, StorageError>;
     fn last_voted_get(&self) -> Result<LastVoted, StorageError>;
     fn last_executed_get(&self) -> Result<LastExecuted, StorageError>;
     fn last_proposed_get(&self) -> Result<LastProposed, StorageError>;
@@ -113,18 +128,60 @@ pub trait StateStoreReadTransaction: Sized {
     fn foreign_receive_counters_get(&self) -> Result<ForeignReceiveCounters, StorageError>;
     fn transactions_get(&self, tx_id: &TransactionId) -> Result<TransactionRecord, StorageError>;
     fn transactions_exists(&self, tx_id: &TransactionId) -> Result<bool, StorageError>;
+    /// Returns the transaction record along with its latest execution and pool state. The transaction pool fields are
+    /// None if the transaction is no longer in the pool.
+    fn transactions_get_full(&self, tx_id: &TransactionId) -> Result<FullTransactionView, StorageError>;
 
     fn transactions_get_any<'a, I: IntoIterator<Item = &'a TransactionId>>(
         &self,
         tx_ids: I,
     ) -> Result<Vec<TransactionRecord>, StorageError>;
+    /// Returns all transactions that list the given substate in their resulting outputs
+    fn transactions_get_by_output(&self, output: &VersionedSubstateId) -> Result<Vec<TransactionRecord>, StorageError>;
+    /// Returns all transactions that list any version of the given substate in their resulting outputs, in insertion
+    /// order. This uses the indexed output table rather than scanning the resulting_outputs JSON of every transaction.
+    fn transactions_get_by_output_substate(
+        &self,
+        substate_id: &SubstateId,
+    ) -> Result<Vec<TransactionRecord>, StorageError>;
+    /// Returns the ids of all transactions that have resolved the given substate as an input
+    fn transactions_get_ids_by_input(&self, substate_id: &SubstateId) -> Result<Vec<TransactionId>, StorageError>;
+    fn transaction_inputs_get_all(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Vec<VersionedSubstateIdLockIntent>, StorageError>;
     fn transactions_get_paginated(
         &self,
         limit: u64,
         offset: u64,
         asc_desc_created_at: Option<Ordering>,
     ) -> Result<Vec<TransactionRecord>, StorageError>;
+    /// Returns up to `limit` transactions in insertion order (or reverse insertion order if descending) that come after
+    /// the transaction `after_id`, or from the start if None. Unlike [Self::transactions_get_paginated], pages are not
+    /// shifted by inserts between requests and deep pages are as cheap as the first. Returns a NotFound error if
+    /// `after_id` does not exist.
+    fn transactions_get_after(
+        &self,
+        after_id: Option<&TransactionId>,
+        limit: u64,
+        ordering: Ordering,
+    ) -> Result<Vec<TransactionRecord>, StorageError>;
 
+    /// Returns finalized transactions with the given decision. If an epoch is provided, only transactions whose epoch
+    /// bounds include that epoch are returned.
+    fn transactions_get_by_decision(
+        &self,
+        decision: Decision,
+        epoch: Option<Epoch>,
+        limit: u64,
+        offset: u64,
+    ) -> Result<Vec<TransactionRecord>, StorageError>;
+    /// Counts aborted transactions grouped by abort reason, optionally limited to transactions finalized by a block
+    /// in the given epoch. Aborts recorded without a reason are counted under [AbortReason::Unknown].
+    fn transactions_count_by_abort_reason(
+        &self,
+        epoch: Option<Epoch>,
+    ) -> Result<HashMap<AbortReason, u64>, StorageError>;
     fn transaction_executions_get(
         &self,
         tx_id: &TransactionId,
@@ -136,7 +193,22 @@ pub trait StateStoreReadTransaction: Sized {
         tx_id: &TransactionId,
         from_block_id: &BlockId,
     ) -> Result<TransactionExecution, StorageError>;
+    /// Returns all transaction executions recorded for the given block, ordered by transaction id.
+    fn transaction_executions_get_for_block(
+        &self,
+        block_id: &BlockId,
+    ) -> Result<Vec<TransactionExecution>, StorageError>;
+    /// Returns the execution of the transaction in the highest block, regardless of which chain that block is on.
+    /// Executions in the same block are ordered by insertion, most recent first. Only executions for blocks that have
+    /// been stored are considered. Returns None if the transaction has no such executions.
+    fn transaction_executions_get_latest(
+        &self,
+        tx_id: &TransactionId,
+    ) -> Result<Option<TransactionExecution>, StorageError>;
     fn blocks_get(&self, block_id: &BlockId) -> Result<Block, StorageError>;
+    /// Returns the block together with the transaction records referenced by its commands. Returns NotFound if the
+    /// block does not exist.
+    fn blocks_get_full_context(&self, block_id: &BlockId) -> Result<BlockContext, StorageError>;
     fn blocks_get_last_n_in_epoch(&self, n: usize, epoch: Epoch) -> Result<Vec<Block>, StorageError>;
     /// Returns all blocks from and excluding the start block (lower height) to the end block (inclusive)
     fn blocks_get_all_between(
@@ -148,6 +220,7 @@ pub trait StateStoreReadTransaction: Sized {
         include_dummy_blocks: bool,
     ) -> Result<Vec<Block>, StorageError>;
     fn blocks_exists(&self, block_id: &BlockId) -> Result<bool, StorageError>;
+    fn blocks_get_foreign_indexes(&self, block_id: &BlockId) -> Result<IndexMap<Shard, u64>, StorageError>;
     fn blocks_is_ancestor(&self, descendant: &BlockId, ancestor: &BlockId) -> Result<bool, StorageError>;
     fn blocks_get_all_by_parent(&self, parent: &BlockId) -> Result<Vec<Block>, StorageError>;
     fn blocks_get_parent_chain(&self, block_id: &BlockId, limit: usize) -> Result<Vec<Block>, StorageError>;
@@ -157,6 +230,9 @@ pub trait StateStoreReadTransaction: Sized {
         epoch: Epoch,
         validator_public_key: &PublicKey,
     ) -> Result<u64, StorageError>;
+    /// Returns the total leader fee earned by each proposer (keyed by hex-encoded public key) for committed, non-dummy
+    /// blocks in the given epoch.
+    fn blocks_sum_leader_fees_by_proposer(&self, epoch: Epoch) -> Result<HashMap<String, u64>, StorageError>;
     fn blocks_get_any_with_epoch_range(
         &self,
         epoch_range: RangeInclusive<Epoch>,
@@ -171,6 +247,27 @@ pub trait StateStoreReadTransaction: Sized {
         ordering_index: Option<usize>,
         ordering: Option<Ordering>,
     ) -> Result<Vec<Block>, StorageError>;
+    /// Returns blocks in the given epoch ordered by height. An empty Vec is returned once `offset` is past the last
+    /// block of the epoch.
+    fn blocks_get_paginated_by_epoch(&self, epoch: Epoch, limit: u64, offset: u64) -> Result<Vec<Block>, StorageError>;
+    /// Returns blocks for the given shard group ordered by epoch and height. An empty Vec is returned if there are no
+    /// blocks for the shard group.
+    fn blocks_get_paginated_by_shard_group(
+        &self,
+        shard_group: ShardGroup,
+        limit: u64,
+        offset: u64,
+        ordering: Ordering,
+    ) -> Result<Vec<Block>, StorageError>;
+    /// Returns up to `n` of the most recent blocks across all shard groups, ordered by height and then timestamp,
+    /// highest first. Dummy blocks are excluded unless `include_dummy` is true; they can be identified with
+    /// [Block::is_dummy].
+    fn blocks_get_tip_n(&self, n: u64, include_dummy: bool) -> Result<Vec<Block>, StorageError>;
+    /// Returns the highest committed block, or None if no block has been committed.
+    fn blocks_get_commit_tip(&self) -> Result<Option<Block>, StorageError>;
+    /// Returns the non-dummy block with the timestamp closest to the given unix timestamp, or None if there are no
+    /// blocks. If two blocks are equally close, the earlier one is returned.
+    fn blocks_get_nearest_to_timestamp(&self, timestamp: u64) -> Result<Option<Block>, StorageError>;
     fn blocks_get_count(&self) -> Result<i64, StorageError>;
 
     fn filtered_blocks_get_count(
@@ -188,6 +285,8 @@ pub trait StateStoreReadTransaction: Sized {
     ) -> Result<SubstateChange, StorageError>;
 
     fn parked_blocks_exists(&self, block_id: &BlockId) -> Result<bool, StorageError>;
+    /// Returns the distinct ids of parked blocks that are still waiting on the given transaction
+    fn parked_blocks_waiting_on(&self, transaction_id: &TransactionId) -> Result<Vec<BlockId>, StorageError>;
 
     // -------------------------------- QuorumCertificate -------------------------------- //
     fn quorum_certificates_get(&self, qc_id: &QcId) -> Result<QuorumCertificate, StorageError>;
@@ -196,6 +295,15 @@ pub trait StateStoreReadTransaction: Sized {
         qc_ids: I,
     ) -> Result<Vec<QuorumCertificate>, StorageError>;
     fn quorum_certificates_get_by_block_id(&self, block_id: &BlockId) -> Result<QuorumCertificate, StorageError>;
+    fn quorum_certificates_get_signature_count(&self, qc_id: &QcId) -> Result<usize, StorageError>;
+    /// Walks the justify chain starting at `from_block`, returning the QC justifying each block followed by the QC
+    /// justifying the block it certifies, and so on. The walk ends after the zero QC is returned or once `max_depth`
+    /// QCs have been returned.
+    fn quorum_certificates_get_chain(
+        &self,
+        from_block: &BlockId,
+        max_depth: usize,
+    ) -> Result<Vec<QuorumCertificate>, StorageError>;
 
     // -------------------------------- Transaction Pools -------------------------------- //
     fn transaction_pool_get_for_blocks(
@@ -206,6 +314,9 @@ pub trait StateStoreReadTransaction: Sized {
     ) -> Result<TransactionPoolRecord, StorageError>;
     fn transaction_pool_exists(&self, transaction_id: &TransactionId) -> Result<bool, StorageError>;
     fn transaction_pool_get_all(&self) -> Result<Vec<TransactionPoolRecord>, StorageError>;
+    /// Returns every transaction pool record joined with its latest pending state update, read in a single query so
+    /// that the result is a consistent snapshot of the pool.
+    fn transaction_pool_dump(&self) -> Result<Vec<TransactionPoolRecordWithUpdate>, StorageError>;
     fn transaction_pool_get_many_ready(&self, max_txs: usize) -> Result<Vec<TransactionPoolRecord>, StorageError>;
     fn transaction_pool_count(
         &self,
@@ -213,6 +324,14 @@ pub trait StateStoreReadTransaction: Sized {
         is_ready: Option<bool>,
         has_foreign_data: Option<bool>,
     ) -> Result<usize, StorageError>;
+    /// Returns the pool history entries for the given transactions, oldest first.
+    fn transaction_pool_history_get_for_transactions<'a, I: IntoIterator<Item = &'a TransactionId>>(
+        &self,
+        tx_ids: I,
+    ) -> Result<Vec<TransactionPoolHistoryEntry>, StorageError>;
+    /// Returns the ids of transaction pool entries that do not have a corresponding transaction record. A non-empty
+    /// result indicates an invariant violation.
+    fn check_pool_transaction_consistency(&self) -> Result<Vec<TransactionId>, StorageError>;
 
     fn transactions_fetch_involved_shards(
         &self,
@@ -226,9 +345,21 @@ pub trait StateStoreReadTransaction: Sized {
         sender_leaf_hash: &FixedHash,
     ) -> Result<Vote, StorageError>;
     fn votes_count_for_block(&self, block_id: &BlockId) -> Result<u64, StorageError>;
-    fn votes_get_for_block(&self, block_id: &BlockId) -> Result<Vec<Vote>, StorageError>;
+    /// Returns the votes for the block in the given epoch. If a sender voted more than once, only their latest vote is
+    /// returned.
+    fn votes_get_for_block(&self, epoch: Epoch, block_id: &BlockId) -> Result<Vec<Vote>, StorageError>;
+    // -------------------------------- Rejected blocks -------------------------------- //
+    /// Returns up to `limit` of the most recently rejected blocks, most recent first.
+    fn rejected_blocks_get_recent(&self, limit: u64) -> Result<Vec<RejectedBlock>, StorageError>;
     //---------------------------------- Substates --------------------------------------------//
     fn substates_get(&self, substate_id: &SubstateAddress) -> Result<SubstateRecord, StorageError>;
+    /// Returns the given version of the substate, whether or not it has since been destroyed, or None if that version
+    /// was never recorded.
+    fn substates_get_at_version(
+        &self,
+        substate_id: &SubstateId,
+        version: u32,
+    ) -> Result<Option<SubstateRecord>, StorageError>;
     fn substates_get_any(
         &self,
         substate_ids: &HashSet<SubstateRequirement>,
@@ -245,6 +376,12 @@ pub trait StateStoreReadTransaction: Sized {
     fn substates_exists_for_transaction(&self, transaction_id: &TransactionId) -> Result<bool, StorageError>;
 
     fn substates_get_n_after(&self, n: usize, after: &SubstateAddress) -> Result<Vec<SubstateRecord>, StorageError>;
+    /// Streams all substates after the given substate address. See [StateStoreReadTransaction] for streaming
+    /// semantics.
+    fn substates_stream_after(
+        &mut self,
+        after: &SubstateAddress,
+    ) -> Result<impl Iterator<Item = Result<SubstateRecord, StorageError>> + '_, StorageError>;
 
     fn substates_get_many_within_range(
         &self,
@@ -252,11 +389,13 @@ pub trait StateStoreReadTransaction: Sized {
         end: &SubstateAddress,
         exclude_shards: &[SubstateAddress],
     ) -> Result<Vec<SubstateRecord>, StorageError>;
+    /// Returns the substates created (UP) by the given transaction, ordered by substate id.
     fn substates_get_many_by_created_transaction(
         &self,
         tx_id: &TransactionId,
     ) -> Result<Vec<SubstateRecord>, StorageError>;
 
+    /// Returns the substates destroyed (DOWN) by the given transaction, ordered by substate id.
     fn substates_get_many_by_destroyed_transaction(
         &self,
         tx_id: &TransactionId,
@@ -265,6 +404,12 @@ pub trait StateStoreReadTransaction: Sized {
         &self,
         transaction_id: &TransactionId,
     ) -> Result<Vec<SubstateRecord>, StorageError>;
+    /// Returns the total size in bytes of the stored substate data, optionally only for substates created by the given
+    /// shard. Destroyed substates are only counted if `include_destroyed` is true.
+    fn substates_total_data_bytes(&self, shard: Option<Shard>, include_destroyed: bool) -> Result<u64, StorageError>;
+    /// Integrity scan that recomputes the substate address of every stored (substate_id, version) and returns the ids
+    /// of the substates whose stored address does not match.
+    fn substates_verify_addresses(&self) -> Result<Vec<SubstateId>, StorageError>;
 
     fn substate_locks_get_all_for_block(
         &self,
@@ -272,6 +417,15 @@ pub trait StateStoreReadTransaction: Sized {
     ) -> Result<IndexMap<SubstateId, Vec<LockedSubstate>>, StorageError>;
 
     fn substate_locks_get_latest_for_substate(&self, substate_id: &SubstateId) -> Result<LockedSubstate, StorageError>;
+    /// Returns the existing locks on the substate that conflict with the lock requested by the given transaction. Two
+    /// locks held by different transactions conflict unless both are read locks. Locks held by the requesting
+    /// transaction itself are never returned.
+    fn substate_locks_get_conflicting(
+        &self,
+        substate_id: &SubstateId,
+        transaction_id: &TransactionId,
+        requested: SubstateLockFlag,
+    ) -> Result<Vec<LockedSubstate>, StorageError>;
 
     fn pending_state_tree_diffs_get_all_up_to_commit_block(
         &self,
@@ -284,11 +438,28 @@ pub trait StateStoreReadTransaction: Sized {
         id: StateTransitionId,
         end_epoch: Epoch,
     ) -> Result<Vec<StateTransition>, StorageError>;
+    /// Streams all state transitions from the given id up to (but excluding) `end_epoch`. See
+    /// [StateStoreReadTransaction] for streaming semantics.
+    fn state_transitions_stream_after(
+        &mut self,
+        id: StateTransitionId,
+        end_epoch: Epoch,
+    ) -> Result<impl Iterator<Item = Result<StateTransition, StorageError>> + '_, StorageError>;
 
     fn state_transitions_get_last_id(&self, shard: Shard) -> Result<StateTransitionId, StorageError>;
+    /// Returns the inclusive ranges of sequence numbers missing from the state transitions of the shard. Sequence
+    /// numbers start at 0, so an empty result means that the shard's state transitions are contiguous.
+    fn state_transitions_find_gaps(&self, shard: Shard) -> Result<Vec<(i64, i64)>, StorageError>;
 
     fn state_tree_nodes_get(&self, shard: Shard, key: &NodeKey) -> Result<Node<Version>, StorageError>;
     fn state_tree_versions_get_latest(&self, shard: Shard) -> Result<Option<Version>, StorageError>;
+    /// Returns the latest state tree version for each of the given shards using a single query. Shards that do not
+    /// have a state tree version are not included in the result.
+    fn state_tree_versions_get_latest_many(&self, shards: &[Shard]) -> Result<HashMap<Shard, Version>, StorageError>;
+    /// Checks that the root node for the latest recorded state tree version of the shard exists and is not stale.
+    /// Returns false if the version and the tree nodes have drifted apart. A shard with no recorded version is
+    /// consistent.
+    fn state_tree_verify_shard_version(&self, shard: Shard) -> Result<bool, StorageError>;
 
     // -------------------------------- Epoch checkpoint -------------------------------- //
     fn epoch_checkpoint_get(&self, epoch: Epoch) -> Result<EpochCheckpoint, StorageError>;
@@ -318,6 +489,7 @@ pub trait StateStoreWriteTransaction {
 
     // -------------------------------- Bookkeeping -------------------------------- //
     fn last_sent_vote_set(&mut self, last_sent_vote: &LastSentVote) -> Result<(), StorageError>;
+    fn last_sent_vote_clear(&mut self) -> Result<(), StorageError>;
     fn last_voted_set(&mut self, last_voted: &LastVoted) -> Result<(), StorageError>;
     fn last_votes_unset(&mut self, last_voted: &LastVoted) -> Result<(), StorageError>;
     fn last_executed_set(&mut self, last_exec: &LastExecuted) -> Result<(), StorageError>;
@@ -328,6 +500,8 @@ pub trait StateStoreWriteTransaction {
     fn high_qc_set(&mut self, high_qc: &HighQc) -> Result<(), StorageError>;
     fn foreign_proposal_upsert(&mut self, foreign_proposal: &ForeignProposal) -> Result<(), StorageError>;
     fn foreign_proposal_delete(&mut self, foreign_proposal: &ForeignProposal) -> Result<(), StorageError>;
+    /// Deletes all foreign proposals with a base layer block height below `height`, returning the number deleted.
+    fn foreign_proposals_delete_below_base_layer_height(&mut self, height: u64) -> Result<usize, StorageError>;
     fn foreign_send_counters_set(
         &mut self,
         foreign_send_counter: &ForeignSendCounters,
@@ -351,6 +525,33 @@ pub trait StateStoreWriteTransaction {
         block_id: BlockId,
         transaction: I,
     ) -> Result<(), StorageError>;
+    /// Sets the abort reason and details of a transaction, or clears them if None.
+    fn transactions_set_abort_details(
+        &mut self,
+        tx_id: &TransactionId,
+        abort_reason: Option<AbortReason>,
+        abort_details: Option<&str>,
+    ) -> Result<(), StorageError>;
+    /// Records an abort that was decided in a block. It is replaced if one was already recorded for the transaction in
+    /// the block.
+    fn transactions_add_pending_abort(
+        &mut self,
+        block_id: &BlockId,
+        block_height: NodeHeight,
+        tx_id: &TransactionId,
+        abort_reason: AbortReason,
+        abort_details: &str,
+    ) -> Result<(), StorageError>;
+    /// Sets the abort reason and details of each transaction with a pending abort in the committed block. All pending
+    /// aborts at or below the block's height are removed, since blocks on other forks at those heights cannot commit.
+    fn transactions_commit_pending_aborts(
+        &mut self,
+        block_id: &BlockId,
+        block_height: NodeHeight,
+    ) -> Result<(), StorageError>;
+    /// Removes a transaction and all of its associated pool, pool state update, execution, input and output rows.
+    /// Either all rows are removed or, if an error occurs, none are.
+    fn transactions_purge(&mut self, tx_id: &TransactionId) -> Result<(), StorageError>;
     // -------------------------------- Transaction Executions -------------------------------- //
     fn transaction_executions_insert_or_ignore(
         &mut self,
@@ -359,6 +560,12 @@ pub trait StateStoreWriteTransaction {
 
     // -------------------------------- Transaction Pool -------------------------------- //
     fn transaction_pool_insert_new(&mut self, tx_id: TransactionId, decision: Decision) -> Result<(), StorageError>;
+    /// Inserts all of the transactions into the pool at the New stage. Transactions that are already in the pool are
+    /// left unchanged.
+    fn transaction_pool_insert_new_batch<I: IntoIterator<Item = (TransactionId, Decision)>>(
+        &mut self,
+        items: I,
+    ) -> Result<(), StorageError>;
     fn transaction_pool_set_atom(&mut self, transaction: TransactionAtom) -> Result<(), StorageError>;
     fn transaction_pool_add_pending_update(
         &mut self,
@@ -383,6 +590,17 @@ pub trait StateStoreWriteTransaction {
         new_locked_block: &LockedBlock,
         tx_ids: I,
     ) -> Result<(), StorageError>;
+    /// Deletes transaction pool history rows that were recorded more than `older_than` ago, returning the number of
+    /// rows removed. The transaction pool itself is not affected.
+    fn transaction_pool_history_prune(&mut self, older_than: Duration) -> Result<usize, StorageError>;
+    /// Removes the pending state updates for blocks that are not in `valid_block_ids`, for example after a reorg has
+    /// orphaned them. The readiness and pending stage of each affected transaction are recomputed from its most recent
+    /// remaining update. If no updates remain, the pending stage is cleared and the readiness is left unchanged.
+    /// Returns the number of updates removed.
+    fn transaction_pool_prune_orphaned_updates(
+        &mut self,
+        valid_block_ids: &HashSet<BlockId>,
+    ) -> Result<usize, StorageError>;
 
     fn missing_transactions_insert<
         'a,
@@ -403,6 +621,18 @@ pub trait StateStoreWriteTransaction {
 
     // -------------------------------- Votes -------------------------------- //
     fn votes_insert(&mut self, vote: &Vote) -> Result<(), StorageError>;
+    /// Inserts the votes in batches. Votes that have already been inserted are skipped.
+    fn votes_insert_all<'a, I: IntoIterator<Item = &'a Vote>>(&mut self, votes: I) -> Result<(), StorageError>;
+
+    // -------------------------------- Rejected blocks -------------------------------- //
+    /// Records a block proposal that failed validation. A block that has already been recorded is ignored, and only the
+    /// most recently rejected blocks are retained.
+    fn rejected_blocks_insert(
+        &mut self,
+        block_id: &BlockId,
+        height: NodeHeight,
+        reason: &str,
+    ) -> Result<(), StorageError>;
 
     //---------------------------------- Substates --------------------------------------------//
     fn substate_locks_insert_all<I: IntoIterator<Item = (SubstateId, Vec<LockedSubstate>)>>(
@@ -416,7 +646,20 @@ pub trait StateStoreWriteTransaction {
         transaction_ids: I,
     ) -> Result<(), StorageError>;
 
-    fn substates_create(&mut self, substate: SubstateRecord) -> Result<(), StorageError>;
+    /// Removes locks that were leaked by transactions that left the pool without releasing them, i.e. locks owned by
+    /// a block at or below the height of `committed_up_to` whose transaction is no longer in the pool. Returns the
+    /// number of locks removed.
+    fn substate_locks_remove_expired(&mut self, committed_up_to: BlockId) -> Result<usize, StorageError>;
+
+    /// Creates the substate and records an UP state transition. `state_version` is the current state tree version of
+    /// the substate's shard, and is read from the store if None.
+    fn substates_create(
+        &mut self,
+        substate: SubstateRecord,
+        state_version: Option<Version>,
+    ) -> Result<(), StorageError>;
+    /// Marks the substate as destroyed and records a DOWN state transition. `state_version` is the current state tree
+    /// version of the shard, and is read from the store if None.
     fn substates_down(
         &mut self,
         versioned_substate_id: VersionedSubstateId,
@@ -425,6 +668,7 @@ pub trait StateStoreWriteTransaction {
         destroyed_block_height: NodeHeight,
         destroyed_transaction_id: &TransactionId,
         destroyed_qc_id: &QcId,
+        state_version: Option<Version>,
     ) -> Result<(), StorageError>;
 
     // -------------------------------- Pending State Tree Diffs -------------------------------- //
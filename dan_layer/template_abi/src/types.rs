@@ -45,6 +45,12 @@ impl TemplateDef {
         }
     }
 
+    pub fn version(&self) -> u32 {
+        match self {
+            TemplateDef::V1(def) => def.version,
+        }
+    }
+
     pub fn get_function(&self, name: &str) -> Option<&FunctionDef> {
         match self {
             TemplateDef::V1(def) => def.get_function(name),
@@ -63,6 +69,10 @@ impl TemplateDef {
 pub struct TemplateDefV1 {
     pub template_name: String,
     pub tari_version: String,
+    /// The ABI version of the template, as given by `#[template(version = N)]`. Templates built before this field was
+    /// added decode as version 0.
+    #[serde(default)]
+    pub version: u32,
     pub functions: Vec<FunctionDef>,
 }
 
@@ -79,6 +89,10 @@ pub struct FunctionDef {
     pub arguments: Vec<ArgDef>,
     pub output: Type,
     pub is_mut: bool,
+    /// True if the function does not take a `self` receiver i.e. it is called on the template rather than on a
+    /// component. Templates built before this field was added decode as false.
+    #[serde(default)]
+    pub is_constructor: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,6 +120,7 @@ pub enum Type {
     U128,
     String,
     Vec(Box<Type>),
+    Option(Box<Type>),
     Tuple(Vec<Type>),
     Other {
         name: String,
@@ -139,6 +154,7 @@ impl std::fmt::Display for Type {
             Type::U128 => write!(f, "U128"),
             Type::String => write!(f, "String"),
             Type::Vec(t) => write!(f, "Vec<{}>", t),
+            Type::Option(t) => write!(f, "Option<{}>", t),
             Type::Tuple(types) => {
                 let type_list = types.iter().map(|t| format!("{:?}", t)).collect::<Vec<_>>().join(",");
                 write!(f, "Tuple<{}>", type_list)
@@ -109,6 +109,17 @@ impl Display for LogLevelParseError {
 
 impl std::error::Error for LogLevelParseError {}
 
+// -------------------------------- Call arguments -------------------------------- //
+
+/// Reports an argument passed to a template function that could not be decoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvalidCallArgumentArg {
+    pub function: String,
+    pub arg_index: usize,
+    pub expected_type: String,
+    pub details: String,
+}
+
 // -------------------------------- Component -------------------------------- //
 
 /// An operation over a component
@@ -25,7 +25,16 @@ use tari_bor::to_value;
 use tari_template_abi::{call_engine, EngineOp};
 
 use crate::{
-    args::{ComponentAction, ComponentInvokeArg, ComponentRef, CreateComponentArg, EmitLogArg, InvokeResult, LogLevel},
+    args::{
+        ComponentAction,
+        ComponentInvokeArg,
+        ComponentRef,
+        CreateComponentArg,
+        EmitLogArg,
+        InvalidCallArgumentArg,
+        InvokeResult,
+        LogLevel,
+    },
     auth::OwnerRule,
     component::ComponentManager,
     context::Context,
@@ -80,6 +89,24 @@ impl TariEngine {
         });
     }
 
+    /// Reports that the argument at `arg_index` of `function` could not be decoded as `expected_type`. The engine fails
+    /// the call with an error carrying these details, so this function never returns.
+    pub fn invalid_call_argument<T: ToString>(
+        &self,
+        function: &str,
+        arg_index: usize,
+        expected_type: &str,
+        details: T,
+    ) -> ! {
+        call_engine::<_, ()>(EngineOp::InvalidCallArgument, &InvalidCallArgumentArg {
+            function: function.to_string(),
+            arg_index,
+            expected_type: expected_type.to_string(),
+            details: details.to_string(),
+        });
+        unreachable!("engine did not fail the call for an invalid argument")
+    }
+
     pub fn component_manager(&self, component_address: ComponentAddress) -> ComponentManager {
         ComponentManager::new(component_address)
     }
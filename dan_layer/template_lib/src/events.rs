@@ -26,7 +26,11 @@ use tari_template_abi::{call_engine, EngineOp};
 
 use crate::{args::EmitEventArg, models::Metadata};
 
-/// Requests the engine to emit an event that will be permanently recorded in the transaction result
+/// Requests the engine to emit an event that will be permanently recorded in the transaction result.
+///
+/// The engine fills in the address of the calling template, the address of the current component (if called from a
+/// component method) and the transaction hash, so the recorded event is the same `Event` that the indexer scans.
+/// Topics may not start with the prefix reserved for events emitted by the engine itself.
 pub fn emit_event<T: Into<String>, P: Into<Metadata>>(topic: T, payload: P) {
     call_engine::<_, ()>(EngineOp::EmitEvent, &EmitEventArg {
         topic: topic.into(),
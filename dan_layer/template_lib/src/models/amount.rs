@@ -82,7 +82,7 @@ impl Amount {
     }
 
     pub fn saturating_sub_positive(&self, other: Self) -> Self {
-        let amount = Amount(self.0 - other.0);
+        let amount = self.saturating_sub(other);
         if amount.is_negative() {
             Amount(0)
         } else {
@@ -258,6 +258,29 @@ mod tests {
         assert_eq!(f, 1i64);
     }
 
+    #[test]
+    fn checked_arithmetic_boundaries() {
+        assert_eq!(Amount::MAX.checked_add(Amount(1)), None);
+        assert_eq!(Amount::MAX.checked_add(Amount(0)), Some(Amount::MAX));
+        assert_eq!(Amount(i64::MIN).checked_sub(Amount(1)), None);
+        // Amounts are signed, so going below zero is not an overflow
+        assert_eq!(Amount::zero().checked_sub(Amount(1)), Some(Amount(-1)));
+        assert_eq!(Amount::zero().checked_sub_positive(Amount(1)), None);
+        assert_eq!(Amount::MAX.checked_mul(&Amount(2)), None);
+        assert_eq!(Amount::MAX.checked_mul(&Amount(1)), Some(Amount::MAX));
+    }
+
+    #[test]
+    fn saturating_arithmetic_boundaries() {
+        assert_eq!(Amount::MAX.saturating_add(Amount(1)), Amount::MAX);
+        assert_eq!(Amount(i64::MIN).saturating_sub(Amount(1)), Amount(i64::MIN));
+        assert_eq!(Amount::zero().saturating_sub(Amount(1)), Amount(-1));
+        assert_eq!(Amount::zero().saturating_sub_positive(Amount(1)), Amount::zero());
+        assert_eq!(Amount(i64::MIN).saturating_sub_positive(Amount(1)), Amount::zero());
+        assert_eq!(Amount::zero().saturating_sub_positive(Amount(i64::MIN)), Amount::MAX);
+        assert_eq!(Amount::MAX.saturating_mul(&Amount(2)), Amount::MAX);
+    }
+
     #[test]
     fn can_serialize() {
         let a = Amount(4);
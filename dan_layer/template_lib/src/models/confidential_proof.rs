@@ -1,7 +1,10 @@
 //   Copyright 2023 The Tari Project
 //   SPDX-License-Identifier: BSD-3-Clause
 
-use std::mem::size_of;
+use std::{
+    fmt::{self, Display},
+    mem::size_of,
+};
 
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, Bytes};
@@ -42,6 +45,34 @@ impl ConfidentialOutputStatement {
             change_revealed_amount: Amount::zero(),
         }
     }
+
+    /// Creates an output proof that transfers the output to the receiver and returns change to the sender.
+    pub fn with_change(
+        output: ConfidentialStatement,
+        change: ConfidentialStatement,
+        range_proof: Vec<u8>,
+        output_revealed_amount: Amount,
+        change_revealed_amount: Amount,
+    ) -> Self {
+        Self {
+            output_statement: Some(output),
+            change_statement: Some(change),
+            range_proof,
+            output_revealed_amount,
+            change_revealed_amount,
+        }
+    }
+
+    /// Creates an output proof that transfers the output to the receiver without returning any change to the sender.
+    pub fn without_change(output: ConfidentialStatement, range_proof: Vec<u8>, output_revealed_amount: Amount) -> Self {
+        Self {
+            output_statement: Some(output),
+            change_statement: None,
+            range_proof,
+            output_revealed_amount,
+            change_revealed_amount: Amount::zero(),
+        }
+    }
 }
 
 /// A zero-knowledge proof that a confidential resource amount is valid
@@ -62,6 +93,42 @@ pub struct ConfidentialStatement {
     pub viewable_balance_proof: Option<ViewableBalanceProof>,
 }
 
+impl ConfidentialStatement {
+    /// Checks that the minimum value promised by the statement is at least `min`.
+    pub fn assert_minimum_value(&self, min: u64) -> Result<(), ConfidentialProofError> {
+        if self.minimum_value_promise < min {
+            return Err(ConfidentialProofError::MinimumValueTooLow {
+                minimum_value_promise: self.minimum_value_promise,
+                required_minimum: min,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// All the types of errors that can occur when checking a confidential proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfidentialProofError {
+    MinimumValueTooLow {
+        minimum_value_promise: u64,
+        required_minimum: u64,
+    },
+}
+
+impl Display for ConfidentialProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MinimumValueTooLow {
+                minimum_value_promise,
+                required_minimum,
+            } => write!(
+                f,
+                "Minimum value promise {minimum_value_promise} is less than the required minimum {required_minimum}"
+            ),
+        }
+    }
+}
+
 /// ### Verifiable encryption
 ///
 /// A verifiable ElGamal encryption proving system that asserts the value bound to a Pedersen
@@ -255,3 +322,55 @@ impl TryFrom<&[u8]> for EncryptedData {
         Ok(Self(out))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_statement() -> ConfidentialStatement {
+        ConfidentialStatement {
+            commitment: Default::default(),
+            sender_public_nonce: Default::default(),
+            encrypted_data: EncryptedData([0u8; EncryptedData::size()]),
+            minimum_value_promise: 0,
+            viewable_balance_proof: None,
+        }
+    }
+
+    #[test]
+    fn it_constructs_output_statements_with_and_without_change() {
+        let proof = ConfidentialOutputStatement::with_change(
+            create_statement(),
+            create_statement(),
+            vec![1, 2, 3],
+            Amount(10),
+            Amount(5),
+        );
+        assert!(proof.output_statement.is_some());
+        assert!(proof.change_statement.is_some());
+        assert_eq!(proof.range_proof, vec![1, 2, 3]);
+        assert_eq!(proof.output_revealed_amount, Amount(10));
+        assert_eq!(proof.change_revealed_amount, Amount(5));
+
+        let proof = ConfidentialOutputStatement::without_change(create_statement(), vec![1, 2, 3], Amount(10));
+        assert!(proof.output_statement.is_some());
+        assert!(proof.change_statement.is_none());
+        assert_eq!(proof.output_revealed_amount, Amount(10));
+        assert_eq!(proof.change_revealed_amount, Amount::zero());
+    }
+
+    #[test]
+    fn it_asserts_the_minimum_value_promise() {
+        let statement = ConfidentialStatement {
+            minimum_value_promise: 100,
+            ..create_statement()
+        };
+        statement.assert_minimum_value(99).unwrap();
+        statement.assert_minimum_value(100).unwrap();
+        let err = statement.assert_minimum_value(101).unwrap_err();
+        assert_eq!(err, ConfidentialProofError::MinimumValueTooLow {
+            minimum_value_promise: 100,
+            required_minimum: 101,
+        });
+    }
+}
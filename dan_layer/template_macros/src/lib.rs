@@ -24,19 +24,29 @@ mod template;
 
 use proc_macro::TokenStream;
 
-/// Generates Tari template definition and dispatcher code from annotated template code.
+/// Generates Tari template definition and dispatcher code from annotated template code. An optional ABI version may be
+/// given with `#[template(version = N)]`. Methods marked with `#[readonly]` never persist the component state, even if
+/// they take `&mut self`. With `#[template(gen_client)]`, a `<Name>_client::<Name>Client` stub is also generated for
+/// non-WASM targets that builds the instruction for each template function; this requires a dependency on
+/// `tari_engine_types`.
 #[proc_macro_attribute]
-pub fn template(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    template::generate_template(proc_macro2::TokenStream::from(item))
-        .unwrap_or_else(|err| err.to_compile_error())
-        .into()
+pub fn template(attr: TokenStream, item: TokenStream) -> TokenStream {
+    template::generate_template(
+        proc_macro2::TokenStream::from(attr),
+        proc_macro2::TokenStream::from(item),
+    )
+    .unwrap_or_else(|err| err.to_compile_error())
+    .into()
 }
 
 /// Returns the template code without the wasm ABI code. This allows the code to compile for non-WASM targets and allows
 /// "intellisense" to work in IDEs.
 #[proc_macro_attribute]
-pub fn template_non_wasm(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    template::generate_template_non_wasm(proc_macro2::TokenStream::from(item))
-        .unwrap_or_else(|err| err.to_compile_error())
-        .into()
+pub fn template_non_wasm(attr: TokenStream, item: TokenStream) -> TokenStream {
+    template::generate_template_non_wasm(
+        proc_macro2::TokenStream::from(attr),
+        proc_macro2::TokenStream::from(item),
+    )
+    .unwrap_or_else(|err| err.to_compile_error())
+    .into()
 }
@@ -32,20 +32,37 @@ use tari_template_abi::{
     ABI_TEMPLATE_DEF_GLOBAL_NAME,
 };
 
-use crate::template::ast::{TemplateAst, TypeAst};
+use crate::template::ast::{TemplateAst, TemplateAttrs, TypeAst};
 
 pub const TARI_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-pub fn generate_abi(ast: &TemplateAst) -> Result<TokenStream> {
+pub fn generate_abi(ast: &TemplateAst, attrs: &TemplateAttrs) -> Result<TokenStream> {
+    let template_def = generate_template_def(ast, attrs)?;
+    let template_def_data = tari_bor::encode_with_len(&template_def);
+    let len = template_def_data.len();
+    let template_def_name = format_ident!("{ABI_TEMPLATE_DEF_GLOBAL_NAME}");
+
+    let output = quote! {
+        #[no_mangle]
+        pub static #template_def_name: [u8;#len] = [#(#template_def_data),*];
+    };
+
+    Ok(output)
+}
+
+fn generate_template_def(ast: &TemplateAst, attrs: &TemplateAttrs) -> Result<TemplateDef> {
     let template_name_as_str = ast.template_name.to_string();
 
     let template_def = TemplateDef::V1(TemplateDefV1 {
         template_name: template_name_as_str.clone(),
         tari_version: TARI_VERSION.to_owned(),
+        version: attrs.version,
         functions: ast
             .get_functions()
             .map(|func| {
-                let is_mut = func.is_mut();
+                // A readonly method never persists the component state, so it only needs read access to the component
+                let is_mut = func.is_mut() && !func.is_readonly;
+                let is_constructor = !func.has_receiver();
                 Ok::<_, syn::Error>(FunctionDef {
                     name: func.name,
                     arguments: func
@@ -59,21 +76,13 @@ pub fn generate_abi(ast: &TemplateAst) -> Result<TokenStream> {
                         .map(|ty| convert_to_arg_type(&template_name_as_str, ty))
                         .unwrap_or(ArgType::Unit),
                     is_mut,
+                    is_constructor,
                 })
             })
             .collect::<Result<_>>()?,
     });
 
-    let template_def_data = tari_bor::encode_with_len(&template_def);
-    let len = template_def_data.len();
-    let template_def_name = format_ident!("{ABI_TEMPLATE_DEF_GLOBAL_NAME}");
-
-    let output = quote! {
-        #[no_mangle]
-        pub static #template_def_name: [u8;#len] = [#(#template_def_data),*];
-    };
-
-    Ok(output)
+    Ok(template_def)
 }
 
 fn convert_to_arg_type(template_name: &str, ty: &TypeAst) -> ArgType {
@@ -175,6 +184,22 @@ fn path_segment_to_arg_type(template_name: &str, segment: &PathSegment) -> ArgTy
                 },
             }
         },
+        "Option" => match &segment.arguments {
+            PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => match &args[0] {
+                GenericArgument::Type(Type::Path(path)) => {
+                    let ty = path_segment_to_arg_type(template_name, &path.path.segments[0]);
+                    ArgType::Option(Box::new(ty))
+                },
+                GenericArgument::Type(Type::Tuple(tuple)) => {
+                    ArgType::Option(Box::new(tuple_to_arg_type(template_name, tuple)))
+                },
+                // TODO: These should be errors
+                a => panic!("Invalid option generic argument {:?}", a),
+            },
+            PathArguments::Parenthesized(_) | PathArguments::None => {
+                panic!("Option must specify a type {:?}", segment)
+            },
+        },
         "Self" => ArgType::Other {
             name: format!("Component<{}>", template_name),
         },
@@ -200,3 +225,139 @@ fn tuple_to_arg_type(template_name: &str, tuple: &TypeTuple) -> ArgType {
 
     ArgType::Tuple(subtypes)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use indoc::indoc;
+    use proc_macro2::TokenStream;
+    use quote::quote;
+    use syn::parse2;
+    use tari_template_abi::Type;
+
+    use super::generate_template_def;
+    use crate::template::ast::{TemplateAst, TemplateAttrs};
+
+    fn parse_template() -> TemplateAst {
+        let input = TokenStream::from_str(indoc! {"
+            mod foo {
+                struct Foo {}
+                impl Foo {
+                    pub fn method(&self) {}
+                }
+            }
+        "})
+        .unwrap();
+        parse2::<TemplateAst>(input).unwrap()
+    }
+
+    #[test]
+    fn it_encodes_the_template_version() {
+        let ast = parse_template();
+        let attrs = parse2::<TemplateAttrs>(quote! { version = 3 }).unwrap();
+
+        let template_def = generate_template_def(&ast, &attrs).unwrap();
+        assert_eq!(template_def.version(), 3);
+    }
+
+    #[test]
+    fn it_defaults_the_template_version_to_zero() {
+        let ast = parse_template();
+        let attrs = parse2::<TemplateAttrs>(TokenStream::new()).unwrap();
+
+        let template_def = generate_template_def(&ast, &attrs).unwrap();
+        assert_eq!(template_def.version(), 0);
+    }
+
+    #[test]
+    fn it_marks_readonly_methods_as_not_mutable() {
+        let input = TokenStream::from_str(indoc! {"
+            mod foo {
+                struct Foo {}
+                impl Foo {
+                    #[readonly]
+                    pub fn get(&mut self) -> u32 {
+                        1
+                    }
+
+                    pub fn set(&mut self) {}
+                }
+            }
+        "})
+        .unwrap();
+        let ast = parse2::<TemplateAst>(input).unwrap();
+        let attrs = parse2::<TemplateAttrs>(TokenStream::new()).unwrap();
+
+        let template_def = generate_template_def(&ast, &attrs).unwrap();
+        assert!(!template_def.get_function("get").unwrap().is_mut);
+        assert!(template_def.get_function("set").unwrap().is_mut);
+    }
+
+    #[test]
+    fn it_marks_functions_without_a_receiver_as_constructors() {
+        let input = TokenStream::from_str(indoc! {"
+            mod foo {
+                struct Foo {}
+                impl Foo {
+                    pub fn new() -> Self {
+                        Self {}
+                    }
+
+                    pub fn get(&self) -> u32 {
+                        1
+                    }
+
+                    pub fn set(&mut self, value: u32) {}
+                }
+            }
+        "})
+        .unwrap();
+        let ast = parse2::<TemplateAst>(input).unwrap();
+        let attrs = parse2::<TemplateAttrs>(TokenStream::new()).unwrap();
+
+        let template_def = generate_template_def(&ast, &attrs).unwrap();
+        assert!(template_def.get_function("new").unwrap().is_constructor);
+        assert!(!template_def.get_function("get").unwrap().is_constructor);
+        assert!(!template_def.get_function("set").unwrap().is_constructor);
+    }
+
+    #[test]
+    fn it_maps_option_types() {
+        let input = TokenStream::from_str(indoc! {"
+            mod foo {
+                struct Foo {}
+                impl Foo {
+                    pub fn find(&self, key: Option<u32>) -> Option<u32> {
+                        key
+                    }
+                }
+            }
+        "})
+        .unwrap();
+        let ast = parse2::<TemplateAst>(input).unwrap();
+        let attrs = parse2::<TemplateAttrs>(TokenStream::new()).unwrap();
+
+        let template_def = generate_template_def(&ast, &attrs).unwrap();
+        let find = template_def.get_function("find").unwrap();
+        assert_eq!(find.arguments[1].arg_type, Type::Option(Box::new(Type::U32)));
+        assert_eq!(find.output, Type::Option(Box::new(Type::U32)));
+    }
+
+    #[test]
+    fn it_parses_the_gen_client_attribute() {
+        let attrs = parse2::<TemplateAttrs>(quote! { version = 2, gen_client }).unwrap();
+        assert_eq!(attrs.version, 2);
+        assert!(attrs.gen_client);
+
+        let attrs = parse2::<TemplateAttrs>(TokenStream::new()).unwrap();
+        assert!(!attrs.gen_client);
+    }
+
+    #[test]
+    fn it_rejects_unknown_attributes() {
+        parse2::<TemplateAttrs>(quote! { foo = 1 }).unwrap_err();
+        parse2::<TemplateAttrs>(quote! { version = "1" }).unwrap_err();
+        parse2::<TemplateAttrs>(quote! { gen_client = true }).unwrap_err();
+    }
+}
@@ -20,13 +20,20 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::fmt::{Debug, Formatter};
+use std::{
+    collections::HashSet,
+    fmt::{Debug, Formatter},
+};
 
 use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
     token::Comma,
+    visit::{self, Visit},
     Error,
+    Expr,
+    ExprAssign,
+    ExprAssignOp,
     FnArg,
     Ident,
     ImplItem,
@@ -34,6 +41,9 @@ use syn::{
     Item,
     ItemMod,
     ItemUse,
+    Lit,
+    Meta,
+    MetaNameValue,
     Result,
     ReturnType,
     Signature,
@@ -43,11 +53,44 @@ use syn::{
     UseTree,
 };
 
+/// The arguments of the template attribute e.g. `#[template(version = 1, gen_client)]`
+#[derive(Debug, Default)]
+pub struct TemplateAttrs {
+    /// The ABI version of the template. Defaults to 0 if not specified.
+    pub version: u32,
+    /// Generate a client stub that builds the instructions to call the template's functions
+    pub gen_client: bool,
+}
+
+impl Parse for TemplateAttrs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut attrs = Self::default();
+        let args = Punctuated::<Meta, Comma>::parse_terminated(input)?;
+        for arg in args {
+            match arg {
+                Meta::NameValue(MetaNameValue { path, lit, .. }) if path.is_ident("version") => match lit {
+                    Lit::Int(lit) => {
+                        attrs.version = lit.base10_parse()?;
+                    },
+                    lit => return Err(Error::new_spanned(lit, "template version must be an integer")),
+                },
+                Meta::Path(path) if path.is_ident("gen_client") => {
+                    attrs.gen_client = true;
+                },
+                arg => return Err(Error::new_spanned(arg.path(), "unknown template attribute")),
+            }
+        }
+        Ok(attrs)
+    }
+}
+
 #[allow(dead_code)]
 pub struct TemplateAst {
     pub template_name: Ident,
     pub module_content: Vec<Item>,
     pub uses: Vec<ItemUse>,
+    /// Names of the methods marked with the `#[readonly]` attribute
+    pub readonly_functions: HashSet<String>,
 }
 
 impl Parse for TemplateAst {
@@ -65,6 +108,7 @@ impl Parse for TemplateAst {
         let mut template_name = None;
         let mut has_impl = false;
         let mut uses = Vec::new();
+        let mut readonly_functions = HashSet::new();
 
         for item in items {
             match item {
@@ -80,8 +124,15 @@ impl Parse for TemplateAst {
                     }
                 },
                 // TODO: check name matches template name
-                Item::Impl(_) => {
+                Item::Impl(ref mut item) => {
                     has_impl = true;
+                    for impl_item in &mut item.items {
+                        if let ImplItem::Method(method) = impl_item {
+                            if Self::take_readonly_attr(method)? {
+                                readonly_functions.insert(method.sig.ident.to_string());
+                            }
+                        }
+                    }
                 },
                 Item::Use(item) => {
                     // Exclude super imports
@@ -114,6 +165,7 @@ impl Parse for TemplateAst {
                 .map(|(_, c)| c)
                 .ok_or_else(|| Error::new(module.ident.span(), "Template module must contain content"))?,
             uses,
+            readonly_functions,
         })
     }
 }
@@ -127,10 +179,40 @@ impl TemplateAst {
                 _ => None,
             })
             .flatten()
-            .filter_map(Self::get_function_from_item)
+            .filter_map(|item| self.get_function_from_item(item))
     }
 
-    fn get_function_from_item(item: &ImplItem) -> Option<FunctionAst> {
+    /// Removes the `#[readonly]` attribute from the method, returning true if it was present. The attribute only has
+    /// meaning to the template macro, so it must not be left in the generated code.
+    fn take_readonly_attr(method: &mut ImplItemMethod) -> Result<bool> {
+        let Some(pos) = method.attrs.iter().position(|attr| attr.path.is_ident("readonly")) else {
+            return Ok(false);
+        };
+        let attr = method.attrs.remove(pos);
+        if !attr.tokens.is_empty() {
+            return Err(Error::new_spanned(
+                attr,
+                "the readonly attribute does not take any arguments",
+            ));
+        }
+
+        // Best-effort check that the method does not assign to its own state
+        let mut visitor = SelfAssignmentVisitor::default();
+        visitor.visit_block(&method.block);
+        if let Some(expr) = visitor.assignment {
+            return Err(Error::new_spanned(
+                expr,
+                format!(
+                    "method '{}' is marked as readonly but modifies the component state",
+                    method.sig.ident
+                ),
+            ));
+        }
+
+        Ok(true)
+    }
+
+    fn get_function_from_item(&self, item: &ImplItem) -> Option<FunctionAst> {
         match item {
             ImplItem::Method(m) => {
                 if !Self::is_public_function(m) {
@@ -143,6 +225,7 @@ impl TemplateAst {
                     statements: Self::get_statements(m),
                     is_constructor: Self::is_constructor(&m.sig),
                     is_public: true,
+                    is_readonly: self.readonly_functions.contains(&m.sig.ident.to_string()),
                 })
             },
             _ => todo!("get_function_from_item does not support anything other than methods"),
@@ -231,6 +314,9 @@ pub struct FunctionAst {
     pub statements: Vec<Stmt>,
     pub is_constructor: bool,
     pub is_public: bool,
+    /// True if the method is marked with `#[readonly]`, in which case the component state is never persisted after
+    /// the call
+    pub is_readonly: bool,
 }
 
 impl FunctionAst {
@@ -240,6 +326,46 @@ impl FunctionAst {
             .iter()
             .any(|t| matches!(t, TypeAst::Receiver { mutability: true }))
     }
+
+    /// Returns true if the first argument is a `&self` or `&mut self` receiver
+    pub fn has_receiver(&self) -> bool {
+        matches!(self.input_types.first(), Some(TypeAst::Receiver { .. }))
+    }
+}
+
+/// Finds the first assignment (e.g. `self.x = y` or `self.x += 1`) to a field of `self`
+#[derive(Default)]
+struct SelfAssignmentVisitor {
+    assignment: Option<Expr>,
+}
+
+impl SelfAssignmentVisitor {
+    fn is_self_place(expr: &Expr) -> bool {
+        match expr {
+            Expr::Path(path) => path.path.is_ident("self"),
+            Expr::Field(field) => Self::is_self_place(&field.base),
+            Expr::Index(index) => Self::is_self_place(&index.expr),
+            Expr::Paren(paren) => Self::is_self_place(&paren.expr),
+            Expr::Unary(unary) => Self::is_self_place(&unary.expr),
+            _ => false,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for SelfAssignmentVisitor {
+    fn visit_expr_assign(&mut self, node: &'ast ExprAssign) {
+        if self.assignment.is_none() && Self::is_self_place(&node.left) {
+            self.assignment = Some(Expr::Assign(node.clone()));
+        }
+        visit::visit_expr_assign(self, node);
+    }
+
+    fn visit_expr_assign_op(&mut self, node: &'ast ExprAssignOp) {
+        if self.assignment.is_none() && Self::is_self_place(&node.left) {
+            self.assignment = Some(Expr::AssignOp(node.clone()));
+        }
+        visit::visit_expr_assign_op(self, node);
+    }
 }
 
 pub enum TypeAst {
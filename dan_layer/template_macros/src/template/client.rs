@@ -0,0 +1,165 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::template::ast::{FunctionAst, TemplateAst, TypeAst};
+
+/// Generates a `<Name>_client` module containing a `<Name>Client` struct with an associated function for each public
+/// template function. Each one returns the instruction that calls the function with the given arguments: functions
+/// that take `self` produce a `CallMethod` on a component and all others a `CallFunction` on the template.
+///
+/// The client is only generated for non-WASM targets, and requires the crate to depend on `tari_engine_types`.
+pub fn generate_client(ast: &TemplateAst) -> TokenStream {
+    let template_name = &ast.template_name;
+    let template_mod_name = format_ident!("{}_template", template_name);
+    let client_mod_name = format_ident!("{}_client", template_name);
+    let client_name = format_ident!("{}Client", template_name);
+    let uses = &ast.uses;
+    let functions = ast.get_functions().map(generate_client_function);
+
+    quote! {
+        #[cfg(not(target_arch = "wasm32"))]
+        #[allow(non_snake_case)]
+        pub mod #client_mod_name {
+            use ::tari_template_lib::template_dependencies::*;
+            #[allow(unused_imports)]
+            use super::#template_mod_name::*;
+            #(
+                #[allow(unused_imports)]
+                #uses
+            )*
+
+            pub struct #client_name;
+
+            impl #client_name {
+                #(#functions)*
+            }
+        }
+    }
+}
+
+fn generate_client_function(function: FunctionAst) -> TokenStream {
+    let func_ident = format_ident!("{}", function.name);
+    let func_name = &function.name;
+
+    let mut params = vec![];
+    let mut args = vec![];
+    for (i, input_type) in function.input_types.iter().enumerate() {
+        let arg_ident = format_ident!("arg_{}", i);
+        match input_type {
+            TypeAst::Receiver { .. } => {},
+            TypeAst::Typed { type_path, .. } => {
+                params.push(quote! { #arg_ident: #type_path });
+                args.push(arg_ident);
+            },
+            TypeAst::Tuple { type_tuple, .. } => {
+                params.push(quote! { #arg_ident: #type_tuple });
+                args.push(arg_ident);
+            },
+        }
+    }
+
+    if function.has_receiver() {
+        quote! {
+            pub fn #func_ident(
+                component_address: ::tari_template_lib::models::ComponentAddress,
+                #(#params),*
+            ) -> ::tari_engine_types::instruction::Instruction {
+                ::tari_engine_types::instruction::Instruction::CallMethod {
+                    component_address,
+                    method: #func_name.to_string(),
+                    args: ::tari_template_lib::args![#(#args),*],
+                }
+            }
+        }
+    } else {
+        quote! {
+            pub fn #func_ident(
+                template_address: ::tari_template_lib::models::TemplateAddress,
+                #(#params),*
+            ) -> ::tari_engine_types::instruction::Instruction {
+                ::tari_engine_types::instruction::Instruction::CallFunction {
+                    template_address,
+                    function: #func_name.to_string(),
+                    args: ::tari_template_lib::args![#(#args),*],
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use indoc::indoc;
+    use proc_macro2::TokenStream;
+    use quote::quote;
+    use syn::parse2;
+
+    use super::generate_client;
+    use crate::template::ast::TemplateAst;
+
+    #[test]
+    fn it_generates_a_client_function_for_each_public_function() {
+        let input = TokenStream::from_str(indoc! {"
+            mod state {
+                struct State {}
+                impl State {
+                    pub fn new() -> Self {
+                        Self {}
+                    }
+
+                    pub fn set(&mut self, value: u32) {}
+
+                    fn private_function() {}
+                }
+            }
+        "})
+        .unwrap();
+        let ast = parse2::<TemplateAst>(input).unwrap();
+
+        let output = generate_client(&ast);
+
+        assert_eq!(
+            output.to_string(),
+            quote! {
+                #[cfg(not(target_arch = "wasm32"))]
+                #[allow(non_snake_case)]
+                pub mod State_client {
+                    use ::tari_template_lib::template_dependencies::*;
+                    #[allow(unused_imports)]
+                    use super::State_template::*;
+
+                    pub struct StateClient;
+
+                    impl StateClient {
+                        pub fn new(
+                            template_address: ::tari_template_lib::models::TemplateAddress,
+                        ) -> ::tari_engine_types::instruction::Instruction {
+                            ::tari_engine_types::instruction::Instruction::CallFunction {
+                                template_address,
+                                function: "new".to_string(),
+                                args: ::tari_template_lib::args![],
+                            }
+                        }
+
+                        pub fn set(
+                            component_address: ::tari_template_lib::models::ComponentAddress,
+                            arg_1: u32
+                        ) -> ::tari_engine_types::instruction::Instruction {
+                            ::tari_engine_types::instruction::Instruction::CallMethod {
+                                component_address,
+                                method: "set".to_string(),
+                                args: ::tari_template_lib::args![arg_1],
+                            }
+                        }
+                    }
+                }
+            }
+            .to_string()
+        );
+    }
+}
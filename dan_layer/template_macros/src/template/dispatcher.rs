@@ -22,7 +22,23 @@
 
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{format_ident, quote};
-use syn::{parse_quote, token::Brace, Block, Expr, ExprBlock, ExprField, Result, Stmt, TypePath, TypeTuple};
+use syn::{
+    parse_quote,
+    token::Brace,
+    AngleBracketedGenericArguments,
+    Block,
+    Expr,
+    ExprBlock,
+    ExprField,
+    GenericArgument,
+    PathArguments,
+    PathSegment,
+    Result,
+    Stmt,
+    Type,
+    TypePath,
+    TypeTuple,
+};
 
 use crate::template::ast::{FunctionAst, TemplateAst, TypeAst};
 
@@ -126,17 +142,11 @@ fn get_function_block(template_ident: &Ident, ast: FunctionAst) -> Expr {
             // non-self argument
             TypeAst::Typed { type_path, .. } => {
                 args.push(parse_quote! { #arg_ident });
-                stmts.push(parse_quote! {
-                    let #arg_ident = from_value::<#type_path>(&call_info.args[#i])
-                        .unwrap_or_else(|e| panic!("failed to decode argument at position {} for function '{}': {}", #i, #func_name, e));
-                })
+                stmts.push(decode_arg_stmt(&arg_ident, i, func_name, &parse_quote! { #type_path }));
             },
             TypeAst::Tuple { type_tuple, .. } => {
                 args.push(parse_quote! { #arg_ident });
-                stmts.push(parse_quote! {
-                    let #arg_ident = from_value::<#type_tuple>(&call_info.args[#i])
-                        .unwrap_or_else(|e| panic!("failed to decode tuple argument at position {} for function '{}': {}", #i, #func_name, e));
-                });
+                stmts.push(decode_arg_stmt(&arg_ident, i, func_name, &parse_quote! { #type_tuple }));
             },
         }
     }
@@ -155,8 +165,8 @@ fn get_function_block(template_ident: &Ident, ast: FunctionAst) -> Expr {
         result = encode_with_len(&rtn);
     });
 
-    // after user function invocation, update the component state
-    if is_mutable_call {
+    // after user function invocation, update the component state (unless the method is marked as readonly)
+    if is_mutable_call && !ast.is_readonly {
         stmts.push(parse_quote! {
             component_manager.set_state(state);
         });
@@ -175,6 +185,18 @@ fn get_function_block(template_ident: &Ident, ast: FunctionAst) -> Expr {
     })
 }
 
+/// Decodes the argument at `index`. If the caller passed a value that cannot be decoded as that type, the engine fails
+/// the call with an error carrying the argument position and expected type.
+fn decode_arg_stmt(arg_ident: &Ident, index: usize, func_name: &str, arg_type: &Type) -> Stmt {
+    let type_name = quote!(#arg_type).to_string().replace(' ', "");
+    parse_quote! {
+        let #arg_ident = match from_value::<#arg_type>(&call_info.args[#index]) {
+            Ok(arg) => arg,
+            Err(e) => engine().invalid_call_argument(#func_name, #index, #type_name, e),
+        };
+    }
+}
+
 fn replace_self_in_output(ast: &FunctionAst) -> Vec<Stmt> {
     let mut stmts: Vec<Stmt> = vec![];
     match &ast.output_type {
@@ -210,9 +232,30 @@ fn replace_self_in_single_value(type_path: &TypePath) -> Option<Stmt> {
         });
     }
 
+    if type_ident == "Option" && is_self_generic_argument(&type_path.path.segments[0]) {
+        // An Option<Self> result creates the component if one was returned
+        return Some(parse_quote! {
+            let rtn = rtn.map(|rtn| engine().create_component(
+                rtn,
+                ::tari_template_lib::auth::OwnerRule::default(),
+                ::tari_template_lib::auth::ComponentAccessRules::new(),
+                None,
+            ));
+        });
+    }
+
     None
 }
 
+fn is_self_generic_argument(segment: &PathSegment) -> bool {
+    match &segment.arguments {
+        PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => {
+            matches!(args.first(), Some(GenericArgument::Type(Type::Path(path))) if path.path.is_ident("Self"))
+        },
+        _ => false,
+    }
+}
+
 fn replace_self_in_tuple(type_tuple: &TypeTuple) -> Stmt {
     // build the expressions for each element in the tuple
     let elems: Vec<Expr> = type_tuple
@@ -263,3 +306,156 @@ fn build_tuple_field_expr(name: String, i: u32) -> Expr {
 
     Expr::Field(field_expr)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use indoc::indoc;
+    use proc_macro2::TokenStream;
+    use quote::quote;
+    use syn::parse2;
+
+    use super::get_function_block;
+    use crate::template::ast::TemplateAst;
+
+    #[test]
+    fn test_codegen_typed_arg() {
+        let input = TokenStream::from_str(indoc! {"
+            mod foo {
+                struct Foo {}
+                impl Foo {
+                    pub fn some_args_function(a: i8) -> u32 {
+                        1_u32
+                    }
+                }
+            }
+        "})
+        .unwrap();
+
+        let ast = parse2::<TemplateAst>(input).unwrap();
+        let function = ast.get_functions().next().unwrap();
+
+        let output = get_function_block(&ast.template_name, function);
+
+        assert_code_eq(quote! { #output }, quote! {
+            {
+                assert_eq!(
+                    call_info.args.len(),
+                    1usize,
+                    "Call \"{}\" had unexpected number of args. Got = {} expected = {}",
+                    call_info.func_name,
+                    call_info.args.len(),
+                    1usize,
+                );
+                let arg_0 = match from_value::<i8>(&call_info.args[0usize]) {
+                    Ok(arg) => arg,
+                    Err(e) => engine().invalid_call_argument("some_args_function", 0usize, "i8", e),
+                };
+                let rtn = Foo_template::Foo::some_args_function(arg_0);
+                result = encode_with_len(&rtn);
+            }
+        });
+    }
+
+    #[test]
+    fn test_codegen_option_return() {
+        let input = TokenStream::from_str(indoc! {"
+            mod foo {
+                struct Foo {}
+                impl Foo {
+                    pub fn find(key: u32) -> Option<u32> {
+                        Some(key)
+                    }
+
+                    pub fn maybe_new() -> Option<Self> {
+                        Some(Self {})
+                    }
+                }
+            }
+        "})
+        .unwrap();
+
+        let ast = parse2::<TemplateAst>(input).unwrap();
+        let mut functions = ast.get_functions();
+
+        let output = get_function_block(&ast.template_name, functions.next().unwrap());
+        assert_code_eq(quote! { #output }, quote! {
+            {
+                assert_eq!(
+                    call_info.args.len(),
+                    1usize,
+                    "Call \"{}\" had unexpected number of args. Got = {} expected = {}",
+                    call_info.func_name,
+                    call_info.args.len(),
+                    1usize,
+                );
+                let arg_0 = match from_value::<u32>(&call_info.args[0usize]) {
+                    Ok(arg) => arg,
+                    Err(e) => engine().invalid_call_argument("find", 0usize, "u32", e),
+                };
+                let rtn = Foo_template::Foo::find(arg_0);
+                result = encode_with_len(&rtn);
+            }
+        });
+
+        let output = get_function_block(&ast.template_name, functions.next().unwrap());
+        assert!(quote! { #output }
+            .to_string()
+            .contains("let rtn = rtn . map (| rtn | engine () . create_component ("));
+    }
+
+    #[test]
+    fn test_codegen_readonly_method() {
+        let input = TokenStream::from_str(indoc! {"
+            mod foo {
+                struct Foo {}
+                impl Foo {
+                    #[readonly]
+                    pub fn get(&mut self) -> u32 {
+                        1_u32
+                    }
+                }
+            }
+        "})
+        .unwrap();
+
+        let ast = parse2::<TemplateAst>(input).unwrap();
+        let function = ast.get_functions().next().unwrap();
+        assert!(function.is_readonly);
+
+        let output = get_function_block(&ast.template_name, function);
+        let output = quote! { #output }.to_string();
+        assert!(output.contains("Foo_template :: Foo :: get (& mut state)"));
+        assert!(!output.contains("set_state"));
+        // The attribute is removed from the template definition
+        let content = &ast.module_content;
+        assert!(!quote! { #(#content)* }.to_string().contains("readonly"));
+    }
+
+    #[test]
+    fn test_readonly_method_that_assigns_state_is_rejected() {
+        let input = TokenStream::from_str(indoc! {"
+            mod foo {
+                struct Foo {
+                    value: u32,
+                }
+                impl Foo {
+                    #[readonly]
+                    pub fn bump(&mut self) -> u32 {
+                        self.value += 1;
+                        self.value
+                    }
+                }
+            }
+        "})
+        .unwrap();
+
+        let err = parse2::<TemplateAst>(input).err().unwrap();
+        assert!(err.to_string().contains("marked as readonly"));
+    }
+
+    fn assert_code_eq(a: TokenStream, b: TokenStream) {
+        assert_eq!(a.to_string(), b.to_string());
+    }
+}
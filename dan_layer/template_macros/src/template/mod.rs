@@ -22,6 +22,7 @@
 
 mod abi;
 mod ast;
+mod client;
 mod definition;
 mod dispatcher;
 
@@ -29,14 +30,22 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{parse2, Result};
 
-use self::{abi::generate_abi, ast::TemplateAst, definition::generate_definition, dispatcher::generate_dispatcher};
+use self::{
+    abi::generate_abi,
+    ast::{TemplateAst, TemplateAttrs},
+    client::generate_client,
+    definition::generate_definition,
+    dispatcher::generate_dispatcher,
+};
 
-pub fn generate_template(input: TokenStream) -> Result<TokenStream> {
-    let ast = parse2::<TemplateAst>(input).unwrap();
+pub fn generate_template(attr: TokenStream, input: TokenStream) -> Result<TokenStream> {
+    let attrs = parse2::<TemplateAttrs>(attr)?;
+    let ast = parse2::<TemplateAst>(input)?;
 
     let definition = generate_definition(&ast);
-    let abi = generate_abi(&ast)?;
+    let abi = generate_abi(&ast, &attrs)?;
     let dispatcher = generate_dispatcher(&ast)?;
+    let client = attrs.gen_client.then(|| generate_client(&ast));
 
     let output = quote! {
         #definition
@@ -44,6 +53,8 @@ pub fn generate_template(input: TokenStream) -> Result<TokenStream> {
         #dispatcher
 
         #abi
+
+        #client
     };
 
     // eprintln!("output = {}", output);
@@ -51,13 +62,17 @@ pub fn generate_template(input: TokenStream) -> Result<TokenStream> {
     Ok(output)
 }
 
-pub fn generate_template_non_wasm(input: TokenStream) -> Result<TokenStream> {
-    let ast = parse2::<TemplateAst>(input).unwrap();
+pub fn generate_template_non_wasm(attr: TokenStream, input: TokenStream) -> Result<TokenStream> {
+    let attrs = parse2::<TemplateAttrs>(attr)?;
+    let ast = parse2::<TemplateAst>(input)?;
 
     let definition = generate_definition(&ast);
+    let client = attrs.gen_client.then(|| generate_client(&ast));
 
     let output = quote! {
         #definition
+
+        #client
     };
 
     Ok(output)
@@ -252,4 +252,52 @@ impl Transaction {
     pub fn has_inputs_without_version(&self) -> bool {
         self.inputs().iter().any(|i| i.version().is_none())
     }
+
+    /// Checks that the epoch bounds are satisfiable i.e. if both bounds are set, min_epoch <= max_epoch.
+    pub fn validate_epoch_bounds(&self) -> Result<(), InvalidEpochRangeError> {
+        if let (Some(min_epoch), Some(max_epoch)) = (self.min_epoch(), self.max_epoch()) {
+            if min_epoch > max_epoch {
+                return Err(InvalidEpochRangeError {
+                    transaction_id: self.id,
+                    min_epoch,
+                    max_epoch,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Transaction {transaction_id} minimum epoch ({min_epoch}) is greater than its maximum epoch ({max_epoch})")]
+pub struct InvalidEpochRangeError {
+    pub transaction_id: TransactionId,
+    pub min_epoch: Epoch,
+    pub max_epoch: Epoch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_transaction(min_epoch: Option<u64>, max_epoch: Option<u64>) -> Transaction {
+        Transaction::builder()
+            .with_min_epoch(min_epoch.map(Epoch))
+            .with_max_epoch(max_epoch.map(Epoch))
+            .build()
+    }
+
+    #[test]
+    fn it_validates_epoch_bounds() {
+        create_transaction(Some(5), Some(5)).validate_epoch_bounds().unwrap();
+        create_transaction(Some(1), Some(5)).validate_epoch_bounds().unwrap();
+        create_transaction(None, None).validate_epoch_bounds().unwrap();
+
+        let err = create_transaction(Some(6), Some(5))
+            .validate_epoch_bounds()
+            .unwrap_err();
+        assert_eq!(err.min_epoch, Epoch(6));
+        assert_eq!(err.max_epoch, Epoch(5));
+    }
 }
@@ -9,7 +9,7 @@ use tari_template_lib::{
 };
 
 use crate::{
-    models::{Account, VaultBalance, VaultModel},
+    models::{Account, AccountSummary, VaultBalance, VaultModel},
     storage::{WalletStorageError, WalletStore, WalletStoreReader, WalletStoreWriter},
 };
 
@@ -48,6 +48,13 @@ impl<'a, TStore: WalletStore> AccountsApi<'a, TStore> {
         Ok(accounts)
     }
 
+    /// Returns the accounts owned by a key index in the range `start..end`, ordered by key index
+    pub fn get_by_key_index_range(&self, start: u64, end: u64) -> Result<Vec<Account>, AccountsApiError> {
+        let mut tx = self.store.create_read_tx()?;
+        let accounts = tx.accounts_get_by_key_index_range(start, end)?;
+        Ok(accounts)
+    }
+
     pub fn count(&self) -> Result<u64, AccountsApiError> {
         let mut tx = self.store.create_read_tx()?;
         let count = tx.accounts_count()?;
@@ -87,6 +94,55 @@ impl<'a, TStore: WalletStore> AccountsApi<'a, TStore> {
         Ok(())
     }
 
+    /// Locks `amount` of the vault's revealed balance. Fails if the vault does not have enough unlocked revealed funds.
+    pub fn lock_vault_balance(&self, vault_address: &SubstateId, amount: Amount) -> Result<(), AccountsApiError> {
+        self.store.with_write_tx(|tx| {
+            let vault = tx.vaults_get(vault_address)?;
+            let available = vault.available_revealed_balance();
+            if amount.is_negative() || amount > available {
+                return Err(AccountsApiError::InsufficientFundsToLock {
+                    vault_address: vault_address.clone(),
+                    available,
+                    requested: amount,
+                });
+            }
+            tx.vaults_set_locked_revealed_balance(vault_address, vault.locked_revealed_balance + amount)?;
+            Self::reconcile_locked_balance(tx, vault_address)
+        })
+    }
+
+    /// Unlocks `amount` of the vault's locked revealed balance. Fails if more than the locked balance is unlocked.
+    pub fn unlock_vault_balance(&self, vault_address: &SubstateId, amount: Amount) -> Result<(), AccountsApiError> {
+        self.store.with_write_tx(|tx| {
+            let vault = tx.vaults_get(vault_address)?;
+            if amount.is_negative() || amount > vault.locked_revealed_balance {
+                return Err(AccountsApiError::UnlockExceedsLockedBalance {
+                    vault_address: vault_address.clone(),
+                    locked: vault.locked_revealed_balance,
+                    requested: amount,
+                });
+            }
+            tx.vaults_set_locked_revealed_balance(vault_address, vault.locked_revealed_balance - amount)?;
+            Self::reconcile_locked_balance(tx, vault_address)
+        })
+    }
+
+    /// Checks that the stored locked revealed balance does not exceed the revealed balance
+    fn reconcile_locked_balance(
+        tx: &mut TStore::WriteTransaction<'_>,
+        vault_address: &SubstateId,
+    ) -> Result<(), AccountsApiError> {
+        let vault = tx.vaults_get(vault_address)?;
+        if vault.locked_revealed_balance.is_negative() || vault.locked_revealed_balance > vault.revealed_balance {
+            return Err(AccountsApiError::VaultBalanceInconsistent {
+                vault_address: vault_address.clone(),
+                revealed: vault.revealed_balance,
+                locked: vault.locked_revealed_balance,
+            });
+        }
+        Ok(())
+    }
+
     pub fn get_vault_balance(&self, vault_address: &SubstateId) -> Result<VaultBalance, AccountsApiError> {
         let vault = self.store.with_read_tx(|tx| tx.vaults_get(vault_address))?;
         Ok(VaultBalance {
@@ -106,6 +162,25 @@ impl<'a, TStore: WalletStore> AccountsApi<'a, TStore> {
         Ok(account)
     }
 
+    /// Returns the account along with the number of vaults it has, their total revealed balance and the time of the
+    /// most recent transaction involving the account.
+    pub fn get_account_summary(&self, address: &SubstateId) -> Result<AccountSummary, AccountsApiError> {
+        let mut tx = self.store.create_read_tx()?;
+        let account = tx.accounts_get(address)?;
+        let vaults = tx.vaults_get_by_account(address)?;
+        let total_revealed_balance = vaults.iter().map(|vault| vault.revealed_balance).sum();
+        let last_activity = match address.as_component_address() {
+            Some(component) => tx.transactions_get_last_updated_at(&component)?,
+            None => None,
+        };
+        Ok(AccountSummary {
+            account,
+            num_vaults: vaults.len(),
+            total_revealed_balance,
+            last_activity,
+        })
+    }
+
     pub fn get_account_or_default(&self, address: Option<&SubstateId>) -> Result<Account, AccountsApiError> {
         let mut tx = self.store.create_read_tx()?;
         if let Some(address) = address {
@@ -158,6 +233,22 @@ impl<'a, TStore: WalletStore> AccountsApi<'a, TStore> {
         Ok(())
     }
 
+    /// Removes the account and all of its vaults, returning the number of vaults removed. The default account cannot be
+    /// removed until another account is set as the default.
+    pub fn remove_account(&self, address: &SubstateId) -> Result<usize, AccountsApiError> {
+        self.store.with_write_tx(|tx| {
+            let account = tx.accounts_get(address)?;
+            if account.is_default {
+                return Err(AccountsApiError::CannotRemoveDefaultAccount {
+                    address: address.clone(),
+                });
+            }
+            let num_vaults = tx.accounts_remove(address)?;
+            Ok(num_vaults)
+        })
+    }
+
+    /// Adds a vault to the account. Fails if the account already has a vault for the resource.
     pub fn add_vault(
         &self,
         account_address: SubstateId,
@@ -166,19 +257,30 @@ impl<'a, TStore: WalletStore> AccountsApi<'a, TStore> {
         resource_type: ResourceType,
         token_symbol: Option<String>,
     ) -> Result<(), AccountsApiError> {
-        let mut tx = self.store.create_write_tx()?;
-        tx.vaults_insert(VaultModel {
-            account_address,
-            address: vault_address,
-            resource_address,
-            resource_type,
-            revealed_balance: Amount::zero(),
-            confidential_balance: Amount::zero(),
-            locked_revealed_balance: Amount::zero(),
-            token_symbol,
-        })?;
-        tx.commit()?;
-        Ok(())
+        self.store.with_write_tx(|tx| {
+            // An account holds at most one vault per resource
+            if tx
+                .vaults_get_by_resource(&account_address, &resource_address)
+                .optional()?
+                .is_some()
+            {
+                return Err(AccountsApiError::DuplicateResourceVault {
+                    account_address,
+                    resource_address,
+                });
+            }
+            tx.vaults_insert(VaultModel {
+                account_address,
+                address: vault_address,
+                resource_address,
+                resource_type,
+                revealed_balance: Amount::zero(),
+                confidential_balance: Amount::zero(),
+                locked_revealed_balance: Amount::zero(),
+                token_symbol,
+            })?;
+            Ok(())
+        })
     }
 
     pub fn get_account_by_vault(&self, vault_addr: &&SubstateId) -> Result<Account, AccountsApiError> {
@@ -192,6 +294,29 @@ impl<'a, TStore: WalletStore> AccountsApi<'a, TStore> {
         let vaults = tx.vaults_get_by_account(account)?;
         Ok(vaults)
     }
+
+    /// Counts the vaults of all accounts by revealed balance. Each bucket is the lower bound of a balance range that
+    /// extends up to the next bucket, and each vault is counted in the highest bucket not exceeding its balance.
+    /// Vaults with a balance below the lowest bucket (such as confidential-only vaults, which have no revealed
+    /// balance) are counted in the lowest bucket. Buckets are returned in ascending order.
+    pub fn vault_balance_histogram(&self, buckets: &[Amount]) -> Result<Vec<(Amount, u64)>, AccountsApiError> {
+        let mut histogram = buckets.iter().map(|b| (*b, 0u64)).collect::<Vec<_>>();
+        histogram.sort_by_key(|(bucket, _)| *bucket);
+        histogram.dedup_by_key(|(bucket, _)| *bucket);
+        if histogram.is_empty() {
+            return Ok(histogram);
+        }
+
+        let mut tx = self.store.create_read_tx()?;
+        let balances = tx.vaults_get_all_revealed_balances()?;
+        for balance in balances {
+            let index = histogram
+                .partition_point(|(bucket, _)| *bucket <= balance)
+                .saturating_sub(1);
+            histogram[index].1 += 1;
+        }
+        Ok(histogram)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -200,6 +325,31 @@ pub enum AccountsApiError {
     StoreError(#[from] WalletStorageError),
     #[error("Account name already exists: {name}")]
     AccountNameAlreadyExists { name: String },
+    #[error("Cannot remove default account {address}. Set another account as the default first.")]
+    CannotRemoveDefaultAccount { address: SubstateId },
+    #[error("Account {account_address} already has a vault for resource {resource_address}")]
+    DuplicateResourceVault {
+        account_address: SubstateId,
+        resource_address: ResourceAddress,
+    },
+    #[error("Insufficient funds to lock in vault {vault_address}: available {available}, requested {requested}")]
+    InsufficientFundsToLock {
+        vault_address: SubstateId,
+        available: Amount,
+        requested: Amount,
+    },
+    #[error("Cannot unlock {requested} from vault {vault_address}: only {locked} is locked")]
+    UnlockExceedsLockedBalance {
+        vault_address: SubstateId,
+        locked: Amount,
+        requested: Amount,
+    },
+    #[error("Vault {vault_address} locked balance {locked} exceeds revealed balance {revealed}")]
+    VaultBalanceInconsistent {
+        vault_address: SubstateId,
+        revealed: Amount,
+        locked: Amount,
+    },
 }
 
 impl IsNotFoundError for AccountsApiError {
@@ -0,0 +1,71 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_engine_types::fees::{FeeTable, EXHAUST_DIVISOR, STORAGE_COST_REDUCTION_DIVISOR};
+use tari_template_lib::models::Amount;
+use tari_transaction::Transaction;
+
+// Generous estimates of the work done per instruction, chosen so that the estimate is an upper bound for typical
+// transactions.
+
+/// The number of engine runtime calls (each charged at `per_module_call_cost`) made per instruction. A builtin account
+/// withdraw or deposit makes a few dozen calls (component, vault, resource and workspace access), so this leaves room
+/// for template calls that do more work.
+const ESTIMATED_RUNTIME_CALLS_PER_INSTRUCTION: u64 = 100;
+/// The number of events emitted per instruction
+const ESTIMATED_EVENTS_PER_INSTRUCTION: u64 = 5;
+/// The number of logs emitted per instruction
+const ESTIMATED_LOGS_PER_INSTRUCTION: u64 = 10;
+/// The number of substates created per instruction, in addition to the transaction inputs that are written back
+const ESTIMATED_NEW_SUBSTATES_PER_INSTRUCTION: u64 = 2;
+/// The encoded size of each substate that is written
+const ESTIMATED_BYTES_PER_SUBSTATE: u64 = 4096;
+
+/// An approximate breakdown of the fees a transaction will pay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// The estimated total transaction fee. This is an upper bound for typical transactions.
+    pub transaction_fee: Amount,
+    /// The estimated fee paid to leaders. If the transaction involves more than one shard, this is shared between
+    /// the leader of each involved shard.
+    pub leader_fee: Amount,
+    /// The estimated amount of the transaction fee that is burnt
+    pub exhaust_burn: Amount,
+}
+
+/// Estimates transaction fees before a transaction is submitted, using the same fee table as the engine. The
+/// estimate is based only on the number of instructions and inputs in the transaction, so it is not exact.
+#[derive(Debug, Clone)]
+pub struct FeeEstimator {
+    fee_table: FeeTable,
+}
+
+impl FeeEstimator {
+    pub fn new(fee_table: FeeTable) -> Self {
+        Self { fee_table }
+    }
+
+    pub fn estimate(&self, transaction: &Transaction) -> FeeEstimate {
+        let num_instructions = (transaction.instructions().len() + transaction.fee_instructions().len()) as u64;
+        let num_inputs = transaction.num_unique_inputs() as u64;
+
+        let call_cost =
+            num_instructions * ESTIMATED_RUNTIME_CALLS_PER_INSTRUCTION * self.fee_table.per_module_call_cost();
+        // Assume that every input is written back in addition to any new substates
+        let num_substates_written = num_inputs + num_instructions * ESTIMATED_NEW_SUBSTATES_PER_INSTRUCTION;
+        let storage_cost =
+            num_substates_written * ESTIMATED_BYTES_PER_SUBSTATE * self.fee_table.per_byte_storage_cost() /
+                STORAGE_COST_REDUCTION_DIVISOR;
+        let event_cost = num_instructions * ESTIMATED_EVENTS_PER_INSTRUCTION * self.fee_table.per_event_cost();
+        let log_cost = num_instructions * ESTIMATED_LOGS_PER_INSTRUCTION * self.fee_table.per_log_cost();
+
+        let transaction_fee = call_cost + storage_cost + event_cost + log_cost;
+        let exhaust_burn = transaction_fee / EXHAUST_DIVISOR;
+
+        FeeEstimate {
+            transaction_fee: Amount::try_from(transaction_fee).unwrap_or(Amount::MAX),
+            leader_fee: Amount::try_from(transaction_fee - exhaust_burn).unwrap_or(Amount::MAX),
+            exhaust_burn: Amount::try_from(exhaust_burn).unwrap_or(Amount::MAX),
+        }
+    }
+}
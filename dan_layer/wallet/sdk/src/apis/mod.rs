@@ -6,6 +6,7 @@ pub mod confidential_crypto;
 pub mod confidential_outputs;
 pub mod confidential_transfer;
 pub mod config;
+pub mod fee_estimator;
 pub mod jwt;
 pub mod key_manager;
 pub mod non_fungible_tokens;
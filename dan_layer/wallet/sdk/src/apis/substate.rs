@@ -60,6 +60,41 @@ where
         Ok(substates)
     }
 
+    /// Pins the substate version so that it is kept by the wallet after the substate is downed or replaced by a newer
+    /// version. The version must be the one currently stored by the wallet.
+    pub fn pin_substate(&self, versioned_substate_id: &VersionedSubstateId) -> Result<(), SubstateApiError> {
+        self.store.with_write_tx(|tx| {
+            let substate = tx.substates_get(&versioned_substate_id.substate_id)?;
+            if substate.address.version != versioned_substate_id.version {
+                return Err(SubstateApiError::SubstateVersionNotStored {
+                    address: versioned_substate_id.substate_id.clone(),
+                    version: versioned_substate_id.version,
+                    stored_version: substate.address.version,
+                });
+            }
+            tx.pinned_substates_insert(&substate)?;
+            Ok(())
+        })
+    }
+
+    pub fn unpin_substate(&self, versioned_substate_id: &VersionedSubstateId) -> Result<(), SubstateApiError> {
+        self.store
+            .with_write_tx(|tx| tx.pinned_substates_remove(versioned_substate_id))?;
+        Ok(())
+    }
+
+    pub fn is_pinned(&self, versioned_substate_id: &VersionedSubstateId) -> Result<bool, SubstateApiError> {
+        let mut tx = self.store.create_read_tx()?;
+        let is_pinned = tx.pinned_substates_exists(versioned_substate_id)?;
+        Ok(is_pinned)
+    }
+
+    pub fn get_pinned_substates(&self) -> Result<Vec<SubstateModel>, SubstateApiError> {
+        let mut tx = self.store.create_read_tx()?;
+        let substates = tx.pinned_substates_get_all()?;
+        Ok(substates)
+    }
+
     pub fn load_dependent_substates(
         &self,
         parents: &[&SubstateId],
@@ -234,6 +269,12 @@ pub enum SubstateApiError {
     InvalidValidatorNodeResponse(String),
     #[error("Substate {address} does not exist")]
     SubstateDoesNotExist { address: SubstateId },
+    #[error("Cannot pin version {version} of substate {address} because the wallet has version {stored_version}")]
+    SubstateVersionNotStored {
+        address: SubstateId,
+        version: u32,
+        stored_version: u32,
+    },
     #[error("ValueVisitorError: {0}")]
     ValueVisitorError(#[from] IndexedValueError),
 }
@@ -3,8 +3,10 @@
 
 use std::fmt::{Display, Formatter};
 
+use chrono::NaiveDateTime;
 use tari_bor::{Deserialize, Serialize};
 use tari_engine_types::substate::SubstateId;
+use tari_template_lib::models::Amount;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[cfg_attr(
@@ -35,3 +37,13 @@ pub struct NewAccountInfo {
     pub key_index: u64,
     pub is_default: bool,
 }
+
+#[derive(Debug, Clone)]
+pub struct AccountSummary {
+    pub account: Account,
+    pub num_vaults: usize,
+    /// The sum of the revealed balances of all of the account's vaults
+    pub total_revealed_balance: Amount,
+    /// The last update time of the most recent transaction involving the account, if any
+    pub last_activity: Option<NaiveDateTime>,
+}
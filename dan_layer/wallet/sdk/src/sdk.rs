@@ -5,6 +5,7 @@ use std::{sync::Arc, time::Duration};
 
 use tari_crypto::tari_utilities::SafePassword;
 use tari_dan_common_types::optional::{IsNotFoundError, Optional};
+use tari_engine_types::fees::FeeTable;
 use tari_key_manager::cipher_seed::CipherSeed;
 
 use crate::{
@@ -14,6 +15,7 @@ use crate::{
         confidential_outputs::ConfidentialOutputsApi,
         confidential_transfer::ConfidentialTransferApi,
         config::{ConfigApi, ConfigApiError, ConfigKey},
+        fee_estimator::FeeEstimator,
         jwt::JwtApi,
         key_manager::KeyManagerApi,
         non_fungible_tokens::NonFungibleTokensApi,
@@ -101,6 +103,10 @@ where
         ConfidentialCryptoApi::new()
     }
 
+    pub fn fee_estimator(&self, fee_table: FeeTable) -> FeeEstimator {
+        FeeEstimator::new(fee_table)
+    }
+
     pub fn jwt_api(&self) -> JwtApi<'_, TStore> {
         JwtApi::new(&self.store, self.config.jwt_expiry, self.config.jwt_secret_key.clone())
     }
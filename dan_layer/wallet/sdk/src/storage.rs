@@ -6,6 +6,7 @@ use std::{
     time::Duration,
 };
 
+use chrono::NaiveDateTime;
 use tari_common_types::types::Commitment;
 use tari_dan_common_types::{optional::IsNotFoundError, substate_type::SubstateType};
 use tari_dan_storage::consensus_models::QuorumCertificate;
@@ -128,6 +129,13 @@ pub trait WalletStoreReader {
         status: Option<TransactionStatus>,
         component: Option<ComponentAddress>,
     ) -> Result<Vec<WalletTransaction>, WalletStorageError>;
+    /// Returns the time that the most recently updated (non-dry-run) transaction involving the component was last
+    /// updated, or None if there are no such transactions. A transaction involves a component if it calls a method on
+    /// the component or takes it as an input.
+    fn transactions_get_last_updated_at(
+        &mut self,
+        component: &ComponentAddress,
+    ) -> Result<Option<NaiveDateTime>, WalletStorageError>;
     // Substates
     fn substates_get(&mut self, address: &SubstateId) -> Result<SubstateModel, WalletStorageError>;
     fn substates_get_all(
@@ -138,9 +146,13 @@ pub trait WalletStoreReader {
         offset: Option<u64>,
     ) -> Result<Vec<SubstateModel>, WalletStorageError>;
     fn substates_get_children(&mut self, parent: &SubstateId) -> Result<Vec<SubstateModel>, WalletStorageError>;
+    fn pinned_substates_get_all(&mut self) -> Result<Vec<SubstateModel>, WalletStorageError>;
+    fn pinned_substates_exists(&mut self, address: &VersionedSubstateId) -> Result<bool, WalletStorageError>;
     // Accounts
     fn accounts_get(&mut self, address: &SubstateId) -> Result<Account, WalletStorageError>;
     fn accounts_get_many(&mut self, offset: u64, limit: u64) -> Result<Vec<Account>, WalletStorageError>;
+    /// Returns the accounts with an owner key index in the range `start..end`, ordered by key index
+    fn accounts_get_by_key_index_range(&mut self, start: u64, end: u64) -> Result<Vec<Account>, WalletStorageError>;
     fn accounts_get_default(&mut self) -> Result<Account, WalletStorageError>;
     fn accounts_count(&mut self) -> Result<u64, WalletStorageError>;
     fn accounts_get_by_name(&mut self, name: &str) -> Result<Account, WalletStorageError>;
@@ -155,6 +167,8 @@ pub trait WalletStoreReader {
         resource_address: &ResourceAddress,
     ) -> Result<VaultModel, WalletStorageError>;
     fn vaults_get_by_account(&mut self, account_addr: &SubstateId) -> Result<Vec<VaultModel>, WalletStorageError>;
+    /// Returns the revealed balance of every vault across all accounts.
+    fn vaults_get_all_revealed_balances(&mut self) -> Result<Vec<Amount>, WalletStorageError>;
 
     // Outputs
     fn outputs_get_unspent_balance(&mut self, vault_address: &SubstateId) -> Result<u64, WalletStorageError>;
@@ -254,6 +268,10 @@ pub trait WalletStoreWriter {
         address: VersionedSubstateId,
     ) -> Result<(), WalletStorageError>;
     fn substates_remove(&mut self, substate: &SubstateId) -> Result<SubstateModel, WalletStorageError>;
+    /// Stores a copy of the substate version that is kept until it is unpinned. Pinning an already pinned version is a
+    /// no-op.
+    fn pinned_substates_insert(&mut self, substate: &SubstateModel) -> Result<(), WalletStorageError>;
+    fn pinned_substates_remove(&mut self, address: &VersionedSubstateId) -> Result<(), WalletStorageError>;
 
     // Accounts
     fn accounts_set_default(&mut self, substate_id: &SubstateId) -> Result<(), WalletStorageError>;
@@ -266,6 +284,9 @@ pub trait WalletStoreWriter {
     ) -> Result<(), WalletStorageError>;
 
     fn accounts_update(&mut self, substate_id: &SubstateId, new_name: Option<&str>) -> Result<(), WalletStorageError>;
+    /// Removes the account together with its vaults and any outputs, proofs and non-fungibles they hold. Returns the
+    /// number of vaults removed.
+    fn accounts_remove(&mut self, substate_id: &SubstateId) -> Result<usize, WalletStorageError>;
 
     // Vaults
     fn vaults_insert(&mut self, vault: VaultModel) -> Result<(), WalletStorageError>;
@@ -275,6 +296,11 @@ pub trait WalletStoreWriter {
         revealed_balance: Amount,
         confidential_balance: Amount,
     ) -> Result<(), WalletStorageError>;
+    fn vaults_set_locked_revealed_balance(
+        &mut self,
+        vault_address: &SubstateId,
+        locked_revealed_balance: Amount,
+    ) -> Result<(), WalletStorageError>;
     fn vaults_lock_revealed_funds(
         &mut self,
         proof_id: ConfidentialProofId,
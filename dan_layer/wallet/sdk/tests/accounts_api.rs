@@ -0,0 +1,229 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_dan_wallet_sdk::{
+    apis::accounts::{AccountsApi, AccountsApiError},
+    storage::{WalletStore, WalletStoreReader, WalletStoreWriter},
+};
+use tari_dan_wallet_storage_sqlite::SqliteWalletStore;
+use tari_engine_types::substate::SubstateId;
+use tari_template_lib::{
+    constants::XTR,
+    models::{Amount, ObjectKey, ResourceAddress},
+    resource::ResourceType,
+};
+use tari_transaction::Transaction;
+
+fn account_address() -> SubstateId {
+    "component_0dc41b5cc74b36d696c7b140323a40a2f98b71df5d60e5a6bf4c1a07"
+        .parse()
+        .unwrap()
+}
+
+fn vault_address() -> SubstateId {
+    "vault_0dc41b5cc74b36d696c7b140323a40a2f98b71df5d60e5a6bf4c1a07"
+        .parse()
+        .unwrap()
+}
+
+fn other_resource_address() -> ResourceAddress {
+    ResourceAddress::new(ObjectKey::from_array([1; ObjectKey::LENGTH]))
+}
+
+fn create_store() -> SqliteWalletStore {
+    let store = SqliteWalletStore::try_open(":memory:").unwrap();
+    store.run_migrations().unwrap();
+    let accounts_api = AccountsApi::new(&store);
+    accounts_api
+        .add_account(Some("test"), &account_address(), 0, true)
+        .unwrap();
+    accounts_api
+        .add_vault(
+            account_address(),
+            vault_address(),
+            XTR,
+            ResourceType::Confidential,
+            None,
+        )
+        .unwrap();
+    accounts_api
+        .update_vault_balance(&vault_address(), Amount(100), Amount::zero())
+        .unwrap();
+    store
+}
+
+#[test]
+fn vault_balance_locked_and_unlocked() {
+    let store = create_store();
+    let accounts_api = AccountsApi::new(&store);
+
+    accounts_api.lock_vault_balance(&vault_address(), Amount(60)).unwrap();
+    let vault = accounts_api.get_vault(&&vault_address()).unwrap();
+    assert_eq!(vault.locked_revealed_balance, Amount(60));
+    assert_eq!(vault.available_revealed_balance(), Amount(40));
+
+    let err = accounts_api
+        .lock_vault_balance(&vault_address(), Amount(41))
+        .unwrap_err();
+    assert!(matches!(err, AccountsApiError::InsufficientFundsToLock { .. }));
+
+    accounts_api.unlock_vault_balance(&vault_address(), Amount(20)).unwrap();
+    let vault = accounts_api.get_vault(&&vault_address()).unwrap();
+    assert_eq!(vault.locked_revealed_balance, Amount(40));
+}
+
+#[test]
+fn unlocking_more_than_locked_fails() {
+    let store = create_store();
+    let accounts_api = AccountsApi::new(&store);
+
+    accounts_api.lock_vault_balance(&vault_address(), Amount(10)).unwrap();
+    let err = accounts_api
+        .unlock_vault_balance(&vault_address(), Amount(11))
+        .unwrap_err();
+    assert!(matches!(err, AccountsApiError::UnlockExceedsLockedBalance { .. }));
+
+    let vault = accounts_api.get_vault(&&vault_address()).unwrap();
+    assert_eq!(vault.locked_revealed_balance, Amount(10));
+}
+
+#[test]
+fn remove_account_removes_its_vaults() {
+    let store = create_store();
+    let accounts_api = AccountsApi::new(&store);
+    let other_account: SubstateId = "component_1dc41b5cc74b36d696c7b140323a40a2f98b71df5d60e5a6bf4c1a07"
+        .parse()
+        .unwrap();
+    accounts_api
+        .add_account(Some("other"), &other_account, 1, false)
+        .unwrap();
+
+    // The default account cannot be removed
+    let err = accounts_api.remove_account(&account_address()).unwrap_err();
+    assert!(matches!(err, AccountsApiError::CannotRemoveDefaultAccount { .. }));
+    assert!(accounts_api.has_vault(&vault_address()).unwrap());
+
+    accounts_api.set_default_account(&other_account).unwrap();
+    let num_vaults = accounts_api.remove_account(&account_address()).unwrap();
+    assert_eq!(num_vaults, 1);
+    assert!(!accounts_api.has_account(&account_address()).unwrap());
+    assert!(!accounts_api.has_vault(&vault_address()).unwrap());
+    assert_eq!(accounts_api.count().unwrap(), 1);
+}
+
+#[test]
+fn vault_balance_histogram_counts_vaults_by_revealed_balance() {
+    let store = create_store();
+    let accounts_api = AccountsApi::new(&store);
+    let confidential_vault: SubstateId = "vault_1dc41b5cc74b36d696c7b140323a40a2f98b71df5d60e5a6bf4c1a07"
+        .parse()
+        .unwrap();
+    accounts_api
+        .add_vault(
+            account_address(),
+            confidential_vault,
+            other_resource_address(),
+            ResourceType::Confidential,
+            None,
+        )
+        .unwrap();
+
+    let histogram = accounts_api
+        .vault_balance_histogram(&[Amount(1000), Amount(10), Amount(100)])
+        .unwrap();
+    assert_eq!(histogram, vec![(Amount(10), 1), (Amount(100), 1), (Amount(1000), 0)]);
+}
+
+#[test]
+fn account_summary_aggregates_vaults() {
+    let store = create_store();
+    let accounts_api = AccountsApi::new(&store);
+
+    let summary = accounts_api.get_account_summary(&account_address()).unwrap();
+    assert_eq!(summary.account.address, account_address());
+    assert_eq!(summary.num_vaults, 1);
+    assert_eq!(summary.total_revealed_balance, Amount(100));
+    assert!(summary.last_activity.is_none());
+
+    let empty_account: SubstateId = "component_1dc41b5cc74b36d696c7b140323a40a2f98b71df5d60e5a6bf4c1a07"
+        .parse()
+        .unwrap();
+    accounts_api
+        .add_account(Some("empty"), &empty_account, 1, false)
+        .unwrap();
+    let summary = accounts_api.get_account_summary(&empty_account).unwrap();
+    assert_eq!(summary.num_vaults, 0);
+    assert_eq!(summary.total_revealed_balance, Amount::zero());
+    assert!(summary.last_activity.is_none());
+}
+
+#[test]
+fn account_summary_reports_the_latest_transaction_involving_the_account() {
+    let store = create_store();
+    let accounts_api = AccountsApi::new(&store);
+    let account_component = account_address().as_component_address().unwrap();
+
+    let transaction = Transaction::builder()
+        .call_method(account_component, "get_balances", vec![])
+        .build();
+    store
+        .with_write_tx(|tx| tx.transactions_insert(&transaction, &[], None, false))
+        .unwrap();
+    let stored = store.with_read_tx(|tx| tx.transactions_get(*transaction.id())).unwrap();
+
+    let summary = accounts_api.get_account_summary(&account_address()).unwrap();
+    assert_eq!(summary.last_activity, Some(stored.last_update_time));
+
+    // Transactions that do not involve the account are not counted
+    let other_account: SubstateId = "component_1dc41b5cc74b36d696c7b140323a40a2f98b71df5d60e5a6bf4c1a07"
+        .parse()
+        .unwrap();
+    accounts_api
+        .add_account(Some("other"), &other_account, 1, false)
+        .unwrap();
+    let summary = accounts_api.get_account_summary(&other_account).unwrap();
+    assert!(summary.last_activity.is_none());
+}
+
+#[test]
+fn get_by_key_index_range_returns_accounts_ordered_by_key_index() {
+    let store = create_store();
+    let accounts_api = AccountsApi::new(&store);
+    for (seed, key_index) in [(3u8, 7u64), (1, 5), (2, 6), (4, 10)] {
+        let address = format!(
+            "component_{}dc41b5cc74b36d696c7b140323a40a2f98b71df5d60e5a6bf4c1a07",
+            seed
+        )
+        .parse()
+        .unwrap();
+        accounts_api.add_account(None, &address, key_index, false).unwrap();
+    }
+
+    let accounts = accounts_api.get_by_key_index_range(5, 10).unwrap();
+    let key_indexes = accounts.iter().map(|a| a.key_index).collect::<Vec<_>>();
+    assert_eq!(key_indexes, vec![5, 6, 7]);
+
+    assert!(accounts_api.get_by_key_index_range(11, 20).unwrap().is_empty());
+}
+
+#[test]
+fn adding_a_second_vault_for_a_resource_fails() {
+    let store = create_store();
+    let accounts_api = AccountsApi::new(&store);
+    let other_vault: SubstateId = "vault_1dc41b5cc74b36d696c7b140323a40a2f98b71df5d60e5a6bf4c1a07"
+        .parse()
+        .unwrap();
+
+    let err = accounts_api
+        .add_vault(
+            account_address(),
+            other_vault.clone(),
+            XTR,
+            ResourceType::Confidential,
+            None,
+        )
+        .unwrap_err();
+    assert!(matches!(err, AccountsApiError::DuplicateResourceVault { .. }));
+    assert!(!accounts_api.has_vault(&other_vault).unwrap());
+    assert_eq!(accounts_api.get_vaults_by_account(&account_address()).unwrap().len(), 1);
+}
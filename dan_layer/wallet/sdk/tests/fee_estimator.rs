@@ -0,0 +1,105 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_dan_wallet_sdk::apis::fee_estimator::FeeEstimator;
+use tari_engine_types::fees::FeeTable;
+use tari_template_lib::{
+    args,
+    constants::CONFIDENTIAL_TARI_RESOURCE_ADDRESS,
+    models::{Amount, ComponentAddress, ObjectKey},
+};
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+
+fn fee_table() -> FeeTable {
+    FeeTable {
+        per_module_call_cost: 1,
+        per_byte_storage_cost: 1,
+        per_event_cost: 1,
+        per_log_cost: 1,
+    }
+}
+
+fn component_address(n: u8) -> ComponentAddress {
+    ComponentAddress::new(ObjectKey::from_array([n; ObjectKey::LENGTH]))
+}
+
+#[test]
+fn estimate_grows_with_instructions_and_inputs() {
+    let estimator = FeeEstimator::new(fee_table());
+
+    let empty = estimator.estimate(&Transaction::builder().build());
+    assert_eq!(empty.transaction_fee, Amount::zero());
+
+    let one_call = Transaction::builder()
+        .fee_transaction_pay_from_component(component_address(1), Amount(1000))
+        .call_method(component_address(1), "withdraw", vec![])
+        .build();
+    let one_call_estimate = estimator.estimate(&one_call);
+    assert!(one_call_estimate.transaction_fee > Amount::zero());
+
+    let with_input = Transaction::builder()
+        .fee_transaction_pay_from_component(component_address(1), Amount(1000))
+        .call_method(component_address(1), "withdraw", vec![])
+        .add_input(component_address(2))
+        .build();
+    assert!(estimator.estimate(&with_input).transaction_fee > one_call_estimate.transaction_fee);
+}
+
+#[test]
+fn leader_fee_and_burn_account_for_the_whole_fee() {
+    let transaction = Transaction::builder()
+        .fee_transaction_pay_from_component(component_address(1), Amount(1000))
+        .call_method(component_address(1), "withdraw", vec![])
+        .call_method(component_address(2), "deposit", vec![])
+        .build();
+
+    let estimate = FeeEstimator::new(fee_table()).estimate(&transaction);
+    assert_eq!(estimate.leader_fee + estimate.exhaust_burn, estimate.transaction_fee);
+    assert!(estimate.exhaust_burn > Amount::zero());
+}
+
+#[test]
+fn zero_rated_fee_table_estimates_no_fees() {
+    let transaction = Transaction::builder()
+        .fee_transaction_pay_from_component(component_address(1), Amount(1000))
+        .call_method(component_address(1), "withdraw", vec![])
+        .build();
+
+    let estimate = FeeEstimator::new(FeeTable::zero_rated()).estimate(&transaction);
+    assert_eq!(estimate.transaction_fee, Amount::zero());
+}
+
+#[test]
+fn estimate_is_an_upper_bound_of_the_fees_charged_by_the_engine() {
+    let mut test = TemplateTest::new(Vec::<&str>::new());
+    let (sender, sender_proof, sender_key) = test.create_funded_account();
+    let (receiver, _, _) = test.create_funded_account();
+    test.enable_fees();
+
+    let transaction = Transaction::builder()
+        .fee_transaction_pay_from_component(sender, Amount(10_000))
+        .call_method(sender, "withdraw", args![
+            CONFIDENTIAL_TARI_RESOURCE_ADDRESS,
+            Amount(100)
+        ])
+        .put_last_instruction_output_on_workspace("bucket")
+        .call_method(receiver, "deposit", args![Workspace("bucket")])
+        .add_input(sender)
+        .add_input(receiver)
+        .sign(&sender_key)
+        .build();
+
+    let estimate = FeeEstimator::new(test.fee_table().clone()).estimate(&transaction);
+    let result = test.execute_expect_success(transaction, vec![sender_proof]);
+    let fee_receipt = result.finalize.fee_receipt;
+
+    assert!(!fee_receipt.cost_breakdown.is_empty());
+    assert!(
+        estimate.transaction_fee >= fee_receipt.total_fees_charged(),
+        "Estimated {} but the engine charged {} ({:?})",
+        estimate.transaction_fee,
+        fee_receipt.total_fees_charged(),
+        fee_receipt.cost_breakdown
+    );
+}
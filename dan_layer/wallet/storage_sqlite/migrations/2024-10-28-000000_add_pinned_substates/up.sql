@@ -0,0 +1,15 @@
+-- Copies of substate versions that the user has pinned. Pinned versions are kept even after the substate is downed or
+-- replaced by a newer version in the substates table.
+CREATE TABLE pinned_substates
+(
+    id               INTEGER  NOT NULL PRIMARY KEY AUTOINCREMENT,
+    module_name      TEXT     NULL,
+    address          TEXT     NOT NULL,
+    parent_address   TEXT     NULL,
+    version          INTEGER  NOT NULL,
+    transaction_hash TEXT     NOT NULL,
+    template_address TEXT     NULL,
+    created_at       DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX pinned_substates_uniq_address_version ON pinned_substates (address, version);
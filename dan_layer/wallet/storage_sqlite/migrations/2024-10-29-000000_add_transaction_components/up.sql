@@ -0,0 +1,26 @@
+-- The components (e.g. accounts) that each transaction calls or takes as an input. This allows the transactions of an
+-- account to be found without scanning the serialized instructions.
+CREATE TABLE transaction_components
+(
+    id                INTEGER  NOT NULL PRIMARY KEY AUTOINCREMENT,
+    transaction_hash  TEXT     NOT NULL,
+    component_address TEXT     NOT NULL,
+    created_at        DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX transaction_components_uniq_transaction_hash_component_address ON transaction_components (transaction_hash, component_address);
+CREATE INDEX transaction_components_idx_component_address ON transaction_components (component_address);
+
+-- Backfill from the instructions and inputs of existing transactions
+INSERT OR IGNORE INTO transaction_components (transaction_hash, component_address)
+SELECT t.hash, json_extract(i.value, '$.CallMethod.component_address')
+FROM transactions t, json_each(t.instructions) i
+WHERE json_extract(i.value, '$.CallMethod.component_address') IS NOT NULL
+UNION
+SELECT t.hash, json_extract(i.value, '$.CallMethod.component_address')
+FROM transactions t, json_each(t.fee_instructions) i
+WHERE json_extract(i.value, '$.CallMethod.component_address') IS NOT NULL
+UNION
+SELECT t.hash, json_extract(i.value, '$.substate_id')
+FROM transactions t, json_each(t.inputs) i
+WHERE json_extract(i.value, '$.substate_id') LIKE 'component_%';
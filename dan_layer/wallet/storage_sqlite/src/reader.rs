@@ -4,6 +4,7 @@
 use std::{collections::HashMap, str::FromStr, sync::MutexGuard};
 
 use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::NaiveDateTime;
 use diesel::{
     dsl::sum,
     sql_query,
@@ -30,6 +31,7 @@ use tari_dan_wallet_sdk::{
         SubstateModel,
         TransactionStatus,
         VaultModel,
+        VersionedSubstateId,
         WalletTransaction,
     },
     storage::{WalletStorageError, WalletStoreReader},
@@ -39,7 +41,7 @@ use tari_engine_types::{
     TemplateAddress,
 };
 use tari_template_lib::{
-    models::{ResourceAddress, VaultId},
+    models::{Amount, ResourceAddress, VaultId},
     prelude::{ComponentAddress, NonFungibleId},
 };
 use tari_transaction::TransactionId;
@@ -208,7 +210,7 @@ impl WalletStoreReader for ReadTransaction<'_> {
         status: Option<TransactionStatus>,
         component: Option<ComponentAddress>,
     ) -> Result<Vec<WalletTransaction>, WalletStorageError> {
-        use crate::schema::transactions;
+        use crate::schema::{transaction_components, transactions};
 
         let mut rows = transactions::table.into_boxed().filter(transactions::dry_run.eq(false));
         if let Some(status) = status {
@@ -216,9 +218,11 @@ impl WalletStoreReader for ReadTransaction<'_> {
         }
         if let Some(component) = component {
             rows = rows.filter(
-                transactions::instructions
-                    .like(format!("%{}%", component))
-                    .or(transactions::fee_instructions.like(format!("%{}%", component))),
+                transactions::hash.eq_any(
+                    transaction_components::table
+                        .select(transaction_components::transaction_hash)
+                        .filter(transaction_components::component_address.eq(component.to_string())),
+                ),
             );
         }
         let rows = rows
@@ -229,6 +233,30 @@ impl WalletStoreReader for ReadTransaction<'_> {
         rows.into_iter().map(|row| row.try_into_wallet_transaction()).collect()
     }
 
+    fn transactions_get_last_updated_at(
+        &mut self,
+        component: &ComponentAddress,
+    ) -> Result<Option<NaiveDateTime>, WalletStorageError> {
+        use crate::schema::{transaction_components, transactions};
+
+        let updated_at = transactions::table
+            .select(transactions::updated_at)
+            .filter(transactions::dry_run.eq(false))
+            .filter(
+                transactions::hash.eq_any(
+                    transaction_components::table
+                        .select(transaction_components::transaction_hash)
+                        .filter(transaction_components::component_address.eq(component.to_string())),
+                ),
+            )
+            .order(transactions::updated_at.desc())
+            .first::<NaiveDateTime>(self.connection())
+            .optional()
+            .map_err(|e| WalletStorageError::general("transactions_get_last_updated_at", e))?;
+
+        Ok(updated_at)
+    }
+
     // -------------------------------- Substates -------------------------------- //
     fn substates_get(&mut self, address: &SubstateId) -> Result<SubstateModel, WalletStorageError> {
         use crate::schema::substates;
@@ -297,6 +325,30 @@ impl WalletStoreReader for ReadTransaction<'_> {
         rows.into_iter().map(|rec| rec.try_to_record()).collect()
     }
 
+    fn pinned_substates_get_all(&mut self) -> Result<Vec<SubstateModel>, WalletStorageError> {
+        use crate::schema::pinned_substates;
+
+        let rows = pinned_substates::table
+            .order_by(pinned_substates::id.asc())
+            .get_results::<models::Substate>(self.connection())
+            .map_err(|e| WalletStorageError::general("pinned_substates_get_all", e))?;
+
+        rows.into_iter().map(|rec| rec.try_to_record()).collect()
+    }
+
+    fn pinned_substates_exists(&mut self, address: &VersionedSubstateId) -> Result<bool, WalletStorageError> {
+        use crate::schema::pinned_substates;
+
+        let count = pinned_substates::table
+            .filter(pinned_substates::address.eq(address.substate_id.to_string()))
+            .filter(pinned_substates::version.eq(address.version as i32))
+            .count()
+            .first::<i64>(self.connection())
+            .map_err(|e| WalletStorageError::general("pinned_substates_exists", e))?;
+
+        Ok(count > 0)
+    }
+
     // -------------------------------- Accounts -------------------------------- //
     fn accounts_get(&mut self, address: &SubstateId) -> Result<Account, WalletStorageError> {
         use crate::schema::accounts;
@@ -342,6 +394,29 @@ impl WalletStoreReader for ReadTransaction<'_> {
         Ok(accs)
     }
 
+    fn accounts_get_by_key_index_range(&mut self, start: u64, end: u64) -> Result<Vec<Account>, WalletStorageError> {
+        use crate::schema::accounts;
+
+        let start = i64::try_from(start).unwrap_or(i64::MAX);
+        let end = i64::try_from(end).unwrap_or(i64::MAX);
+        let rows = accounts::table
+            .filter(accounts::owner_key_index.ge(start))
+            .filter(accounts::owner_key_index.lt(end))
+            .order_by(accounts::owner_key_index.asc())
+            .load::<models::Account>(self.connection())
+            .map_err(|e| WalletStorageError::general("accounts_get_by_key_index_range", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                row.try_into().map_err(|e| WalletStorageError::DecodingError {
+                    operation: "accounts_get_by_key_index_range",
+                    item: "account",
+                    details: format!("Failed to convert SQL record to Account: {}", e),
+                })
+            })
+            .collect()
+    }
+
     fn accounts_count(&mut self) -> Result<u64, WalletStorageError> {
         use crate::schema::accounts;
 
@@ -541,6 +616,17 @@ impl WalletStoreReader for ReadTransaction<'_> {
         Ok(vaults)
     }
 
+    fn vaults_get_all_revealed_balances(&mut self) -> Result<Vec<Amount>, WalletStorageError> {
+        use crate::schema::vaults;
+
+        let balances = vaults::table
+            .select(vaults::revealed_balance)
+            .load::<i64>(self.connection())
+            .map_err(|e| WalletStorageError::general("vaults_get_all_revealed_balances", e))?;
+
+        Ok(balances.into_iter().map(Amount::new).collect())
+    }
+
     // -------------------------------- Outputs -------------------------------- //
     fn outputs_get_unspent_balance(&mut self, vault_address: &SubstateId) -> Result<u64, WalletStorageError> {
         use crate::schema::{outputs, vaults};
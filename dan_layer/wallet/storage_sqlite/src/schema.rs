@@ -76,6 +76,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    pinned_substates (id) {
+        id -> Integer,
+        module_name -> Nullable<Text>,
+        address -> Text,
+        parent_address -> Nullable<Text>,
+        version -> Integer,
+        transaction_hash -> Text,
+        template_address -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     proofs (id) {
         id -> Integer,
@@ -100,6 +113,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    transaction_components (id) {
+        id -> Integer,
+        transaction_hash -> Text,
+        component_address -> Text,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     transactions (id) {
         id -> Integer,
@@ -154,8 +176,10 @@ diesel::allow_tables_to_appear_in_same_query!(
     key_manager_states,
     non_fungible_tokens,
     outputs,
+    pinned_substates,
     proofs,
     substates,
+    transaction_components,
     transactions,
     vaults,
 );
@@ -2,6 +2,7 @@
 //   SPDX-License-Identifier: BSD-3-Clause
 
 use std::{
+    collections::HashSet,
     ops::{Add, Deref, DerefMut, Sub},
     str::FromStr,
     sync::MutexGuard,
@@ -29,7 +30,12 @@ use tari_dan_wallet_sdk::{
     },
     storage::{WalletStorageError, WalletStoreReader, WalletStoreWriter},
 };
-use tari_engine_types::{commit_result::FinalizeResult, substate::SubstateId, TemplateAddress};
+use tari_engine_types::{
+    commit_result::FinalizeResult,
+    instruction::Instruction,
+    substate::SubstateId,
+    TemplateAddress,
+};
 use tari_template_lib::models::{Amount, EncryptedData};
 use tari_transaction::{SubstateRequirement, Transaction, TransactionId};
 use tari_utilities::hex::Hex;
@@ -269,7 +275,7 @@ impl WalletStoreWriter for WriteTransaction<'_> {
         new_account_info: Option<&NewAccountInfo>,
         is_dry_run: bool,
     ) -> Result<(), WalletStorageError> {
-        use crate::schema::transactions;
+        use crate::schema::{transaction_components, transactions};
 
         diesel::insert_into(transactions::table)
             .values((
@@ -286,6 +292,38 @@ impl WalletStoreWriter for WriteTransaction<'_> {
             .execute(self.connection())
             .map_err(|e| WalletStorageError::general("transactions_insert", e))?;
 
+        // Record the components that the transaction involves so that the transactions of an account can be looked up
+        let component_addresses = transaction
+            .fee_instructions()
+            .iter()
+            .chain(transaction.instructions())
+            .filter_map(|instruction| match instruction {
+                Instruction::CallMethod { component_address, .. } => Some(*component_address),
+                _ => None,
+            })
+            .chain(
+                transaction
+                    .all_inputs_iter()
+                    .filter_map(|input| input.substate_id().as_component_address()),
+            )
+            .collect::<HashSet<_>>();
+        let transaction_hash = transaction.id().to_string();
+        let values = component_addresses
+            .iter()
+            .map(|address| {
+                (
+                    transaction_components::transaction_hash.eq(&transaction_hash),
+                    transaction_components::component_address.eq(address.to_string()),
+                )
+            })
+            .collect::<Vec<_>>();
+        if !values.is_empty() {
+            diesel::insert_or_ignore_into(transaction_components::table)
+                .values(values)
+                .execute(self.connection())
+                .map_err(|e| WalletStorageError::general("transactions_insert", e))?;
+        }
+
         Ok(())
     }
 
@@ -408,6 +446,46 @@ impl WalletStoreWriter for WriteTransaction<'_> {
         Ok(substate)
     }
 
+    fn pinned_substates_insert(&mut self, substate: &SubstateModel) -> Result<(), WalletStorageError> {
+        use crate::schema::pinned_substates;
+
+        diesel::insert_into(pinned_substates::table)
+            .values((
+                pinned_substates::module_name.eq(&substate.module_name),
+                pinned_substates::address.eq(substate.address.substate_id.to_string()),
+                pinned_substates::parent_address.eq(substate.parent_address.as_ref().map(|a| a.to_string())),
+                pinned_substates::version.eq(substate.address.version as i32),
+                pinned_substates::transaction_hash.eq(substate.transaction_hash.to_hex()),
+                pinned_substates::template_address.eq(substate.template_address.map(|a| a.to_string())),
+            ))
+            .on_conflict((pinned_substates::address, pinned_substates::version))
+            .do_nothing()
+            .execute(self.connection())
+            .map_err(|e| WalletStorageError::general("pinned_substates_insert", e))?;
+
+        Ok(())
+    }
+
+    fn pinned_substates_remove(&mut self, address: &VersionedSubstateId) -> Result<(), WalletStorageError> {
+        use crate::schema::pinned_substates;
+
+        let num_rows = diesel::delete(pinned_substates::table)
+            .filter(pinned_substates::address.eq(address.substate_id.to_string()))
+            .filter(pinned_substates::version.eq(address.version as i32))
+            .execute(self.connection())
+            .map_err(|e| WalletStorageError::general("pinned_substates_remove", e))?;
+
+        if num_rows == 0 {
+            return Err(WalletStorageError::NotFound {
+                operation: "pinned_substates_remove",
+                entity: "pinned substate".to_string(),
+                key: address.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
     // -------------------------------- Accounts -------------------------------- //
 
     fn accounts_set_default(&mut self, address: &SubstateId) -> Result<(), WalletStorageError> {
@@ -486,6 +564,50 @@ impl WalletStoreWriter for WriteTransaction<'_> {
         Ok(())
     }
 
+    fn accounts_remove(&mut self, address: &SubstateId) -> Result<usize, WalletStorageError> {
+        use crate::schema::{accounts, non_fungible_tokens, outputs, proofs, vaults};
+
+        let account_id = accounts::table
+            .select(accounts::id)
+            .filter(accounts::address.eq(address.to_string()))
+            .first::<i32>(self.connection())
+            .optional()
+            .map_err(|e| WalletStorageError::general("accounts_remove", e))?
+            .ok_or_else(|| WalletStorageError::NotFound {
+                operation: "accounts_remove",
+                entity: "account".to_string(),
+                key: address.to_string(),
+            })?;
+
+        let vault_ids = vaults::table
+            .select(vaults::id)
+            .filter(vaults::account_id.eq(account_id))
+            .load::<i32>(self.connection())
+            .map_err(|e| WalletStorageError::general("accounts_remove", e))?;
+
+        diesel::delete(non_fungible_tokens::table.filter(non_fungible_tokens::vault_id.eq_any(&vault_ids)))
+            .execute(self.connection())
+            .map_err(|e| WalletStorageError::general("accounts_remove non_fungible_tokens", e))?;
+
+        diesel::delete(outputs::table.filter(outputs::account_id.eq(account_id)))
+            .execute(self.connection())
+            .map_err(|e| WalletStorageError::general("accounts_remove outputs", e))?;
+
+        diesel::delete(proofs::table.filter(proofs::account_id.eq(account_id)))
+            .execute(self.connection())
+            .map_err(|e| WalletStorageError::general("accounts_remove proofs", e))?;
+
+        let num_vaults = diesel::delete(vaults::table.filter(vaults::account_id.eq(account_id)))
+            .execute(self.connection())
+            .map_err(|e| WalletStorageError::general("accounts_remove vaults", e))?;
+
+        diesel::delete(accounts::table.filter(accounts::id.eq(account_id)))
+            .execute(self.connection())
+            .map_err(|e| WalletStorageError::general("accounts_remove", e))?;
+
+        Ok(num_vaults)
+    }
+
     fn vaults_insert(&mut self, vault: VaultModel) -> Result<(), WalletStorageError> {
         use crate::schema::{accounts, vaults};
 
@@ -542,6 +664,30 @@ impl WalletStoreWriter for WriteTransaction<'_> {
         Ok(())
     }
 
+    fn vaults_set_locked_revealed_balance(
+        &mut self,
+        vault_address: &SubstateId,
+        locked_revealed_balance: Amount,
+    ) -> Result<(), WalletStorageError> {
+        use crate::schema::vaults;
+
+        let num_rows = diesel::update(vaults::table)
+            .set(vaults::locked_revealed_balance.eq(locked_revealed_balance.value()))
+            .filter(vaults::address.eq(vault_address.to_string()))
+            .execute(self.connection())
+            .map_err(|e| WalletStorageError::general("vaults_set_locked_revealed_balance", e))?;
+
+        if num_rows == 0 {
+            return Err(WalletStorageError::NotFound {
+                operation: "vaults_set_locked_revealed_balance",
+                entity: "vault".to_string(),
+                key: vault_address.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
     fn vaults_lock_revealed_funds(
         &mut self,
         proof_id: ConfidentialProofId,
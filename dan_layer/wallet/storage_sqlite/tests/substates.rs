@@ -57,3 +57,36 @@ fn get_and_insert_substates() {
     assert_eq!(returned.address.substate_id, child_address);
     assert_eq!(returned.address.version, 0);
 }
+
+#[test]
+fn pinned_substates_are_kept_after_the_substate_is_removed() {
+    let db = SqliteWalletStore::try_open(":memory:").unwrap();
+    db.run_migrations().unwrap();
+    let mut tx = db.create_write_tx().unwrap();
+    let address = SubstateId::from_str("component_1f019e4d434cbf2b99c0af89ee212f422af86de7280a169d2e392dfb").unwrap();
+    let versioned_address = VersionedSubstateId {
+        substate_id: address.clone(),
+        version: 0,
+    };
+    tx.substates_upsert_root(TransactionId::default(), versioned_address.clone(), None, None)
+        .unwrap();
+    assert!(!tx.pinned_substates_exists(&versioned_address).unwrap());
+
+    let substate = tx.substates_get(&address).unwrap();
+    tx.pinned_substates_insert(&substate).unwrap();
+    // Pinning again is a no-op
+    tx.pinned_substates_insert(&substate).unwrap();
+    assert!(tx.pinned_substates_exists(&versioned_address).unwrap());
+
+    tx.substates_remove(&address).unwrap();
+    let pinned = tx.pinned_substates_get_all().unwrap();
+    assert_eq!(pinned.len(), 1);
+    assert_eq!(pinned[0].address.substate_id, address);
+    assert_eq!(pinned[0].address.version, 0);
+    assert_eq!(pinned[0].transaction_hash, substate.transaction_hash);
+
+    tx.pinned_substates_remove(&versioned_address).unwrap();
+    assert!(tx.pinned_substates_get_all().unwrap().is_empty());
+    tx.pinned_substates_remove(&versioned_address).unwrap_err();
+    tx.commit().unwrap();
+}
@@ -140,7 +140,7 @@ async fn indexer_scans_network_events(
 
     let mut graphql_client = indexer.get_graphql_indexer_client().await;
     let query = format!(
-        r#"{{ getEventsForSubstate(substateId: "{}", version: {}) {{ substateId, templateAddress, txHash, topic, payload }} }}"#,
+        r#"{{ getEventsForSubstate(substateId: "{}", fromVersion: {}) {{ substateId, templateAddress, txHash, topic, payload }} }}"#,
         component_address.substate_id,
         component_address.version.unwrap()
     );